@@ -4,6 +4,7 @@ use cesride::{
     data::{dat, Value},
     matter, Matter, Number, Prefixer, Saider, Serder, Tholder,
 };
+use keride::eventing::witnessing::ample;
 
 use super::endorsement::Seal;
 
@@ -405,41 +406,3 @@ pub(crate) fn interact(
 
     Serder::new_with_ked(&ked, None, None)
 }
-
-fn ample(n: u128, f: Option<u128>, weak: Option<bool>) -> Result<u128> {
-    let weak = weak.unwrap_or(true);
-    let n = if n > 0 { n } else { 0 };
-    if let Some(f) = f {
-        let f = if f > 0 { f } else { 0 };
-        let m1 = (n + f + 2) / 2;
-        let m2 = if n - f > 0 { n - f } else { 0 };
-
-        if m2 < m1 && n > 0 {
-            return err!(Error::Value);
-        }
-
-        if weak {
-            match [n, m1, m2].iter().min() {
-                Some(x) => Ok(*x),
-                None => unreachable!(),
-            }
-        } else {
-            Ok(std::cmp::min(n, std::cmp::max(m1, m2)))
-        }
-    } else {
-        let f1 = std::cmp::max(1, std::cmp::max(0, n - 1) / 3);
-        let f2 = std::cmp::max(1, (std::cmp::max(0, n - 1) + 2) / 3);
-
-        if weak {
-            match [n, (n + f1 + 3) / 2, (n + f2 + 3) / 2].iter().min() {
-                Some(x) => Ok(*x),
-                None => unreachable!(),
-            }
-        } else {
-            match [0, n - f1, (n + f1 + 3) / 2].iter().max() {
-                Some(x) => Ok(std::cmp::min(n, *x)),
-                None => unreachable!(),
-            }
-        }
-    }
-}
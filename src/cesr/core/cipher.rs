@@ -0,0 +1,118 @@
+use crate::cesr::core::matter::{tables as matter, Matter};
+use crate::error::{err, Error, Result};
+
+/// A sealed box of the qb64 of a seed or salt, encrypted to an X25519 public key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cipher {
+    raw: Vec<u8>,
+    code: String,
+    size: u32,
+}
+
+impl Default for Cipher {
+    fn default() -> Self {
+        Cipher { raw: vec![], code: matter::Codex::X25519_Cipher_Seed.to_string(), size: 0 }
+    }
+}
+
+fn validate_code(code: &str) -> Result<()> {
+    const CODES: &[&str] = &[matter::Codex::X25519_Cipher_Seed, matter::Codex::X25519_Cipher_Salt];
+
+    if !CODES.contains(&code) {
+        return err!(Error::UnexpectedCode(code.to_string()));
+    }
+
+    Ok(())
+}
+
+impl Cipher {
+    /// Without a code, the code of raw ciphertext is inferred from its size.
+    pub fn new(
+        code: Option<&str>,
+        raw: Option<&[u8]>,
+        qb64b: Option<&[u8]>,
+        qb64: Option<&str>,
+        qb2: Option<&[u8]>,
+    ) -> Result<Self> {
+        let code = match (code, raw) {
+            (None, Some(raw))
+                if raw.len() == matter::raw_size(matter::Codex::X25519_Cipher_Salt)? as usize =>
+            {
+                Some(matter::Codex::X25519_Cipher_Salt)
+            }
+            (None, Some(_)) => Some(matter::Codex::X25519_Cipher_Seed),
+            (code, _) => code,
+        };
+
+        let cipher: Self = Matter::new(code, raw, qb64b, qb64, qb2)?;
+        validate_code(&cipher.code())?;
+        Ok(cipher)
+    }
+
+    pub fn new_with_raw(raw: &[u8], code: Option<&str>) -> Result<Self> {
+        Self::new(code, Some(raw), None, None, None)
+    }
+
+    pub fn new_with_qb64b(qb64b: &[u8]) -> Result<Self> {
+        Self::new(None, None, Some(qb64b), None, None)
+    }
+
+    pub fn new_with_qb64(qb64: &str) -> Result<Self> {
+        Self::new(None, None, None, Some(qb64), None)
+    }
+
+    pub fn new_with_qb2(qb2: &[u8]) -> Result<Self> {
+        Self::new(None, None, None, None, Some(qb2))
+    }
+}
+
+impl Matter for Cipher {
+    fn code(&self) -> String {
+        self.code.clone()
+    }
+
+    fn raw(&self) -> Vec<u8> {
+        self.raw.clone()
+    }
+
+    fn size(&self) -> u32 {
+        self.size
+    }
+
+    fn set_code(&mut self, code: &str) {
+        self.code = code.to_string();
+    }
+
+    fn set_raw(&mut self, raw: &[u8]) {
+        self.raw = raw.to_vec();
+    }
+
+    fn set_size(&mut self, size: u32) {
+        self.size = size;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cesr::core::cipher::Cipher;
+    use crate::cesr::core::matter::{tables as matter, Matter};
+
+    #[test]
+    fn new() {
+        let raw = vec![1u8; 92];
+        let cipher = Cipher::new_with_raw(&raw, None).unwrap();
+        assert_eq!(cipher.code(), matter::Codex::X25519_Cipher_Seed);
+        assert_eq!(cipher.qb64().unwrap().len(), 124);
+
+        let cipher = Cipher::new_with_raw(&raw[..72], None).unwrap();
+        assert_eq!(cipher.code(), matter::Codex::X25519_Cipher_Salt);
+        assert_eq!(cipher.qb64().unwrap().len(), 100);
+
+        assert_eq!(Cipher::new_with_qb64(&cipher.qb64().unwrap()).unwrap(), cipher);
+        assert_eq!(Cipher::new_with_qb64b(&cipher.qb64b().unwrap()).unwrap(), cipher);
+        assert_eq!(Cipher::new_with_qb2(&cipher.qb2().unwrap()).unwrap(), cipher);
+
+        assert!(Cipher::new_with_raw(&raw[..72], Some(matter::Codex::X25519_Cipher_Seed)).is_err());
+        assert!(Cipher::new_with_raw(&raw[..32], Some(matter::Codex::Blake3_256)).is_err());
+    }
+}
@@ -94,9 +94,18 @@ pub mod Ids {
     pub const s: &str = "s";
     pub const f: &str = "f";
     pub const v: &str = "v";
+    pub const p: &str = "p";
+    pub const c: &str = "c";
+    pub const r: &str = "r";
+    pub const q: &str = "q";
     pub const kt: &str = "kt";
     pub const nt: &str = "nt";
+    pub const bt: &str = "bt";
+    pub const br: &str = "br";
+    pub const ba: &str = "ba";
     pub const di: &str = "di";
+    pub const dt: &str = "dt";
+    pub const rr: &str = "rr";
 }
 
 const REVER_STRING: &str = "(?P<ident>[A-Z]{4})(?P<major>[0-9a-f])(?P<minor>[0-9a-f])(?P<kind>[A-Z]{4})(?P<size>[0-9a-f]{6})_";
@@ -0,0 +1,200 @@
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::cesr::core::{
+    cipher::Cipher,
+    encrypter::Encrypter,
+    matter::{tables as matter, Matter},
+    salter::Salter,
+};
+use crate::cesr::crypto::encrypt;
+use crate::error::{err, Error, Result};
+use crate::signing::Signer;
+
+/// An X25519 private decryption key that opens `Cipher`s sealed to its public key.
+#[derive(Debug, Clone, PartialEq, ZeroizeOnDrop)]
+pub struct Decrypter {
+    raw: Vec<u8>,
+    #[zeroize(skip)]
+    code: String,
+    #[zeroize(skip)]
+    size: u32,
+}
+
+impl Default for Decrypter {
+    fn default() -> Self {
+        Decrypter { raw: vec![], code: matter::Codex::X25519_Private.to_string(), size: 0 }
+    }
+}
+
+fn validate_code(code: &str) -> Result<()> {
+    if code != matter::Codex::X25519_Private {
+        return err!(Error::UnexpectedCode(code.to_string()));
+    }
+
+    Ok(())
+}
+
+impl Decrypter {
+    pub fn new(
+        code: Option<&str>,
+        raw: Option<&[u8]>,
+        qb64b: Option<&[u8]>,
+        qb64: Option<&str>,
+        qb2: Option<&[u8]>,
+    ) -> Result<Self> {
+        let code =
+            if raw.is_some() { Some(code.unwrap_or(matter::Codex::X25519_Private)) } else { code };
+        let decrypter: Self = Matter::new(code, raw, qb64b, qb64, qb2)?;
+        validate_code(&decrypter.code())?;
+        Ok(decrypter)
+    }
+
+    pub fn new_with_raw(raw: &[u8], code: Option<&str>) -> Result<Self> {
+        Self::new(code, Some(raw), None, None, None)
+    }
+
+    pub fn new_with_qb64b(qb64b: &[u8]) -> Result<Self> {
+        Self::new(None, None, Some(qb64b), None, None)
+    }
+
+    pub fn new_with_qb64(qb64: &str) -> Result<Self> {
+        Self::new(None, None, None, Some(qb64), None)
+    }
+
+    pub fn new_with_qb2(qb2: &[u8]) -> Result<Self> {
+        Self::new(None, None, None, None, Some(qb2))
+    }
+
+    /// Converts the Ed25519 seed of `signer` into its X25519 decryption key.
+    pub fn new_with_signer(signer: &Signer) -> Result<Self> {
+        let mut raw = encrypt::decryption_key(&signer.code(), &signer.raw())?;
+        let decrypter = Self::new_with_raw(&raw, None);
+        raw.zeroize();
+        decrypter
+    }
+
+    /// The public encryption key of this decryption key.
+    pub fn encrypter(&self) -> Result<Encrypter> {
+        Encrypter::new_with_raw(&encrypt::public_key(&self.raw)?, None)
+    }
+
+    /// Opens `cipher`, returning the qb64b of the seed or salt it seals.
+    pub fn decrypt(&self, cipher: &Cipher) -> Result<Vec<u8>> {
+        encrypt::unseal(&self.raw, &cipher.raw())
+    }
+
+    /// Opens a seed cipher into its signer.
+    pub fn decrypt_signer(&self, cipher: &Cipher, transferable: Option<bool>) -> Result<Signer> {
+        if cipher.code() != matter::Codex::X25519_Cipher_Seed {
+            return err!(Error::UnexpectedCode(cipher.code()));
+        }
+
+        let mut qb64b = self.decrypt(cipher)?;
+        let signer = Signer::new_with_qb64b(&qb64b, transferable);
+        qb64b.zeroize();
+        signer
+    }
+
+    /// Opens a salt cipher into its salter.
+    pub fn decrypt_salter(&self, cipher: &Cipher, tier: Option<&str>) -> Result<Salter> {
+        if cipher.code() != matter::Codex::X25519_Cipher_Salt {
+            return err!(Error::UnexpectedCode(cipher.code()));
+        }
+
+        let mut qb64b = self.decrypt(cipher)?;
+        let salter = Salter::new_with_qb64b(&qb64b, tier);
+        qb64b.zeroize();
+        salter
+    }
+}
+
+impl Matter for Decrypter {
+    fn code(&self) -> String {
+        self.code.clone()
+    }
+
+    fn raw(&self) -> Vec<u8> {
+        self.raw.clone()
+    }
+
+    fn size(&self) -> u32 {
+        self.size
+    }
+
+    fn set_code(&mut self, code: &str) {
+        self.code = code.to_string();
+    }
+
+    fn set_raw(&mut self, raw: &[u8]) {
+        self.raw = raw.to_vec();
+    }
+
+    fn set_size(&mut self, size: u32) {
+        self.size = size;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cesr::core::{
+        cipher::Cipher,
+        common::Tierage,
+        decrypter::Decrypter,
+        encrypter::Encrypter,
+        matter::{tables as matter, Matter},
+        salter::Salter,
+    };
+    use crate::signing::Signer;
+    use hex_literal::hex;
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let seed = hex!("183b30c40f2a7646fae3a24565651f966fce294785e35886da04f0dcde06c02b");
+        let signer = Signer::new_with_raw(&seed, None, None).unwrap();
+        assert_eq!(signer.qb64().unwrap(), "ABg7MMQPKnZG-uOiRWVlH5ZvzilHheNYhtoE8NzeBsAr");
+
+        let encrypter = Encrypter::new_with_verfer(&signer.verfer()).unwrap();
+        assert_eq!(encrypter.code(), matter::Codex::X25519);
+        assert_eq!(encrypter.qb64().unwrap(), "CDGt7nGkOK7DGutg--9LCWlgfzkpT1TCLJpDRccLOzI9");
+        assert!(encrypter.verify_signer(&signer).unwrap());
+        assert!(!encrypter.verify_signer(&Signer::new_with_defaults(None, None).unwrap()).unwrap());
+
+        let decrypter = Decrypter::new_with_signer(&signer).unwrap();
+        assert_eq!(decrypter.code(), matter::Codex::X25519_Private);
+        assert_eq!(decrypter.qb64().unwrap(), "OLDi4kUqfOozp67rbWzsfy9ML27TDsqq56Io2eycu2dY");
+        assert_eq!(decrypter.encrypter().unwrap(), encrypter);
+
+        // seeds
+        let cipher = encrypter.encrypt_signer(&signer).unwrap();
+        assert_eq!(cipher.code(), matter::Codex::X25519_Cipher_Seed);
+        assert_eq!(cipher.qb64().unwrap().len(), 124);
+        let cipher = Cipher::new_with_qb64(&cipher.qb64().unwrap()).unwrap();
+        assert_eq!(decrypter.decrypt(&cipher).unwrap(), signer.qb64b().unwrap());
+        let decrypted = decrypter.decrypt_signer(&cipher, Some(false)).unwrap();
+        assert_eq!(decrypted.raw(), signer.raw());
+        assert_eq!(decrypted.verfer().code(), matter::Codex::Ed25519N);
+        assert!(decrypter.decrypt_salter(&cipher, None).is_err());
+
+        // salts
+        let salter = Salter::new_with_raw(b"0123456789abcdef", None, Some(Tierage::min)).unwrap();
+        let cipher = encrypter.encrypt_salter(&salter).unwrap();
+        assert_eq!(cipher.code(), matter::Codex::X25519_Cipher_Salt);
+        assert_eq!(cipher.qb64().unwrap().len(), 100);
+        let decrypted = decrypter.decrypt_salter(&cipher, Some(Tierage::min)).unwrap();
+        assert_eq!(decrypted, salter);
+        assert!(decrypter.decrypt_signer(&cipher, None).is_err());
+
+        // only the matching key opens a cipher
+        let other = Decrypter::new_with_signer(&Signer::new_with_defaults(None, None).unwrap());
+        assert!(other.unwrap().decrypt(&cipher).is_err());
+
+        // only Ed25519 keys convert
+        let signer =
+            Signer::new_with_defaults(None, Some(matter::Codex::ECDSA_256k1_Seed)).unwrap();
+        assert!(Decrypter::new_with_signer(&signer).is_err());
+        assert!(Encrypter::new_with_verfer(&signer.verfer()).is_err());
+
+        assert!(Encrypter::new_with_qb64(&decrypter.qb64().unwrap()).is_err());
+        assert!(Decrypter::new_with_qb64(&encrypter.qb64().unwrap()).is_err());
+    }
+}
@@ -0,0 +1,123 @@
+use zeroize::Zeroize;
+
+use crate::cesr::core::{
+    cipher::Cipher,
+    matter::{tables as matter, Matter},
+    salter::Salter,
+    verfer::Verfer,
+};
+use crate::cesr::crypto::encrypt;
+use crate::error::{err, Error, Result};
+use crate::signing::Signer;
+
+/// An X25519 public encryption key that seals seeds and salts into `Cipher`s.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Encrypter {
+    raw: Vec<u8>,
+    code: String,
+    size: u32,
+}
+
+impl Default for Encrypter {
+    fn default() -> Self {
+        Encrypter { raw: vec![], code: matter::Codex::X25519.to_string(), size: 0 }
+    }
+}
+
+fn validate_code(code: &str) -> Result<()> {
+    if code != matter::Codex::X25519 {
+        return err!(Error::UnexpectedCode(code.to_string()));
+    }
+
+    Ok(())
+}
+
+impl Encrypter {
+    pub fn new(
+        code: Option<&str>,
+        raw: Option<&[u8]>,
+        qb64b: Option<&[u8]>,
+        qb64: Option<&str>,
+        qb2: Option<&[u8]>,
+    ) -> Result<Self> {
+        let code = if raw.is_some() { Some(code.unwrap_or(matter::Codex::X25519)) } else { code };
+        let encrypter: Self = Matter::new(code, raw, qb64b, qb64, qb2)?;
+        validate_code(&encrypter.code())?;
+        Ok(encrypter)
+    }
+
+    pub fn new_with_raw(raw: &[u8], code: Option<&str>) -> Result<Self> {
+        Self::new(code, Some(raw), None, None, None)
+    }
+
+    pub fn new_with_qb64b(qb64b: &[u8]) -> Result<Self> {
+        Self::new(None, None, Some(qb64b), None, None)
+    }
+
+    pub fn new_with_qb64(qb64: &str) -> Result<Self> {
+        Self::new(None, None, None, Some(qb64), None)
+    }
+
+    pub fn new_with_qb2(qb2: &[u8]) -> Result<Self> {
+        Self::new(None, None, None, None, Some(qb2))
+    }
+
+    /// Converts the Ed25519 verification key of `verfer` into its X25519 encryption key.
+    pub fn new_with_verfer(verfer: &Verfer) -> Result<Self> {
+        let raw = encrypt::encryption_key(&verfer.code(), &verfer.raw())?;
+        Self::new_with_raw(&raw, None)
+    }
+
+    /// Whether `signer` holds the seed of the key pair of this encryption key.
+    pub fn verify_signer(&self, signer: &Signer) -> Result<bool> {
+        Ok(encrypt::encryption_key(&signer.verfer().code(), &signer.verfer().raw())? == self.raw)
+    }
+
+    /// Seals `ser`, the qb64b of a seed or salt, into a cipher with `code`.
+    pub fn encrypt(&self, ser: &[u8], code: &str) -> Result<Cipher> {
+        let raw = encrypt::seal(&self.raw, ser)?;
+        Cipher::new_with_raw(&raw, Some(code))
+    }
+
+    /// Seals the seed of `signer`.
+    pub fn encrypt_signer(&self, signer: &Signer) -> Result<Cipher> {
+        let mut ser = signer.qb64b()?;
+        let cipher = self.encrypt(&ser, matter::Codex::X25519_Cipher_Seed);
+        ser.zeroize();
+        cipher
+    }
+
+    /// Seals the salt of `salter`.
+    pub fn encrypt_salter(&self, salter: &Salter) -> Result<Cipher> {
+        let mut ser = salter.qb64b()?;
+        let cipher = self.encrypt(&ser, matter::Codex::X25519_Cipher_Salt);
+        ser.zeroize();
+        cipher
+    }
+}
+
+impl Matter for Encrypter {
+    fn code(&self) -> String {
+        self.code.clone()
+    }
+
+    fn raw(&self) -> Vec<u8> {
+        self.raw.clone()
+    }
+
+    fn size(&self) -> u32 {
+        self.size
+    }
+
+    fn set_code(&mut self, code: &str) {
+        self.code = code.to_string();
+    }
+
+    fn set_raw(&mut self, raw: &[u8]) {
+        self.raw = raw.to_vec();
+    }
+
+    fn set_size(&mut self, size: u32) {
+        self.size = size;
+    }
+}
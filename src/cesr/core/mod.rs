@@ -1,10 +1,13 @@
 pub mod bexter;
 pub mod cigar;
+pub mod cipher;
 pub mod common;
 pub mod counter;
 pub mod creder;
 pub mod dater;
+pub mod decrypter;
 pub mod diger;
+pub mod encrypter;
 pub mod indexer;
 pub mod matter;
 pub mod number;
@@ -1,3 +1,10 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Mutex, MutexGuard},
+    thread,
+};
+
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
 use crate::signing::signer::Signer;
@@ -146,6 +153,17 @@ impl Salter {
 
         Ok(signers)
     }
+
+    /// Derives the keys of the identifier with `stem` along its paths, see `Deriver`.
+    pub fn deriver(&self, stem: Option<&str>, tier: Option<&str>, temp: Option<bool>) -> Deriver {
+        Deriver {
+            salter: self.clone(),
+            stem: stem.unwrap_or("").to_string(),
+            tier: tier.map(|tier| tier.to_string()),
+            temp: temp.unwrap_or(false),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
 }
 
 impl Default for Salter {
@@ -185,6 +203,171 @@ impl Matter for Salter {
     }
 }
 
+/// Derivation path of a key, the `stem` of its identifier and its rotation and key indices.
+/// Displays in KERIpy's path format, `{stem}{ridx:x}{kidx:x}`, as used by salty creators.
+///
+/// The format has no separator between the indices, so distinct paths can display the same and
+/// derive the same key: rotation 0x1 with key 0x1b and rotation 0x11 with key 0xb both display
+/// as `11b`. KERIpy derives along the same strings, so the collision is kept for compatibility.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Path {
+    stem: String,
+    ridx: u16,
+    kidx: u16,
+}
+
+impl Path {
+    pub fn new(stem: &str, ridx: u16, kidx: u16) -> Self {
+        Path { stem: stem.to_string(), ridx, kidx }
+    }
+
+    pub fn stem(&self) -> String {
+        self.stem.clone()
+    }
+
+    pub fn ridx(&self) -> u16 {
+        self.ridx
+    }
+
+    pub fn kidx(&self) -> u16 {
+        self.kidx
+    }
+
+    /// Path of the key `offset` places after this one at the same rotation.
+    pub fn child(&self, offset: u16) -> Result<Self> {
+        let kidx = match self.kidx.checked_add(offset) {
+            Some(kidx) => kidx,
+            None => return err!(Error::Value(format!("key index overflow at offset {offset}"))),
+        };
+
+        Ok(Path { stem: self.stem.clone(), ridx: self.ridx, kidx })
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{:x}{:x}", self.stem, self.ridx, self.kidx)
+    }
+}
+
+// derived signers by path, code and transferability
+type Cache = HashMap<(Path, String, bool), Signer>;
+
+/// Derivation of the keys of an identifier from a salt, along paths under its stem.
+///
+/// Each key is the key `Salter::signer` derives from the display of its path, stretched with
+/// Argon2id at the deriver's tier, so keys are those of a KERIpy salty creator. KERIpy stretches
+/// the whole path with the salt for every key, so no intermediate stretch can be shared between
+/// keys without changing them: each key costs a full stretch. Derived keys are cached instead,
+/// and `derive` stretches the paths of a batch in parallel.
+#[derive(Debug)]
+pub struct Deriver {
+    salter: Salter,
+    stem: String,
+    tier: Option<String>,
+    temp: bool,
+    cache: Mutex<Cache>,
+}
+
+impl Deriver {
+    pub fn stem(&self) -> String {
+        self.stem.clone()
+    }
+
+    pub fn path(&self, ridx: u16, kidx: u16) -> Path {
+        Path::new(&self.stem, ridx, kidx)
+    }
+
+    pub fn signer(
+        &self,
+        path: &Path,
+        code: Option<&str>,
+        transferable: Option<bool>,
+    ) -> Result<Signer> {
+        if path.stem != self.stem {
+            return err!(Error::Value(format!(
+                "path stem '{}' does not match deriver stem '{}'",
+                path.stem, self.stem
+            )));
+        }
+
+        let code = code.unwrap_or(matter::Codex::Ed25519_Seed);
+        let transferable = transferable.unwrap_or(true);
+        let key = (path.clone(), code.to_string(), transferable);
+        if let Some(signer) = self.cache()?.get(&key) {
+            return Ok(signer.clone());
+        }
+
+        let signer = self.salter.signer(
+            Some(code),
+            Some(transferable),
+            Some(&path.to_string()),
+            self.tier.as_deref(),
+            Some(self.temp),
+        )?;
+        self.cache()?.insert(key, signer.clone());
+
+        Ok(signer)
+    }
+
+    /// The signers of `count` consecutive keys from `kidx` at rotation `ridx`, derived in
+    /// parallel.
+    pub fn signers(
+        &self,
+        count: Option<u16>,
+        ridx: Option<u16>,
+        kidx: Option<u16>,
+        code: Option<&str>,
+        transferable: Option<bool>,
+    ) -> Result<Vec<Signer>> {
+        let path = self.path(ridx.unwrap_or(0), kidx.unwrap_or(0));
+        let code = code.unwrap_or(matter::Codex::Ed25519_Seed);
+        let keys = (0..count.unwrap_or(1))
+            .map(|i| Ok((path.child(i)?, code)))
+            .collect::<Result<Vec<_>>>()?;
+
+        self.derive(&keys, transferable)
+    }
+
+    /// The signers of `keys`, each a path and the code of its key, derived in parallel.
+    pub fn derive(&self, keys: &[(Path, &str)], transferable: Option<bool>) -> Result<Vec<Signer>> {
+        let threads = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        let size = keys.len().div_ceil(threads).max(1);
+
+        thread::scope(|scope| {
+            let handles: Vec<_> = keys
+                .chunks(size)
+                .map(|keys| {
+                    scope.spawn(move || {
+                        keys.iter()
+                            .map(|(path, code)| self.signer(path, Some(code), transferable))
+                            .collect::<Result<Vec<_>>>()
+                    })
+                })
+                .collect();
+
+            let mut signers = vec![];
+            for handle in handles {
+                match handle.join() {
+                    Ok(derived) => signers.extend(derived?),
+                    Err(_) => {
+                        return err!(Error::Derivation("key derivation panicked".to_string()))
+                    }
+                }
+            }
+
+            Ok(signers)
+        })
+    }
+
+    fn cache(&self) -> Result<MutexGuard<'_, Cache>> {
+        match self.cache.lock() {
+            Ok(cache) => Ok(cache),
+            Err(_) => err!(Error::Derivation("poisoned key cache".to_string())),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::signing::signer::Signer;
@@ -192,7 +375,7 @@ mod test {
         cesr::core::{
             common::{versify, Ilkage, Serialage, Tierage, CURRENT_VERSION},
             matter::{tables as matter, Matter},
-            salter::Salter,
+            salter::{Path, Salter},
         },
         dat,
     };
@@ -208,6 +391,101 @@ mod test {
         assert!(Salter::new_with_qb2(&salter.qb2().unwrap(), None).is_ok());
     }
 
+    #[test]
+    fn path() {
+        let path = Path::new("", 0, 1);
+        assert_eq!(path.to_string(), "01");
+        assert_eq!(Path::new("ABC", 10, 255).to_string(), "ABCaff");
+        assert_eq!((path.stem(), path.ridx(), path.kidx()), ("".to_string(), 0, 1));
+        assert_eq!(path.child(2).unwrap(), Path::new("", 0, 3));
+        assert!(Path::new("", 0, u16::MAX).child(1).is_err());
+
+        // the indices are not separated, so distinct paths can display the same
+        assert_ne!(Path::new("", 0x1, 0x1b), Path::new("", 0x11, 0xb));
+        assert_eq!(Path::new("", 0x1, 0x1b).to_string(), "11b");
+        assert_eq!(Path::new("", 0x11, 0xb).to_string(), "11b");
+
+        // displays as the path a salty creator derives from
+        let salter = Salter::new_with_raw(b"0123456789abcdef", None, None).unwrap();
+        let signer = salter.signer(None, None, Some(&path.to_string()), None, Some(true)).unwrap();
+        assert_eq!(signer.qb64().unwrap(), "AMPsqBZxWdtYpBhrWnKYitwFa77s902Q-nX3sPTzqs0R");
+    }
+
+    #[test]
+    fn deriver() {
+        let salter = Salter::new_with_raw(b"0123456789abcdef", None, Some(Tierage::min)).unwrap();
+        let deriver = salter.deriver(Some("stem"), None, None);
+        assert_eq!(deriver.stem(), "stem");
+        assert_eq!(deriver.path(1, 2), Path::new("stem", 1, 2));
+
+        // keys are those stretched over the whole path, as a salty creator derives them
+        let signers = deriver.signers(Some(3), Some(1), Some(2), None, None).unwrap();
+        assert_eq!(signers.len(), 3);
+        assert_eq!(deriver.cache.lock().unwrap().len(), 3);
+        for (i, signer) in signers.iter().enumerate() {
+            let path = deriver.path(1, 2 + i as u16);
+            assert_eq!(path.to_string(), format!("stem1{:x}", 2 + i));
+            assert_eq!(signer, &deriver.signer(&path, None, None).unwrap());
+            assert_eq!(
+                signer,
+                &salter.signer(None, None, Some(&path.to_string()), None, None).unwrap()
+            );
+            assert_eq!(signer.code(), matter::Codex::Ed25519_Seed);
+        }
+        assert_eq!(deriver.cache.lock().unwrap().len(), 3);
+        assert_ne!(signers[0], signers[1]);
+        assert_ne!(signers[0], deriver.signer(&deriver.path(2, 2), None, None).unwrap());
+        assert!(deriver.signers(Some(2), None, Some(u16::MAX), None, None).is_err());
+        assert!(deriver.signers(Some(0), None, None, None, None).unwrap().is_empty());
+
+        // a batch may mix codes
+        let keys = [
+            (deriver.path(1, 2), matter::Codex::Ed25519_Seed),
+            (deriver.path(1, 5), matter::Codex::ECDSA_256k1_Seed),
+        ];
+        let derived = deriver.derive(&keys, None).unwrap();
+        assert_eq!(derived[0], signers[0]);
+        assert_eq!(derived[1].code(), matter::Codex::ECDSA_256k1_Seed);
+        assert_eq!(
+            derived[1],
+            salter
+                .signer(Some(matter::Codex::ECDSA_256k1_Seed), None, Some("stem15"), None, None)
+                .unwrap()
+        );
+
+        // the same as KERIpy at the default tier
+        let salter = Salter::new_with_raw(b"0123456789abcdef", None, None).unwrap();
+        let signer =
+            salter.deriver(None, None, Some(true)).signer(&Path::new("", 0, 1), None, None);
+        assert_eq!(signer.unwrap().qb64().unwrap(), "AMPsqBZxWdtYpBhrWnKYitwFa77s902Q-nX3sPTzqs0R");
+
+        // the stem and the tier bind the keys
+        let salter = Salter::new_with_raw(b"0123456789abcdef", None, Some(Tierage::min)).unwrap();
+        assert!(deriver.signer(&Path::new("other", 1, 2), None, None).is_err());
+        let other = salter.deriver(Some("other"), None, None);
+        assert_ne!(signers[0], other.signer(&other.path(1, 2), None, None).unwrap());
+        let low = salter.deriver(Some("stem"), Some(Tierage::low), None);
+        let path = low.path(1, 2);
+        assert_eq!(
+            low.signer(&path, None, None).unwrap(),
+            salter.signer(None, None, Some(&path.to_string()), Some(Tierage::low), None).unwrap()
+        );
+
+        let path = deriver.path(1, 2);
+        for (code, verfer) in [
+            (matter::Codex::Ed25519_Seed, matter::Codex::Ed25519),
+            (matter::Codex::ECDSA_256k1_Seed, matter::Codex::ECDSA_256k1),
+            (matter::Codex::ECDSA_256r1_Seed, matter::Codex::ECDSA_256r1),
+        ] {
+            let signer = deriver.signer(&path, Some(code), None).unwrap();
+            assert_eq!(signer.code(), code);
+            assert_eq!(signer.verfer().code(), verfer);
+            let signer = deriver.signer(&path, Some(code), Some(false)).unwrap();
+            assert_ne!(signer.verfer().code(), verfer);
+        }
+        assert!(deriver.signer(&path, Some(matter::Codex::Blake3_256), None).is_err());
+    }
+
     #[test]
     fn python_interop() {
         let salter = Salter::new(None, None, None, None, None, None).unwrap();
@@ -11,6 +11,7 @@ use crate::{
     },
     data::{dat, Value},
     error::{err, Error, Result},
+    seal::Seal,
 };
 
 #[derive(Debug, Clone, PartialEq)]
@@ -180,6 +181,73 @@ impl Serder {
         Ok(self.pre()?.as_bytes().to_vec())
     }
 
+    pub fn ilk(&self) -> Result<String> {
+        let label = Ids::t;
+        self.ked[label].to_string()
+    }
+
+    pub fn prior(&self) -> Result<Option<String>> {
+        let map = self.ked.to_map()?;
+
+        let label = Ids::p;
+        let result =
+            if map.contains_key(label) { Some(self.ked[label].to_string()?) } else { None };
+
+        Ok(result)
+    }
+
+    pub fn bner(&self) -> Result<Option<Number>> {
+        let map = self.ked.to_map()?;
+
+        let label = Ids::bt;
+        let result = if map.contains_key(label) {
+            let bt = &self.ked[label];
+            let bner = if let Ok(n) = bt.to_i64() {
+                Number::new_with_num(u128::try_from(n)?)?
+            } else {
+                Number::new_with_numh(&bt.to_string()?)?
+            };
+            Some(bner)
+        } else {
+            None
+        };
+
+        Ok(result)
+    }
+
+    pub fn bn(&self) -> Result<u128> {
+        let bner = self.bner()?;
+
+        if let Some(bner) = bner {
+            bner.num()
+        } else {
+            err!(Error::Validation("backer threshold does not exist".to_string()))
+        }
+    }
+
+    pub fn delpre(&self) -> Result<Option<String>> {
+        let map = self.ked.to_map()?;
+
+        let label = Ids::di;
+        let result =
+            if map.contains_key(label) { Some(self.ked[label].to_string()?) } else { None };
+
+        Ok(result)
+    }
+
+    pub fn seals(&self) -> Result<Vec<Seal>> {
+        let map = self.ked.to_map()?;
+
+        let label = Ids::a;
+        if map.contains_key(label) {
+            if let Ok(values) = self.ked[label].to_vec() {
+                return Seal::from_values(&values);
+            }
+        }
+
+        Ok(vec![])
+    }
+
     pub fn est(&self) -> Result<bool> {
         const ILKS: &[&str] = &[Ilkage::icp, Ilkage::rot, Ilkage::dip, Ilkage::drt];
 
@@ -425,7 +493,7 @@ pub(crate) mod test {
             None,
             None,
             None,
-            None,
+            Some(2),
             Some(&[wit0, wit1]),
             None,
             None,
@@ -568,16 +636,6 @@ pub(crate) mod test {
         assert!(Serder::new(None, None, None, Some(&ked), None).is_err());
     }
 
-    pub(crate) mod traiter {
-        #[allow(non_upper_case_globals)]
-        #[allow(non_snake_case)]
-        mod Codex {
-            const EstOnly: &str = "EO";
-            const DoNotDelegate: &str = "DND";
-            const NoBackeds: &str = "NB";
-        }
-    }
-
     // what follows is a simple inception function. it is used above to verify serder functionality.
 
     // this function uses convenience methods unlike most test code. it is likely that it will
@@ -588,9 +646,9 @@ pub(crate) mod test {
         sith: Option<&Value>,   // current signing threshold
         ndigs: Option<&[&str]>, // next keys qb64
         nsith: Option<&Value>,  // next signing threshold
-        toad: Option<u128>,     // witness threshold number
+        toad: Option<u128>,     // witness threshold number, required with witnesses
         wits: Option<&[&str]>,  // witness identifier prefixes qb64
-        cnfg: Option<&[&str]>,  // configuration traits from traiter::Codex
+        cnfg: Option<&[&str]>,  // configuration trait codes
         data: Option<&[Value]>, // seal dicts
         version: Option<&Version>,
         kind: Option<&str>,
@@ -652,14 +710,7 @@ pub(crate) mod test {
             return err!(Error::Value(format!("invalid wits = {wits:?}, has duplicates")));
         }
 
-        let toader = if let Some(toad) = toad {
-            Number::new_with_num(toad)?
-        } else if wits.is_empty() {
-            Number::new_with_num(0)?
-        } else {
-            let toad = ample(wits.len() as u128, None, None)?;
-            Number::new_with_num(toad)?
-        };
+        let toader = Number::new_with_num(toad.unwrap_or(0))?;
 
         if !wits.is_empty() {
             if toader.num()? < 1 || toader.num()? > wits.len() as u128 {
@@ -766,42 +817,4 @@ pub(crate) mod test {
 
         Serder::new(None, None, None, Some(&ked), None)
     }
-
-    fn ample(n: u128, f: Option<u128>, weak: Option<bool>) -> Result<u128> {
-        let weak = weak.unwrap_or(true);
-        let n = if n > 0 { n } else { 0 };
-        if let Some(f) = f {
-            let f = if f > 0 { f } else { 0 };
-            let m1 = (n + f + 2) / 2;
-            let m2 = if n - f > 0 { n - f } else { 0 };
-
-            if m2 < m1 && n > 0 {
-                return err!(Error::Value(format!("invalid f={f}, too big for n={n}")));
-            }
-
-            if weak {
-                match [n, m1, m2].iter().min() {
-                    Some(x) => Ok(*x),
-                    None => err!(Error::Value("unreachable".to_string())),
-                }
-            } else {
-                Ok(std::cmp::min(n, std::cmp::max(m1, m2)))
-            }
-        } else {
-            let f1 = std::cmp::max(1, std::cmp::max(0, n - 1) / 3);
-            let f2 = std::cmp::max(1, (std::cmp::max(0, n - 1) + 2) / 3);
-
-            if weak {
-                match [n, (n + f1 + 3) / 2, (n + f2 + 3) / 2].iter().min() {
-                    Some(x) => Ok(*x),
-                    None => err!(Error::Value("unreachable".to_string())),
-                }
-            } else {
-                match [0, n - f1, (n + f1 + 3) / 2].iter().max() {
-                    Some(x) => Ok(std::cmp::min(n, *x)),
-                    None => err!(Error::Value("unreachable".to_string())),
-                }
-            }
-        }
-    }
 }
@@ -0,0 +1,93 @@
+use crypto_box::{PublicKey, SecretKey};
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use rand_core::OsRng;
+use zeroize::Zeroize;
+
+use crate::cesr::core::matter::tables as matter;
+use crate::error::{err, Error, Result};
+
+/// Converts an Ed25519 public verification key into the X25519 public encryption key of the same
+/// key pair.
+pub(crate) fn encryption_key(code: &str, public_key: &[u8]) -> Result<Vec<u8>> {
+    match code {
+        matter::Codex::Ed25519 | matter::Codex::Ed25519N => {
+            let verifying_key = VerifyingKey::from_bytes(public_key.try_into()?)?;
+            Ok(verifying_key.to_montgomery().to_bytes().to_vec())
+        }
+        _ => err!(Error::UnexpectedCode(code.to_string())),
+    }
+}
+
+/// Converts an Ed25519 private signing seed into the clamped X25519 private decryption key of
+/// the same key pair, as libsodium does.
+pub(crate) fn decryption_key(code: &str, seed: &[u8]) -> Result<Vec<u8>> {
+    match code {
+        matter::Codex::Ed25519_Seed => {
+            let signing_key = SigningKey::from_bytes(seed.try_into()?);
+            let mut key = signing_key.to_scalar_bytes();
+            key[0] &= 248;
+            key[31] &= 127;
+            key[31] |= 64;
+
+            let result = key.to_vec();
+            key.zeroize();
+            Ok(result)
+        }
+        _ => err!(Error::UnexpectedCode(code.to_string())),
+    }
+}
+
+/// The X25519 public encryption key of `private_key`.
+pub(crate) fn public_key(private_key: &[u8]) -> Result<Vec<u8>> {
+    Ok(SecretKey::from_slice(private_key)?.public_key().to_bytes().to_vec())
+}
+
+/// Encrypts `plaintext` to `public_key` as a libsodium sealed box.
+pub(crate) fn seal(public_key: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+    let public_key = PublicKey::from_slice(public_key)?;
+    match public_key.seal(&mut OsRng, plaintext) {
+        Ok(ciphertext) => Ok(ciphertext),
+        Err(_) => err!(Error::Value("unable to encrypt".to_string())),
+    }
+}
+
+/// Decrypts a libsodium sealed box with `private_key`.
+pub(crate) fn unseal(private_key: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>> {
+    let private_key = SecretKey::from_slice(private_key)?;
+    match private_key.unseal(ciphertext) {
+        Ok(plaintext) => Ok(plaintext),
+        Err(_) => err!(Error::Value("unable to decrypt".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cesr::core::matter::tables as matter;
+    use crate::cesr::crypto::{encrypt, sign};
+
+    #[test]
+    fn seal_and_unseal() {
+        let seed = sign::generate(matter::Codex::Ed25519_Seed).unwrap();
+        let verkey = sign::public_key(matter::Codex::Ed25519_Seed, &seed).unwrap();
+
+        let prikey = encrypt::decryption_key(matter::Codex::Ed25519_Seed, &seed).unwrap();
+        let pubkey = encrypt::encryption_key(matter::Codex::Ed25519, &verkey).unwrap();
+        assert_eq!(encrypt::public_key(&prikey).unwrap(), pubkey);
+        assert_eq!(encrypt::encryption_key(matter::Codex::Ed25519N, &verkey).unwrap(), pubkey);
+
+        let ciphertext = encrypt::seal(&pubkey, b"plaintext").unwrap();
+        assert_eq!(ciphertext.len(), 9 + 48);
+        assert_eq!(encrypt::unseal(&prikey, &ciphertext).unwrap(), b"plaintext");
+
+        let other = encrypt::decryption_key(
+            matter::Codex::Ed25519_Seed,
+            &sign::generate(matter::Codex::Ed25519_Seed).unwrap(),
+        )
+        .unwrap();
+        assert!(encrypt::unseal(&other, &ciphertext).is_err());
+        assert!(encrypt::unseal(&prikey, &ciphertext[..40]).is_err());
+
+        assert!(encrypt::decryption_key(matter::Codex::ECDSA_256k1_Seed, &seed).is_err());
+        assert!(encrypt::encryption_key(matter::Codex::ECDSA_256k1, &verkey).is_err());
+    }
+}
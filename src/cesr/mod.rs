@@ -4,11 +4,14 @@ pub(crate) mod crypto;
 pub use crate::cesr::core::{
     bexter::{Bext, Bexter},
     cigar::Cigar,
+    cipher::Cipher,
     common,
     counter::{tables as counter, Counter}, // This seems like it shoudl be an abstract class
     creder::Creder,
     dater::Dater,
+    decrypter::Decrypter,
     diger::Diger,
+    encrypter::Encrypter,
     indexer::{tables as indexer, Indexer},
     matter::{tables as matter, Matter},
     number::{tables as number, Number},
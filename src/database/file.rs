@@ -0,0 +1,681 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, UNIX_EPOCH},
+};
+
+use crate::{
+    cesr::core::{
+        common::{Ids, Ilkage},
+        counter::{tables as counter, Counter},
+        creder::Creder,
+        sadder::Sadder,
+        serder::Serder,
+    },
+    data::{dat, Value},
+    database::{
+        memory::{sad_said, MemoryDatabase},
+        Database,
+    },
+    error::{err, Error, Result},
+    eventing::{
+        escrowing::EscrowEntry,
+        record::EventRecord,
+        state::{strings, KeyStateRecord},
+    },
+    parsing::parser::{parse_message, serialization_size, Message},
+};
+
+const KELS: &str = "kels";
+const TELS: &str = "tels";
+const STATES: &str = "states.jsonl";
+const ESCROWS: &str = "escrows.jsonl";
+const SADS: &str = "sads.jsonl";
+const ACDCS: &str = "acdcs.cesr";
+
+/// A `Database` persisted in a directory as append-only logs: a CESR stream of event records
+/// per identifier under `kels`, a CESR stream of transaction events per registry under `tels`,
+/// a CESR stream of ACDCs, and JSON lines of key states, escrow changes and SADs. Each event
+/// record is logged as its event followed by its attachments in one counted attached material
+/// group, so that the streams are read back by the CESR parser, and any CESR tool. Every append
+/// is synced before it returns. On open the logs are replayed into in-memory indexes by sequence
+/// number and said, after truncating any torn tail left by an interrupted append, a message or
+/// line cut short of what its version string or counters promise.
+///
+/// Witness lists of event records are not stored, they are derived again from the events.
+/// Writes are validated, appended and only then applied in memory, so a failed write changes
+/// neither.
+#[derive(Debug)]
+pub struct FileDatabase {
+    dir: PathBuf,
+    memory: MemoryDatabase,
+}
+
+/// The complete lines of the log at `path`, truncating the file after its last newline.
+fn read_log(path: &Path) -> Result<Vec<String>> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e.into()),
+    };
+
+    let end = bytes.iter().rposition(|b| *b == b'\n').map(|i| i + 1).unwrap_or(0);
+    if end < bytes.len() {
+        let file = OpenOptions::new().write(true).open(path)?;
+        file.set_len(end as u64)?;
+        file.sync_all()?;
+    }
+
+    Ok(String::from_utf8(bytes[..end].to_vec())?.lines().map(|line| line.to_string()).collect())
+}
+
+/// The messages of the CESR stream logged at `path`, each parsed by `parse` into a message and
+/// its size, truncating the file after the last complete one.
+fn read_stream<T>(path: &Path, parse: impl Fn(&str) -> Result<(T, usize)>) -> Result<Vec<T>> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e.into()),
+    };
+
+    let stream = match std::str::from_utf8(&bytes) {
+        Ok(stream) => stream,
+        // torn inside a character
+        Err(e) if e.error_len().is_none() => std::str::from_utf8(&bytes[..e.valid_up_to()])?,
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut messages = vec![];
+    let mut offset = 0;
+    while offset < stream.len() {
+        match parse(&stream[offset..]) {
+            Ok((message, size)) => {
+                messages.push(message);
+                offset += size;
+            }
+            Err(e) if matches!(e.downcast_ref(), Some(Error::Shortage(_))) => break,
+            Err(e) => return Err(e),
+        }
+    }
+
+    if offset < bytes.len() {
+        let file = OpenOptions::new().write(true).open(path)?;
+        file.set_len(offset as u64)?;
+        file.sync_all()?;
+    }
+
+    Ok(messages)
+}
+
+/// Parses a logged event record, its event followed by its counted attachments, which a torn
+/// append may have cut off entirely.
+fn parse_record(stream: &str) -> Result<(Message, usize)> {
+    let (message, size) = parse_message(stream)?;
+    if size == message.serder.raw().len() {
+        let short = stream.len() == size;
+        let e = format!("event {said} logged without attachments", said = message.serder.said()?);
+        return if short { err!(Error::Shortage(e)) } else { err!(Error::Value(e)) };
+    }
+
+    Ok((message, size))
+}
+
+/// Parses a logged serialization without attachments, of a transaction event or an ACDC.
+fn parse_serialization<T>(stream: &str, new: impl Fn(&[u8]) -> Result<T>) -> Result<(T, usize)> {
+    let size = serialization_size(stream.as_bytes())?;
+
+    Ok((new(&stream.as_bytes()[..size])?, size))
+}
+
+/// `message` as logged, its event followed by its attachments in one counted group.
+fn counted(message: &Message) -> Result<String> {
+    let atc = message.attachments()?;
+    let quadlets = atc.len() / 4;
+    let code = if quadlets < 64 * 64 {
+        counter::Codex::AttachedMaterialQuadlets
+    } else {
+        counter::Codex::BigAttachedMaterialQuadlets
+    };
+    let group = Counter::new_with_code_and_count(code, quadlets as u32)?.qb64()?;
+
+    Ok(String::from_utf8(message.serder.raw())? + &group + &atc)
+}
+
+/// Appends `line` to the log at `path`.
+fn append_line(path: &Path, line: &str) -> Result<()> {
+    if line.contains('\n') {
+        return err!(Error::Value(format!("log entry for {path:?} spans lines")));
+    }
+
+    append(path, format!("{line}\n").as_bytes())
+}
+
+/// Appends `entry` to the log at `path` and syncs it, creating the log if needed.
+fn append(path: &Path, entry: &[u8]) -> Result<()> {
+    let created = !path.exists();
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    let len = file.metadata()?.len();
+    if let Err(e) = file.write_all(entry).and_then(|_| file.sync_data()) {
+        // leave no partial entry for a later append to run into
+        let _ = file.set_len(len);
+        return Err(e.into());
+    }
+
+    if created {
+        if let Some(dir) = path.parent() {
+            File::open(dir)?.sync_all()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The name of the log of identifier or registry `pre`, which must be a qb64 prefix.
+fn log_name(pre: &str) -> Result<String> {
+    if pre.is_empty() || !pre.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return err!(Error::Value(format!("invalid prefix = {pre}")));
+    }
+
+    Ok(format!("{pre}.cesr"))
+}
+
+/// The message of `record`, with its attachments as replayed.
+fn message(record: &EventRecord) -> Message {
+    Message {
+        serder: record.serder.clone(),
+        sigers: record.sigers.clone(),
+        wigers: record.wigers.clone(),
+        cigars: record.cigars.clone(),
+        trqs: record.vrcs.clone(),
+        frcs: record.first_seen.clone().into_iter().collect(),
+        sscs: record.source.clone().into_iter().collect(),
+        ..Default::default()
+    }
+}
+
+/// The witnesses designated by the key state at each of `serders`, which are the events of one
+/// identifier by said, following prior event digests back to inception.
+fn derive_wits(serders: &HashMap<String, Serder>) -> Result<HashMap<String, Vec<String>>> {
+    let mut wits: HashMap<String, Vec<String>> = HashMap::new();
+
+    for said in serders.keys() {
+        let mut chain = vec![];
+        let mut current = Some(said.clone());
+        while let Some(said) = current.take() {
+            if wits.contains_key(&said) || chain.contains(&said) {
+                break;
+            }
+            let serder = match serders.get(&said) {
+                Some(serder) => serder,
+                None => break,
+            };
+            if ![Ilkage::icp, Ilkage::dip].contains(&serder.ilk()?.as_str()) {
+                current = serder.prior()?;
+            }
+            chain.push(said);
+        }
+
+        for said in chain.into_iter().rev() {
+            let serder = &serders[&said];
+            let ked = serder.ked();
+            let prior = || -> Result<Vec<String>> {
+                Ok(serder.prior()?.and_then(|p| wits.get(&p).cloned()).unwrap_or_default())
+            };
+
+            let derived = match serder.ilk()?.as_str() {
+                Ilkage::icp | Ilkage::dip => strings(&ked[Ids::b])?,
+                Ilkage::rot | Ilkage::drt => {
+                    let cuts = strings(&ked[Ids::br])?;
+                    let mut derived: Vec<String> =
+                        prior()?.into_iter().filter(|wit| !cuts.contains(wit)).collect();
+                    derived.extend(strings(&ked[Ids::ba])?);
+                    derived
+                }
+                _ => prior()?,
+            };
+            wits.insert(said, derived);
+        }
+    }
+
+    Ok(wits)
+}
+
+impl FileDatabase {
+    /// Opens the database in `dir`, creating it if needed, and replays its logs.
+    pub fn open(dir: &Path) -> Result<Self> {
+        fs::create_dir_all(dir.join(KELS))?;
+        fs::create_dir_all(dir.join(TELS))?;
+
+        let mut db = Self { dir: dir.to_path_buf(), memory: MemoryDatabase::new() };
+        db.load_kels()?;
+        db.load_tels()?;
+        db.load_states()?;
+        db.load_escrows()?;
+        db.load_sads()?;
+        db.load_acdcs()?;
+
+        Ok(db)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn logs(&self, kind: &str) -> Result<Vec<PathBuf>> {
+        let mut paths = vec![];
+        for entry in fs::read_dir(self.dir.join(kind))? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "cesr") {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        Ok(paths)
+    }
+
+    fn load_kels(&mut self) -> Result<()> {
+        for path in self.logs(KELS)? {
+            let messages = read_stream(&path, parse_record)?;
+
+            let mut serders = HashMap::new();
+            for message in &messages {
+                serders.insert(message.serder.said()?, message.serder.clone());
+            }
+            let wits = derive_wits(&serders)?;
+
+            for message in messages {
+                let record = EventRecord {
+                    wits: wits.get(&message.serder.said()?).cloned().unwrap_or_default(),
+                    serder: message.serder,
+                    sigers: message.sigers,
+                    wigers: message.wigers,
+                    cigars: message.cigars,
+                    vrcs: message.trqs,
+                    source: message.sscs.into_iter().next(),
+                    first_seen: message.frcs.into_iter().next(),
+                };
+                self.memory.put_record(&record)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn load_tels(&mut self) -> Result<()> {
+        for path in self.logs(TELS)? {
+            for serder in read_stream(&path, |s| parse_serialization(s, Serder::new_with_raw))? {
+                self.memory.put_tel_event(&serder)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn load_states(&mut self) -> Result<()> {
+        for line in read_log(&self.dir.join(STATES))? {
+            self.memory.put_state(&KeyStateRecord::from_value(&json(&line)?)?)?;
+        }
+
+        Ok(())
+    }
+
+    fn load_escrows(&mut self) -> Result<()> {
+        for line in read_log(&self.dir.join(ESCROWS))? {
+            let change = json(&line)?.to_map()?;
+            let name = match change.get("name") {
+                Some(name) => name.to_string()?,
+                None => return err!(Error::Value(format!("invalid escrow entry {line}"))),
+            };
+
+            match (change.get("message"), change.get("said")) {
+                (Some(message), _) => {
+                    let nanos = change
+                        .get("created")
+                        .map(|created| created.to_string())
+                        .transpose()?
+                        .unwrap_or_default()
+                        .parse::<u64>()?;
+                    let entry = EscrowEntry {
+                        message: parse_whole(&message.to_string()?)?,
+                        reason: change
+                            .get("reason")
+                            .map(|reason| reason.to_string())
+                            .transpose()?
+                            .unwrap_or_default(),
+                        created: UNIX_EPOCH + Duration::from_nanos(nanos),
+                    };
+                    self.memory.put_escrow(&name, &entry)?;
+                }
+                (None, Some(said)) => {
+                    self.memory.remove_escrow(&name, &said.to_string()?)?;
+                }
+                _ => return err!(Error::Value(format!("invalid escrow entry {line}"))),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn load_sads(&mut self) -> Result<()> {
+        for line in read_log(&self.dir.join(SADS))? {
+            self.memory.put_sad(&json(&line)?)?;
+        }
+
+        Ok(())
+    }
+
+    fn load_acdcs(&mut self) -> Result<()> {
+        let path = self.dir.join(ACDCS);
+        for creder in read_stream(&path, |s| parse_serialization(s, Creder::new_with_raw))? {
+            self.memory.put_acdc(&creder)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses a string holding exactly one message.
+fn parse_whole(stream: &str) -> Result<Message> {
+    let (message, size) = parse_message(stream)?;
+    if size != stream.len() {
+        return err!(Error::Value(format!("unexpected data after message {stream}")));
+    }
+
+    Ok(message)
+}
+
+fn json(line: &str) -> Result<Value> {
+    Ok(Value::from(&serde_json::from_str::<serde_json::Value>(line)?))
+}
+
+impl Database for FileDatabase {
+    fn put_record(&mut self, record: &EventRecord) -> Result<()> {
+        self.memory.check_record(record)?;
+        let path = self.dir.join(KELS).join(log_name(&record.serder.pre()?)?);
+        append(&path, counted(&message(record))?.as_bytes())?;
+        self.memory.put_record(record)
+    }
+
+    fn record(&self, said: &str) -> Result<Option<EventRecord>> {
+        self.memory.record(said)
+    }
+
+    fn said(&self, pre: &str, sn: u128) -> Result<Option<String>> {
+        self.memory.said(pre, sn)
+    }
+
+    fn prefixes(&self) -> Result<Vec<String>> {
+        self.memory.prefixes()
+    }
+
+    fn fel(&self, pre: &str) -> Result<Vec<EventRecord>> {
+        self.memory.fel(pre)
+    }
+
+    fn put_state(&mut self, state: &KeyStateRecord) -> Result<()> {
+        append_line(&self.dir.join(STATES), &state.to_value().to_json()?)?;
+        self.memory.put_state(state)
+    }
+
+    fn state(&self, pre: &str) -> Result<Option<KeyStateRecord>> {
+        self.memory.state(pre)
+    }
+
+    fn put_escrow(&mut self, name: &str, entry: &EscrowEntry) -> Result<()> {
+        entry.said()?;
+        let created = entry.created.duration_since(UNIX_EPOCH)?.as_nanos().to_string();
+        let line = dat!({
+            "name": name,
+            "reason": entry.reason.as_str(),
+            "created": created.as_str(),
+            "message": entry.message.messagize()?.as_str(),
+        })
+        .to_json()?;
+        append_line(&self.dir.join(ESCROWS), &line)?;
+        self.memory.put_escrow(name, entry)
+    }
+
+    fn escrow(&self, name: &str) -> Result<Vec<EscrowEntry>> {
+        self.memory.escrow(name)
+    }
+
+    fn remove_escrow(&mut self, name: &str, said: &str) -> Result<bool> {
+        if !self.memory.escrowed(name, said) {
+            return Ok(false);
+        }
+
+        append_line(&self.dir.join(ESCROWS), &dat!({"name": name, "said": said}).to_json()?)?;
+        self.memory.remove_escrow(name, said)
+    }
+
+    fn put_tel_event(&mut self, serder: &Serder) -> Result<()> {
+        serder.sn()?;
+        let path = self.dir.join(TELS).join(log_name(&serder.pre()?)?);
+        append(&path, &serder.raw())?;
+        self.memory.put_tel_event(serder)
+    }
+
+    fn tel_event(&self, pre: &str, sn: u128) -> Result<Option<Serder>> {
+        self.memory.tel_event(pre, sn)
+    }
+
+    fn put_sad(&mut self, sad: &Value) -> Result<()> {
+        sad_said(sad)?;
+        append_line(&self.dir.join(SADS), &sad.to_json()?)?;
+        self.memory.put_sad(sad)
+    }
+
+    fn sad(&self, said: &str) -> Result<Option<Value>> {
+        self.memory.sad(said)
+    }
+
+    fn put_acdc(&mut self, creder: &Creder) -> Result<()> {
+        creder.said()?;
+        append(&self.dir.join(ACDCS), &creder.raw())?;
+        self.memory.put_acdc(creder)
+    }
+
+    fn acdc(&self, said: &str) -> Result<Option<Creder>> {
+        self.memory.acdc(said)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        fs::{self, OpenOptions},
+        io::Write,
+        time::SystemTime,
+    };
+
+    use crate::{
+        cesr::core::{
+            common::Tierage,
+            diger::Diger,
+            indexer::Indexer,
+            matter::{tables as matter, Matter},
+            sadder::Sadder,
+            saider::Saider,
+            salter::Salter,
+            serder::Serder,
+        },
+        data::dat,
+        database::{
+            file::{counted, message, FileDatabase},
+            Database,
+        },
+        eventing::{
+            escrowing::EscrowEntry,
+            event::{incept, interact, registry_incept, rotate},
+            kevery::{Kevery, Outcome},
+        },
+        messaging::messagize,
+        parsing::parser::{parse, Message},
+    };
+
+    #[test]
+    fn file() {
+        let salter = Salter::new_with_raw(b"0123456789abcdef", None, Some(Tierage::min)).unwrap();
+        let signers = salter.signers(Some(3), None, Some("file"), None, None, None, None).unwrap();
+        let witness =
+            &salter.signers(Some(1), None, Some("wit"), None, Some(false), None, None).unwrap()[0];
+        let wit = witness.verfer().qb64().unwrap();
+        let digest = |i: usize| {
+            Diger::new_with_ser(&signers[i].verfer().qb64b().unwrap(), None)
+                .unwrap()
+                .qb64()
+                .unwrap()
+        };
+
+        let icp = incept(
+            &[&signers[0].verfer().qb64().unwrap()],
+            None,
+            Some(&[&digest(1)]),
+            None,
+            None,
+            Some(&[&wit]),
+            None,
+            None,
+            None,
+            None,
+            Some(matter::Codex::Blake3_256),
+            None,
+            None,
+        )
+        .unwrap();
+        let pre = icp.pre().unwrap();
+        let rot = rotate(
+            &pre,
+            &[&signers[1].verfer().qb64().unwrap()],
+            &icp.said().unwrap(),
+            None,
+            None,
+            None,
+            Some(&[&digest(2)]),
+            None,
+            Some(0),
+            Some(&[&wit]),
+            Some(&[&wit]),
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let ixn = interact(&pre, &rot.said().unwrap(), Some(2), None, None, None).unwrap();
+
+        let wigers = [witness.sign_indexed(&icp.raw(), false, 0, None).unwrap()];
+        let sigers = [signers[0].sign_indexed(&icp.raw(), false, 0, None).unwrap()];
+        let mut stream = messagize(&icp, Some(&sigers), None, Some(&wigers), None).unwrap();
+        for serder in [&rot, &ixn] {
+            let sigers = [signers[1].sign_indexed(&serder.raw(), false, 0, None).unwrap()];
+            stream += &messagize(serder, Some(&sigers), None, None, None).unwrap();
+        }
+
+        let mut kevery = Kevery::new();
+        assert_eq!(kevery.process_stream(&stream).unwrap(), vec![Outcome::Accepted; 3]);
+        assert_eq!(kevery.record(&pre, 0).unwrap().wits, vec![wit.clone()]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut db = FileDatabase::open(dir.path()).unwrap();
+        for record in kevery.fel(&pre) {
+            db.put_record(record).unwrap();
+        }
+        db.put_state(&kevery.kever(&pre).unwrap().state(None, None).unwrap()).unwrap();
+
+        let entry = |serder: Serder| EscrowEntry {
+            message: Message { serder, sigers: sigers.to_vec(), ..Default::default() },
+            reason: "out of order".to_string(),
+            created: SystemTime::now(),
+        };
+        db.put_escrow("ooes", &entry(ixn.clone())).unwrap();
+        db.put_escrow("ooes", &entry(rot.clone())).unwrap();
+        assert!(db.remove_escrow("ooes", &ixn.said().unwrap()).unwrap());
+        assert!(!db.remove_escrow("ooes", &ixn.said().unwrap()).unwrap());
+
+        let vcp = registry_incept(&pre, None, None, None, None, None, None, None).unwrap();
+        db.put_tel_event(&vcp).unwrap();
+        let (_, sad) =
+            Saider::saidify(&dat!({"d": "", "i": pre.as_str()}), None, None, None, None).unwrap();
+        db.put_sad(&sad).unwrap();
+        drop(db);
+
+        // everything is replayed on open, with witnesses derived from the events
+        let db = FileDatabase::open(dir.path()).unwrap();
+        let expected: Vec<_> = kevery.fel(&pre).into_iter().cloned().collect();
+        assert_eq!(db.fel(&pre).unwrap(), expected);
+        assert_eq!(db.kel(&pre).unwrap().len(), 3);
+        assert_eq!(db.event(&pre, 0).unwrap().unwrap().wits, vec![wit.clone()]);
+        assert!(db.event(&pre, 2).unwrap().unwrap().wits.is_empty());
+        assert_eq!(db.state(&pre).unwrap().unwrap().s, "2");
+        let escrowed = db.escrow("ooes").unwrap();
+        assert_eq!(escrowed.len(), 1);
+        assert_eq!(escrowed[0].message.serder, rot);
+        assert_eq!(escrowed[0].message.sigers[0].qb64().unwrap(), sigers[0].qb64().unwrap());
+        assert_eq!(db.tel(&vcp.pre().unwrap()).unwrap(), vec![vcp.clone()]);
+        assert_eq!(db.sad(&sad["d"].to_string().unwrap()).unwrap(), Some(sad.clone()));
+
+        // logs are CESR streams, of events with their attachments counted in one group
+        let kel = dir.path().join("kels").join(format!("{pre}.cesr"));
+        let stream = fs::read_to_string(&kel).unwrap();
+        let records: Vec<_> = expected.iter().map(message).collect();
+        assert_eq!(parse(&stream).unwrap(), records);
+        assert!(stream.starts_with(&String::from_utf8(icp.raw()).unwrap()));
+        assert_eq!(stream, records.iter().map(|m| counted(m).unwrap()).collect::<String>());
+        let tel = dir.path().join("tels").join(format!("{}.cesr", vcp.pre().unwrap()));
+        assert_eq!(fs::read(&tel).unwrap(), vcp.raw());
+
+        // a torn append is truncated on open, wherever it was cut
+        let len = fs::metadata(&kel).unwrap().len();
+        let entry = counted(&records[1]).unwrap();
+        let cuts = [1, 40, rot.raw().len(), rot.raw().len() + 2, entry.len() - 4, entry.len() - 1];
+        for cut in cuts {
+            let mut file = OpenOptions::new().append(true).open(&kel).unwrap();
+            file.write_all(&entry.as_bytes()[..cut]).unwrap();
+            drop(file);
+            let db = FileDatabase::open(dir.path()).unwrap();
+            assert_eq!(fs::metadata(&kel).unwrap().len(), len);
+            assert_eq!(db.fel(&pre).unwrap(), expected);
+        }
+
+        let len = fs::metadata(&tel).unwrap().len();
+        let mut file = OpenOptions::new().append(true).open(&tel).unwrap();
+        file.write_all(&vcp.raw()[..vcp.raw().len() / 2]).unwrap();
+        drop(file);
+        let mut db = FileDatabase::open(dir.path()).unwrap();
+        assert_eq!(fs::metadata(&tel).unwrap().len(), len);
+        assert_eq!(db.kel(&pre).unwrap().len(), 3);
+
+        // and later appends follow the last complete entry
+        let record = db.event(&pre, 2).unwrap().unwrap();
+        db.put_record(&record).unwrap();
+        let db = FileDatabase::open(dir.path()).unwrap();
+        assert_eq!(db.fel(&pre).unwrap(), expected);
+
+        // a corrupt complete entry is an error
+        let mut file = OpenOptions::new().append(true).open(&kel).unwrap();
+        file.write_all(&icp.raw()).unwrap();
+        file.write_all(b"-VAB-JAA").unwrap();
+        drop(file);
+        assert!(FileDatabase::open(dir.path()).is_err());
+
+        // a failed append leaves memory as it was
+        let dir = tempfile::tempdir().unwrap();
+        let mut db = FileDatabase::open(dir.path()).unwrap();
+        fs::create_dir(dir.path().join("kels").join(format!("{pre}.cesr"))).unwrap();
+        fs::create_dir(dir.path().join("states.jsonl")).unwrap();
+        assert!(db.put_record(&expected[0]).is_err());
+        assert!(db.kel(&pre).unwrap().is_empty());
+        assert!(db.record(&icp.said().unwrap()).unwrap().is_none());
+        assert!(db.put_state(&kevery.kever(&pre).unwrap().state(None, None).unwrap()).is_err());
+        assert!(db.state(&pre).unwrap().is_none());
+
+        // and nothing invalid is appended
+        assert!(db.put_sad(&dat!({"i": pre.as_str()})).is_err());
+        assert!(!dir.path().join("sads.jsonl").exists());
+    }
+}
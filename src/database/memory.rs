@@ -0,0 +1,364 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{
+    cesr::core::{common::Ids, creder::Creder, sadder::Sadder, serder::Serder},
+    data::Value,
+    database::Database,
+    error::{err, Error, Result},
+    eventing::{escrowing::EscrowEntry, record::EventRecord, state::KeyStateRecord},
+};
+
+/// A `Database` held in memory, for tests and embedded use.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryDatabase {
+    records: HashMap<String, EventRecord>,         // by said
+    kels: HashMap<String, Vec<String>>,            // accepted saids by prefix, indexed by sn
+    fels: HashMap<String, Vec<(u128, String)>>,    // first-seen ordinals and saids by prefix
+    states: HashMap<String, KeyStateRecord>,       // by prefix
+    escrows: HashMap<String, Vec<EscrowEntry>>,    // by escrow name
+    tels: HashMap<String, BTreeMap<u128, Serder>>, // by registry prefix and sn
+    sads: HashMap<String, Value>,                  // by said
+    acdcs: HashMap<String, Creder>,                // by said
+}
+
+impl MemoryDatabase {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fails if `record` cannot be stored, without storing it.
+    pub(crate) fn check_record(&self, record: &EventRecord) -> Result<()> {
+        let serder = &record.serder;
+        let said = serder.said()?;
+
+        if record.first_seen.is_some() {
+            let pre = serder.pre()?;
+            let sn = serder.sn()?;
+            let len = self.kels.get(&pre).map(|kel| kel.len()).unwrap_or_default();
+            if position(sn)? > len {
+                return err!(Error::Validation(format!(
+                    "accepted event {said} at sn = {sn} for {pre} beyond the end of its kel"
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether the event with said `said` is in the escrow named `name`.
+    pub(crate) fn escrowed(&self, name: &str, said: &str) -> bool {
+        self.escrows.get(name).is_some_and(|escrow| {
+            escrow.iter().any(|entry| entry.said().ok().as_deref() == Some(said))
+        })
+    }
+}
+
+/// The said of `sad`, its `d` field, which must be present.
+pub(crate) fn sad_said(sad: &Value) -> Result<String> {
+    let said = match sad.to_map()?.get(Ids::d) {
+        Some(said) => said.to_string()?,
+        None => "".to_string(),
+    };
+    if said.is_empty() {
+        return err!(Error::Value("missing said of sad".to_string()));
+    }
+
+    Ok(said)
+}
+
+/// The position of the event at `sn` in a KEL held in memory.
+fn position(sn: u128) -> Result<usize> {
+    match usize::try_from(sn) {
+        Ok(position) => Ok(position),
+        Err(_) => err!(Error::Value(format!("sn = {sn} out of range"))),
+    }
+}
+
+impl Database for MemoryDatabase {
+    fn put_record(&mut self, record: &EventRecord) -> Result<()> {
+        self.check_record(record)?;
+
+        let serder = &record.serder;
+        let said = serder.said()?;
+
+        if let Some((seqner, _)) = &record.first_seen {
+            let pre = serder.pre()?;
+            let index = position(serder.sn()?)?;
+
+            let kel = self.kels.entry(pre.clone()).or_default();
+            if kel.get(index) != Some(&said) {
+                kel.truncate(index);
+                kel.push(said.clone());
+            }
+
+            let ordinal = seqner.sn()?;
+            let fel = self.fels.entry(pre).or_default();
+            if !fel.iter().any(|(_, d)| *d == said) {
+                let index = fel.partition_point(|(o, _)| *o <= ordinal);
+                fel.insert(index, (ordinal, said.clone()));
+            }
+        }
+
+        self.records.insert(said, record.clone());
+
+        Ok(())
+    }
+
+    fn record(&self, said: &str) -> Result<Option<EventRecord>> {
+        Ok(self.records.get(said).cloned())
+    }
+
+    fn said(&self, pre: &str, sn: u128) -> Result<Option<String>> {
+        let index = position(sn)?;
+        Ok(self.kels.get(pre).and_then(|kel| kel.get(index)).cloned())
+    }
+
+    fn prefixes(&self) -> Result<Vec<String>> {
+        let mut pres: Vec<String> = self.kels.keys().cloned().collect();
+        pres.sort();
+
+        Ok(pres)
+    }
+
+    fn fel(&self, pre: &str) -> Result<Vec<EventRecord>> {
+        Ok(self
+            .fels
+            .get(pre)
+            .map(|fel| fel.iter().filter_map(|(_, said)| self.records.get(said).cloned()).collect())
+            .unwrap_or_default())
+    }
+
+    fn put_state(&mut self, state: &KeyStateRecord) -> Result<()> {
+        self.states.insert(state.i.clone(), state.clone());
+
+        Ok(())
+    }
+
+    fn state(&self, pre: &str) -> Result<Option<KeyStateRecord>> {
+        Ok(self.states.get(pre).cloned())
+    }
+
+    fn put_escrow(&mut self, name: &str, entry: &EscrowEntry) -> Result<()> {
+        let said = entry.said()?;
+        let escrow = self.escrows.entry(name.to_string()).or_default();
+        match escrow.iter_mut().find(|e| e.said().ok().as_ref() == Some(&said)) {
+            Some(escrowed) => *escrowed = entry.clone(),
+            None => escrow.push(entry.clone()),
+        }
+
+        Ok(())
+    }
+
+    fn escrow(&self, name: &str) -> Result<Vec<EscrowEntry>> {
+        Ok(self.escrows.get(name).cloned().unwrap_or_default())
+    }
+
+    fn remove_escrow(&mut self, name: &str, said: &str) -> Result<bool> {
+        let escrow = match self.escrows.get_mut(name) {
+            Some(escrow) => escrow,
+            None => return Ok(false),
+        };
+
+        let len = escrow.len();
+        escrow.retain(|entry| entry.said().ok().as_deref() != Some(said));
+
+        Ok(escrow.len() != len)
+    }
+
+    fn put_tel_event(&mut self, serder: &Serder) -> Result<()> {
+        self.tels.entry(serder.pre()?).or_default().insert(serder.sn()?, serder.clone());
+
+        Ok(())
+    }
+
+    fn tel_event(&self, pre: &str, sn: u128) -> Result<Option<Serder>> {
+        Ok(self.tels.get(pre).and_then(|tel| tel.get(&sn)).cloned())
+    }
+
+    fn put_sad(&mut self, sad: &Value) -> Result<()> {
+        self.sads.insert(sad_said(sad)?, sad.clone());
+
+        Ok(())
+    }
+
+    fn sad(&self, said: &str) -> Result<Option<Value>> {
+        Ok(self.sads.get(said).cloned())
+    }
+
+    fn put_acdc(&mut self, creder: &Creder) -> Result<()> {
+        self.acdcs.insert(creder.said()?, creder.clone());
+
+        Ok(())
+    }
+
+    fn acdc(&self, said: &str) -> Result<Option<Creder>> {
+        Ok(self.acdcs.get(said).cloned())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::SystemTime;
+
+    use crate::{
+        cesr::core::{
+            common::{versify, Identage, Tierage},
+            creder::Creder,
+            diger::Diger,
+            matter::{tables as matter, Matter},
+            sadder::Sadder,
+            saider::Saider,
+            salter::Salter,
+            seqner::Seqner,
+            serder::Serder,
+        },
+        data::dat,
+        database::{memory::MemoryDatabase, Database},
+        eventing::{
+            escrowing::EscrowEntry,
+            event::{incept, interact, registry_incept, rotate},
+            kevery::{Kevery, Outcome},
+        },
+        messaging::messagize,
+        parsing::parser::Message,
+    };
+
+    #[test]
+    fn memory() {
+        let salter = Salter::new_with_raw(b"0123456789abcdef", None, Some(Tierage::min)).unwrap();
+        let signers = salter.signers(Some(3), None, Some("db"), None, None, None, None).unwrap();
+        let digest = |i: usize| {
+            Diger::new_with_ser(&signers[i].verfer().qb64b().unwrap(), None)
+                .unwrap()
+                .qb64()
+                .unwrap()
+        };
+
+        let icp = incept(
+            &[&signers[0].verfer().qb64().unwrap()],
+            None,
+            Some(&[&digest(1)]),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(matter::Codex::Blake3_256),
+            None,
+            None,
+        )
+        .unwrap();
+        let pre = icp.pre().unwrap();
+        let ixn = interact(&pre, &icp.said().unwrap(), None, None, None, None).unwrap();
+        let rot = rotate(
+            &pre,
+            &[&signers[1].verfer().qb64().unwrap()],
+            &icp.said().unwrap(),
+            None,
+            None,
+            None,
+            Some(&[&digest(2)]),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let signed = |serder: &Serder, i: usize| {
+            let sigers = [signers[i].sign_indexed(&serder.raw(), false, 0, None).unwrap()];
+            messagize(serder, Some(&sigers), None, None, None).unwrap()
+        };
+
+        // the rotation supersedes the interaction
+        let mut kevery = Kevery::new();
+        let stream = signed(&icp, 0) + &signed(&ixn, 0) + &signed(&rot, 1);
+        assert_eq!(kevery.process_stream(&stream).unwrap(), vec![Outcome::Accepted; 3]);
+
+        let mut db = MemoryDatabase::new();
+        for record in kevery.fel(&pre) {
+            db.put_record(record).unwrap();
+        }
+        assert_eq!(db.prefixes().unwrap(), vec![pre.clone()]);
+        assert_eq!(db.kel(&pre).unwrap(), kevery.kel(&pre).unwrap());
+        assert_eq!(db.fel(&pre).unwrap().len(), 3);
+        assert_eq!(db.fel(&pre).unwrap()[1].serder, ixn);
+        assert_eq!(db.said(&pre, 1).unwrap(), Some(rot.said().unwrap()));
+        assert_eq!(db.record(&ixn.said().unwrap()).unwrap().unwrap().serder, ixn);
+        assert_eq!(db.event(&pre, 2).unwrap(), None);
+        assert_eq!(db.establishment(&pre, 0).unwrap().unwrap().serder, icp);
+        assert_eq!(db.establishment(&pre, u128::MAX).unwrap().unwrap().serder, rot);
+
+        // updating an accepted record keeps the kel
+        let mut record = db.event(&pre, 0).unwrap().unwrap();
+        record.wits = vec!["BAAA".to_string()];
+        db.put_record(&record).unwrap();
+        assert_eq!(db.kel(&pre).unwrap().len(), 2);
+        assert_eq!(db.event(&pre, 0).unwrap().unwrap().wits, ["BAAA"]);
+
+        // accepted records must extend the kel
+        let mut gap = record.clone();
+        gap.serder = interact(&pre, &rot.said().unwrap(), Some(5), None, None, None).unwrap();
+        gap.first_seen = Some((Seqner::new_with_sn(9).unwrap(), record.first_seen.unwrap().1));
+        assert!(db.put_record(&gap).is_err());
+
+        // sequence numbers beyond the address space are refused, not wrapped
+        let sn = u64::MAX as u128 + 1;
+        gap.serder = interact(&pre, &rot.said().unwrap(), Some(sn), None, None, None).unwrap();
+        assert!(db.put_record(&gap).is_err());
+        assert!(db.said(&pre, sn).is_err());
+        assert_eq!(db.kel(&pre).unwrap().len(), 2);
+
+        let state = kevery.kever(&pre).unwrap().state(None, None).unwrap();
+        db.put_state(&state).unwrap();
+        assert_eq!(db.state(&pre).unwrap(), Some(state));
+        assert_eq!(db.state("Eunknown").unwrap(), None);
+
+        let entry = |serder: Serder| EscrowEntry {
+            message: Message { serder, ..Default::default() },
+            reason: "out of order".to_string(),
+            created: SystemTime::now(),
+        };
+        db.put_escrow("ooes", &entry(ixn.clone())).unwrap();
+        db.put_escrow("ooes", &entry(ixn.clone())).unwrap();
+        db.put_escrow("ooes", &entry(rot.clone())).unwrap();
+        assert_eq!(db.escrow("ooes").unwrap().len(), 2);
+        assert!(db.escrow("pses").unwrap().is_empty());
+        assert!(db.remove_escrow("ooes", &ixn.said().unwrap()).unwrap());
+        assert!(!db.remove_escrow("ooes", &ixn.said().unwrap()).unwrap());
+        assert!(!db.remove_escrow("pses", &ixn.said().unwrap()).unwrap());
+        assert_eq!(db.escrow("ooes").unwrap()[0].message.serder, rot);
+
+        let vcp = registry_incept(&pre, None, None, None, None, None, None, None).unwrap();
+        db.put_tel_event(&vcp).unwrap();
+        assert_eq!(db.tel(&vcp.pre().unwrap()).unwrap(), vec![vcp.clone()]);
+        assert_eq!(db.tel_event(&vcp.pre().unwrap(), 1).unwrap(), None);
+
+        let (_, sad) =
+            Saider::saidify(&dat!({"d": "", "i": pre.as_str()}), None, None, None, None).unwrap();
+        db.put_sad(&sad).unwrap();
+        assert_eq!(db.sad(&sad["d"].to_string().unwrap()).unwrap(), Some(sad.clone()));
+        assert!(db.put_sad(&dat!({"i": pre.as_str()})).is_err());
+
+        let mut acdc = dat!({
+            "v": "ACDC10JSON000000_",
+            "d": "",
+            "i": pre.as_str(),
+            "s": "EE5uDJTq5cc6AEdqbyMpvARUjsK_chNdInf3xyRoCBcT",
+            "a": {"d": "", "dt": "2023-04-30T00:34:11.853572+00:00"},
+        });
+        let size = acdc.to_json().unwrap().len() as u32;
+        acdc["v"] = dat!(&versify(Some(Identage::ACDC), None, None, Some(size)).unwrap());
+        let (_, acdc) = Saider::saidify(&acdc, None, None, None, None).unwrap();
+        let creder = Creder::new_with_raw(acdc.to_json().unwrap().as_bytes()).unwrap();
+        db.put_acdc(&creder).unwrap();
+        assert_eq!(db.acdc(&creder.said().unwrap()).unwrap(), Some(creder));
+        assert_eq!(db.acdc(&sad["d"].to_string().unwrap()).unwrap(), None);
+    }
+}
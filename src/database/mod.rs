@@ -0,0 +1,103 @@
+pub mod file;
+pub mod memory;
+
+use crate::{
+    cesr::core::{creder::Creder, serder::Serder},
+    data::Value,
+    error::Result,
+    eventing::{escrowing::EscrowEntry, record::EventRecord, state::KeyStateRecord},
+};
+
+/// Storage for key event logs, transaction event logs, escrows, key states, SADs and ACDCs.
+/// Sequence numbers and first-seen ordinals are `u128`, as in events.
+pub trait Database: std::fmt::Debug {
+    /// Stores `record`, replacing any record of the same event. An accepted record, one with
+    /// first-seen data, also becomes the event of its identifier at its sequence number,
+    /// superseding any accepted events from there on.
+    fn put_record(&mut self, record: &EventRecord) -> Result<()>;
+
+    /// The record of the event with said `said`, accepted or not.
+    fn record(&self, said: &str) -> Result<Option<EventRecord>>;
+
+    /// The said of the accepted event of `pre` at `sn`.
+    fn said(&self, pre: &str, sn: u128) -> Result<Option<String>>;
+
+    /// The identifiers with accepted events.
+    fn prefixes(&self) -> Result<Vec<String>>;
+
+    /// The accepted records of `pre` in first-seen order, including those later superseded.
+    fn fel(&self, pre: &str) -> Result<Vec<EventRecord>>;
+
+    fn put_state(&mut self, state: &KeyStateRecord) -> Result<()>;
+
+    fn state(&self, pre: &str) -> Result<Option<KeyStateRecord>>;
+
+    /// Stores `entry` in the escrow named `name`, replacing any entry of the same event.
+    fn put_escrow(&mut self, name: &str, entry: &EscrowEntry) -> Result<()>;
+
+    /// The entries of the escrow named `name`, in escrow order.
+    fn escrow(&self, name: &str) -> Result<Vec<EscrowEntry>>;
+
+    /// Removes the entry of the event with said `said` from the escrow named `name`, returning
+    /// whether it was escrowed.
+    fn remove_escrow(&mut self, name: &str, said: &str) -> Result<bool>;
+
+    /// Stores a transaction event of a registry, replacing any event of the registry at its
+    /// sequence number.
+    fn put_tel_event(&mut self, serder: &Serder) -> Result<()>;
+
+    fn tel_event(&self, pre: &str, sn: u128) -> Result<Option<Serder>>;
+
+    /// Stores a self-addressing data structure under its said, the `d` field.
+    fn put_sad(&mut self, sad: &Value) -> Result<()>;
+
+    fn sad(&self, said: &str) -> Result<Option<Value>>;
+
+    fn put_acdc(&mut self, creder: &Creder) -> Result<()>;
+
+    fn acdc(&self, said: &str) -> Result<Option<Creder>>;
+
+    /// The accepted record of `pre` at `sn`.
+    fn event(&self, pre: &str, sn: u128) -> Result<Option<EventRecord>> {
+        match self.said(pre, sn)? {
+            Some(said) => self.record(&said),
+            None => Ok(None),
+        }
+    }
+
+    /// The accepted records of `pre`, in order.
+    fn kel(&self, pre: &str) -> Result<Vec<EventRecord>> {
+        let mut kel = vec![];
+        while let Some(record) = self.event(pre, kel.len() as u128)? {
+            kel.push(record);
+        }
+
+        Ok(kel)
+    }
+
+    /// The latest accepted establishment event of `pre` at or before `sn`.
+    fn establishment(&self, pre: &str, sn: u128) -> Result<Option<EventRecord>> {
+        let mut establishment = None;
+        for record in self.kel(pre)? {
+            if record.serder.sn()? > sn {
+                break;
+            }
+
+            if record.serder.est()? {
+                establishment = Some(record);
+            }
+        }
+
+        Ok(establishment)
+    }
+
+    /// The transaction events of the registry `pre`, in order.
+    fn tel(&self, pre: &str) -> Result<Vec<Serder>> {
+        let mut tel = vec![];
+        while let Some(serder) = self.tel_event(pre, tel.len() as u128)? {
+            tel.push(serder);
+        }
+
+        Ok(tel)
+    }
+}
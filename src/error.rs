@@ -66,6 +66,14 @@ pub enum Error {
     Validation(String),
     #[error("{0}")]
     Derivation(String),
+    #[error("out of order: {0}")]
+    OutOfOrder(String),
+    #[error("missing signatures: {0}")]
+    MissingSignatures(String),
+    #[error("missing receipts: {0}")]
+    MissingReceipts(String),
+    #[error("missing delegation: {0}")]
+    MissingDelegation(String),
 }
 
 macro_rules! err {
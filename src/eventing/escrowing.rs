@@ -0,0 +1,363 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::{Duration, SystemTime},
+};
+
+use crate::{cesr::core::sadder::Sadder, error::Result, parsing::parser::Message};
+
+/// Limits of an escrow. Entries older than `timeout` are dropped when the escrow is next
+/// reprocessed. No more than `limit` entries are held at once for any one identifier, so that
+/// the events of one identifier cannot crowd out those of others, and no more than `capacity`
+/// in all, so that events of made up identifiers cannot exhaust memory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EscrowConfig {
+    pub timeout: Duration,
+    pub limit: usize,
+    pub capacity: usize,
+}
+
+impl Default for EscrowConfig {
+    fn default() -> Self {
+        Self { timeout: Duration::from_secs(3600), limit: 1024, capacity: 65536 }
+    }
+}
+
+/// A message held back from processing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EscrowEntry {
+    pub message: Message,
+    pub reason: String,      // why the message was escrowed
+    pub created: SystemTime, // when the message was first escrowed
+}
+
+impl EscrowEntry {
+    pub fn said(&self) -> Result<String> {
+        self.message.serder.said()
+    }
+
+    pub fn pre(&self) -> Result<String> {
+        self.message.serder.pre()
+    }
+
+    fn expired(&self, timeout: Duration) -> bool {
+        match self.created.elapsed() {
+            Ok(elapsed) => elapsed >= timeout,
+            Err(_) => false,
+        }
+    }
+}
+
+/// Messages waiting on some condition before they can be processed, in escrow order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Escrow {
+    config: EscrowConfig,
+    entries: BTreeMap<u64, EscrowEntry>, // by escrow order
+    saids: HashMap<String, u64>,         // escrow order of each entry, by said
+    counts: HashMap<String, usize>,      // number of entries of each identifier, by prefix
+    next: u64,                           // escrow order of the next entry
+}
+
+impl Escrow {
+    pub fn new(config: &EscrowConfig) -> Self {
+        Self { config: config.clone(), ..Default::default() }
+    }
+
+    pub fn config(&self) -> &EscrowConfig {
+        &self.config
+    }
+
+    /// The entries, in escrow order.
+    pub fn entries(&self) -> Vec<&EscrowEntry> {
+        self.entries.values().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, said: &str) -> Option<&EscrowEntry> {
+        self.entries.get(self.saids.get(said)?)
+    }
+
+    /// Escrows `message`, returning false if a message with the same said is already escrowed or
+    /// the escrow is full, in all or for its identifier.
+    pub fn add(&mut self, message: &Message, reason: &str) -> Result<bool> {
+        let said = message.serder.said()?;
+        let pre = message.serder.pre()?;
+        let held = self.counts.get(&pre).copied().unwrap_or(0);
+        if self.saids.contains_key(&said)
+            || held >= self.config.limit
+            || self.entries.len() >= self.config.capacity
+        {
+            return Ok(false);
+        }
+
+        self.insert(
+            said,
+            pre,
+            EscrowEntry {
+                message: message.clone(),
+                reason: reason.to_string(),
+                created: SystemTime::now(),
+            },
+        );
+
+        Ok(true)
+    }
+
+    /// Merges the signatures and receipts of `message` that the entry with the same said does
+    /// not hold yet into that entry, keeping its creation time. Earlier deliveries are never
+    /// dropped, so a delivery with forged attachments cannot displace a genuine one. Returns
+    /// false if no such entry is escrowed.
+    pub(crate) fn merge(&mut self, message: &Message, reason: &str) -> Result<bool> {
+        let said = message.serder.said()?;
+        let entry = match self.saids.get(&said).and_then(|order| self.entries.get_mut(order)) {
+            Some(entry) => entry,
+            None => return Ok(false),
+        };
+
+        let escrowed = &mut entry.message;
+        for siger in &message.sigers {
+            if !escrowed.sigers.contains(siger) {
+                escrowed.sigers.push(siger.clone());
+            }
+        }
+        for wiger in &message.wigers {
+            if !escrowed.wigers.contains(wiger) {
+                escrowed.wigers.push(wiger.clone());
+            }
+        }
+        for cigar in &message.cigars {
+            if !escrowed.cigars.contains(cigar) {
+                escrowed.cigars.push(cigar.clone());
+            }
+        }
+        entry.reason = reason.to_string();
+
+        Ok(true)
+    }
+
+    pub fn remove(&mut self, said: &str) -> Option<EscrowEntry> {
+        let order = self.saids.remove(said)?;
+        let entry = self.entries.remove(&order)?;
+        if let Ok(pre) = entry.pre() {
+            self.uncount(&pre);
+        }
+
+        Some(entry)
+    }
+
+    /// Drops every entry, or only those of the identifier `pre`, returning the number dropped.
+    pub fn purge(&mut self, pre: Option<&str>) -> usize {
+        let len = self.entries.len();
+        match pre {
+            Some(pre) => {
+                if self.counts.contains_key(pre) {
+                    self.retain(|entry| entry.pre().ok().as_deref() != Some(pre));
+                }
+            }
+            None => {
+                self.entries.clear();
+                self.saids.clear();
+                self.counts.clear();
+            }
+        }
+
+        len - self.entries.len()
+    }
+
+    /// Drops and returns the entries that have been escrowed for longer than the timeout.
+    pub fn expire(&mut self) -> Vec<EscrowEntry> {
+        let timeout = self.config.timeout;
+        self.retain(|entry| !entry.expired(timeout))
+    }
+
+    /// Removes and returns every entry, for reprocessing.
+    pub(crate) fn take(&mut self) -> Vec<EscrowEntry> {
+        self.saids.clear();
+        self.counts.clear();
+
+        std::mem::take(&mut self.entries).into_values().collect()
+    }
+
+    /// Returns an entry taken for reprocessing to the escrow, keeping its original creation time.
+    pub(crate) fn restore(&mut self, entry: EscrowEntry) {
+        let (said, pre) = match (entry.said(), entry.pre()) {
+            (Ok(said), Ok(pre)) => (said, pre),
+            _ => return,
+        };
+
+        if !self.saids.contains_key(&said) {
+            self.insert(said, pre, entry);
+        }
+    }
+
+    fn insert(&mut self, said: String, pre: String, entry: EscrowEntry) {
+        self.entries.insert(self.next, entry);
+        self.saids.insert(said, self.next);
+        *self.counts.entry(pre).or_insert(0) += 1;
+        self.next += 1;
+    }
+
+    fn uncount(&mut self, pre: &str) {
+        if let Some(count) = self.counts.get_mut(pre) {
+            *count -= 1;
+            if *count == 0 {
+                self.counts.remove(pre);
+            }
+        }
+    }
+
+    /// Keeps the entries for which `keep` holds, returning the others in escrow order.
+    fn retain(&mut self, keep: impl Fn(&EscrowEntry) -> bool) -> Vec<EscrowEntry> {
+        let dropped: Vec<u64> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| !keep(entry))
+            .map(|(order, _)| *order)
+            .collect();
+
+        let mut entries = vec![];
+        for order in dropped {
+            if let Some(entry) = self.entries.remove(&order) {
+                if let Ok(said) = entry.said() {
+                    self.saids.remove(&said);
+                }
+                if let Ok(pre) = entry.pre() {
+                    self.uncount(&pre);
+                }
+                entries.push(entry);
+            }
+        }
+
+        entries
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::{
+        cesr::core::{common::Tierage, matter::Matter, sadder::Sadder, salter::Salter},
+        eventing::{
+            escrowing::{Escrow, EscrowConfig},
+            event::{incept, interact},
+        },
+        parsing::parser::Message,
+    };
+
+    #[test]
+    fn escrow() {
+        let salter = Salter::new_with_raw(b"0123456789abcdef", None, Some(Tierage::min)).unwrap();
+        let signers = salter.signers(Some(2), None, Some("esc"), None, None, None, None).unwrap();
+
+        let mut messages = vec![];
+        for signer in &signers {
+            let icp = incept(
+                &[&signer.verfer().qb64().unwrap()],
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            let ixn = interact(&icp.pre().unwrap(), &icp.said().unwrap(), None, None, None, None)
+                .unwrap();
+            messages.push(Message { serder: ixn, ..Default::default() });
+        }
+        let pre = messages[0].serder.pre().unwrap();
+        let later =
+            interact(&pre, &messages[0].serder.said().unwrap(), Some(2), None, None, None).unwrap();
+        let later = Message { serder: later, ..Default::default() };
+        let said = messages[0].serder.said().unwrap();
+
+        let config = EscrowConfig { timeout: Duration::from_secs(60), limit: 1, capacity: 8 };
+        let mut escrow = Escrow::new(&config);
+        assert!(escrow.is_empty());
+        assert!(escrow.add(&messages[0], "out of order").unwrap());
+        assert!(!escrow.add(&messages[0], "out of order").unwrap());
+        // full for this identifier, not for others
+        assert!(!escrow.add(&later, "out of order").unwrap());
+        assert!(escrow.add(&messages[1], "out of order").unwrap());
+        assert_eq!(escrow.len(), 2);
+        assert_eq!(
+            escrow.remove(&messages[1].serder.said().unwrap()).unwrap().message,
+            messages[1]
+        );
+        assert_eq!(escrow.get(&said).unwrap().reason, "out of order");
+        assert_eq!(escrow.entries()[0].pre().unwrap(), pre);
+        assert!(escrow.merge(&messages[0], "partially signed").unwrap());
+        assert!(!escrow.merge(&messages[1], "partially signed").unwrap());
+        assert_eq!(escrow.get(&said).unwrap().reason, "partially signed");
+
+        // later deliveries add their signatures to those escrowed, never displacing them
+        let sigers: Vec<_> = signers
+            .iter()
+            .enumerate()
+            .map(|(i, s)| s.sign_indexed(&messages[0].serder.raw(), false, i as u32, None).unwrap())
+            .collect();
+        let mut delivery = messages[0].clone();
+        delivery.sigers = vec![sigers[0].clone()];
+        assert!(escrow.merge(&delivery, "out of order").unwrap());
+        delivery.sigers = sigers.clone();
+        assert!(escrow.merge(&delivery, "out of order").unwrap());
+        delivery.sigers = vec![sigers[1].clone()];
+        assert!(escrow.merge(&delivery, "out of order").unwrap());
+        assert_eq!(escrow.get(&said).unwrap().message.sigers, sigers);
+
+        assert!(escrow.expire().is_empty());
+        assert_eq!(escrow.purge(Some(&messages[1].serder.pre().unwrap())), 0);
+        assert_eq!(escrow.purge(Some(&pre)), 1);
+        assert!(escrow.remove(&said).is_none());
+
+        let mut escrow =
+            Escrow::new(&EscrowConfig { timeout: Duration::ZERO, limit: 8, capacity: 8 });
+        for message in &messages {
+            assert!(escrow.add(message, "out of order").unwrap());
+        }
+        assert_eq!(escrow.remove(&said).unwrap().message, messages[0]);
+        assert_eq!(escrow.expire().len(), 1);
+        assert!(escrow.is_empty());
+
+        for message in &messages {
+            assert!(escrow.add(message, "out of order").unwrap());
+        }
+        assert_eq!(escrow.purge(None), 2);
+
+        // full in all, whatever the identifier
+        let config = EscrowConfig { capacity: 1, ..Default::default() };
+        let mut escrow = Escrow::new(&config);
+        assert!(escrow.add(&messages[0], "out of order").unwrap());
+        assert!(!escrow.add(&messages[1], "out of order").unwrap());
+        assert!(escrow.remove(&said).is_some());
+        assert!(escrow.add(&messages[1], "out of order").unwrap());
+
+        // reprocessing keeps escrow order and the counts of each identifier
+        let mut escrow = Escrow::new(&EscrowConfig { limit: 1, ..Default::default() });
+        assert!(escrow.add(&messages[1], "out of order").unwrap());
+        assert!(escrow.add(&messages[0], "out of order").unwrap());
+        let entries = escrow.take();
+        assert!(escrow.is_empty());
+        assert!(escrow.add(&later, "out of order").unwrap());
+        assert_eq!(escrow.purge(Some(&pre)), 1);
+        for entry in entries {
+            escrow.restore(entry);
+        }
+        assert_eq!(escrow.entries()[0].message, messages[1]);
+        assert_eq!(escrow.entries()[1].message, messages[0]);
+        assert!(!escrow.add(&later, "out of order").unwrap());
+    }
+}
@@ -0,0 +1,757 @@
+use crate::{
+    cesr::core::{
+        common::{versify, Ids, Ilkage, Serialage, Version, CURRENT_VERSION},
+        dater::Dater,
+        matter::{tables as matter, Matter},
+        number::Number,
+        saider::Saider,
+        salter::Salter,
+        serder::Serder,
+        tholder::Tholder,
+    },
+    data::{dat, Value},
+    error::{err, Error, Result},
+    eventing::{
+        traiting::Traits,
+        witnessing::{has_duplicates, WitnessConfig},
+    },
+    pathing::pather::Pather,
+    prefexing::prefixer::Prefixer,
+    seal::Seal,
+};
+
+fn default_sith(count: usize, floor: i64) -> Value {
+    let s: i64 = (count as i64 + 1) / 2;
+    dat!(std::cmp::max(s, floor))
+}
+
+// when intive, numeric thresholds are serialized as ints rather than hex strings
+fn threshold_value(tholder: &Tholder, intive: bool) -> Result<Value> {
+    if let Some(n) = tholder.num()? {
+        if intive && n < u32::MAX {
+            return Ok(dat!(n));
+        }
+    }
+
+    tholder.sith()
+}
+
+fn toad_value(toader: &Number, intive: bool) -> Result<Value> {
+    Ok(if intive && toader.num()? < u32::MAX as u128 {
+        dat!(toader.num()? as i64)
+    } else {
+        dat!(&toader.numh()?)
+    })
+}
+
+fn stamp_value(stamp: Option<&str>) -> Result<String> {
+    match stamp {
+        Some(stamp) => Ok(stamp.to_string()),
+        None => Dater::new(None, None, None, None, None, None)?.dts(),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn incept(
+    keys: &[&str],          // current keys qb64
+    sith: Option<&Value>,   // current signing threshold
+    ndigs: Option<&[&str]>, // next keys qb64
+    nsith: Option<&Value>,  // next signing threshold
+    toad: Option<u128>,     // witness threshold number
+    wits: Option<&[&str]>,  // witness identifier prefixes qb64
+    cnfg: Option<&[&str]>,  // configuration traits
+    data: Option<&[Seal]>,  // anchored seals
+    version: Option<&Version>,
+    kind: Option<&str>,
+    code: Option<&str>,
+    intive: Option<bool>, // sith, nsith and toad are ints, not hex when numeric
+    delpre: Option<&str>, // delegator identifier prefix
+) -> Result<Serder> {
+    let version = version.unwrap_or(CURRENT_VERSION);
+    let kind = kind.unwrap_or(Serialage::JSON);
+    let intive = intive.unwrap_or(false);
+
+    let vs = &versify(None, Some(version), Some(kind), Some(0))?;
+    let ilk = if delpre.is_none() { Ilkage::icp } else { Ilkage::dip };
+    let sner = Number::new_with_num(0)?;
+
+    let sith = if let Some(sith) = sith { sith.clone() } else { default_sith(keys.len(), 1) };
+
+    let tholder = Tholder::new_with_sith(&sith)?;
+    if let Some(n) = tholder.num()? {
+        if n < 1 {
+            return err!(Error::Value(format!("invalid sith = {n} less than 1")));
+        }
+    }
+    if tholder.size() as usize > keys.len() {
+        return err!(Error::Value(format!(
+            "invalid sith size = {s} for keys = {keys:?}",
+            s = tholder.size()
+        )));
+    }
+
+    let ndigs = ndigs.unwrap_or(&[]);
+    let nsith = if let Some(nsith) = nsith { nsith.clone() } else { default_sith(ndigs.len(), 0) };
+
+    let ntholder = Tholder::new_with_sith(&nsith)?;
+    if ntholder.size() as usize > ndigs.len() {
+        return err!(Error::Value(format!(
+            "invalid nsith size = {s} for keys = {keys:?}",
+            s = ntholder.size()
+        )));
+    }
+
+    let wits = wits.unwrap_or(&[]);
+    let toader = Number::new_with_num(WitnessConfig::new(wits, toad)?.toad())?;
+
+    let cnfg = cnfg.unwrap_or(&[]);
+    Traits::new(cnfg)?;
+    let data = Seal::to_values(data.unwrap_or(&[]));
+
+    let kt = threshold_value(&tholder, intive)?;
+    let nt = threshold_value(&ntholder, intive)?;
+    let toad = toad_value(&toader, intive)?;
+
+    let keys: Vec<Value> = keys.iter().map(|key| dat!(*key)).collect();
+    let ndigs: Vec<Value> = ndigs.iter().map(|dig| dat!(*dig)).collect();
+    let wits: Vec<Value> = wits.iter().map(|wit| dat!(*wit)).collect();
+    let cnfg: Vec<Value> = cnfg.iter().map(|cfg| dat!(*cfg)).collect();
+
+    let mut ked = dat!({
+        "v": vs,
+        "t": ilk,
+        "d": "",
+        "i": "",
+        "s": &sner.numh()?,
+        "kt": kt,
+        "k": keys.as_slice(),
+        "nt": nt,
+        "n": ndigs.as_slice(),
+        "bt": toad,
+        "b": wits.as_slice(),
+        "c": cnfg.as_slice(),
+        "a": data.as_slice()
+    });
+
+    let code = if let Some(delpre) = delpre {
+        let label = Ids::di;
+        ked[label] = dat!(delpre);
+        Some(code.unwrap_or(matter::Codex::Blake3_256))
+    } else {
+        code
+    };
+
+    let prefixer = if delpre.is_none() && code.is_none() && keys.len() == 1 {
+        let prefixer = Prefixer::new_with_qb64(&keys[0].to_string()?)?;
+        if prefixer.digestive() {
+            return err!(Error::Value(format!(
+                "invalid code, digestive = {c}, must be derived from ked",
+                c = prefixer.code()
+            )));
+        }
+        prefixer
+    } else {
+        let prefixer = Prefixer::new_with_ked(&ked, None, code)?;
+        if delpre.is_some() && !prefixer.digestive() {
+            return err!(Error::Value(format!(
+                "invalid derivation code = {c} for delegation, must be digestive",
+                c = prefixer.code()
+            )));
+        }
+        prefixer
+    };
+
+    let label = Ids::i;
+    ked[label] = dat!(&prefixer.qb64()?);
+    let ked = if prefixer.digestive() {
+        let label = Ids::d;
+        ked[label] = dat!(&prefixer.qb64()?);
+        ked
+    } else {
+        let (_, ked) = Saider::saidify(&ked, None, None, None, None)?;
+        ked
+    };
+
+    Serder::new_with_ked(&ked, None, None)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn rotate(
+    pre: &str,              // identifier prefix qb64
+    keys: &[&str],          // current keys qb64
+    dig: &str,              // said of prior event
+    ilk: Option<&str>,      // rot or drt
+    sn: Option<u128>,       // sequence number
+    sith: Option<&Value>,   // current signing threshold
+    ndigs: Option<&[&str]>, // next keys qb64
+    nsith: Option<&Value>,  // next signing threshold
+    toad: Option<u128>,     // witness threshold number
+    wits: Option<&[&str]>,  // prior witness identifier prefixes qb64
+    cuts: Option<&[&str]>,  // witness prefixes to remove
+    adds: Option<&[&str]>,  // witness prefixes to add
+    data: Option<&[Seal]>,  // anchored seals
+    version: Option<&Version>,
+    kind: Option<&str>,
+    intive: Option<bool>, // sith, nsith and toad are ints, not hex when numeric
+) -> Result<Serder> {
+    let ilk = ilk.unwrap_or(Ilkage::rot);
+    let sn = sn.unwrap_or(1);
+    let version = version.unwrap_or(CURRENT_VERSION);
+    let kind = kind.unwrap_or(Serialage::JSON);
+    let intive = intive.unwrap_or(false);
+
+    let vs = &versify(None, Some(version), Some(kind), Some(0))?;
+    if ![Ilkage::rot, Ilkage::drt].contains(&ilk) {
+        return err!(Error::Value(format!("invalid ilk = {ilk} for rotation")));
+    }
+
+    let sner = Number::new_with_num(sn)?;
+    if sner.num()? < 1 {
+        return err!(Error::Value(format!("invalid sn = {sn} for rotation")));
+    }
+
+    let sith = if let Some(sith) = sith { sith.clone() } else { default_sith(keys.len(), 1) };
+
+    let tholder = Tholder::new_with_sith(&sith)?;
+    if let Some(n) = tholder.num()? {
+        if n < 1 {
+            return err!(Error::Value(format!("invalid sith = {n} less than 1")));
+        }
+    }
+    if tholder.size() as usize > keys.len() {
+        return err!(Error::Value(format!(
+            "invalid sith size = {s} for keys = {keys:?}",
+            s = tholder.size()
+        )));
+    }
+
+    let ndigs = ndigs.unwrap_or(&[]);
+    let nsith = if let Some(nsith) = nsith { nsith.clone() } else { default_sith(ndigs.len(), 0) };
+
+    let ntholder = Tholder::new_with_sith(&nsith)?;
+    if ntholder.size() as usize > ndigs.len() {
+        return err!(Error::Value(format!(
+            "invalid nsith size = {s} for ndigs = {ndigs:?}",
+            s = ntholder.size()
+        )));
+    }
+
+    let wits = wits.unwrap_or(&[]);
+    if has_duplicates(wits) {
+        return err!(Error::Value(format!("invalid wits = {wits:?}, has duplicates")));
+    }
+
+    let cuts = cuts.unwrap_or(&[]);
+    if has_duplicates(cuts) {
+        return err!(Error::Value(format!("invalid cuts = {cuts:?}, has duplicates")));
+    }
+    if cuts.iter().any(|cut| !wits.contains(cut)) {
+        return err!(Error::Value(format!("invalid cuts = {cuts:?}, not all members in wits")));
+    }
+
+    let adds = adds.unwrap_or(&[]);
+    if has_duplicates(adds) {
+        return err!(Error::Value(format!("invalid adds = {adds:?}, has duplicates")));
+    }
+    if adds.iter().any(|add| cuts.contains(add)) {
+        return err!(Error::Value(format!("intersecting cuts = {cuts:?} and adds = {adds:?}")));
+    }
+    if adds.iter().any(|add| wits.contains(add)) {
+        return err!(Error::Value(format!("intersecting wits = {wits:?} and adds = {adds:?}")));
+    }
+
+    let mut newits: Vec<&str> = wits.iter().filter(|wit| !cuts.contains(wit)).copied().collect();
+    newits.extend_from_slice(adds);
+
+    let toader = Number::new_with_num(WitnessConfig::new(&newits, toad)?.toad())?;
+
+    let data = Seal::to_values(data.unwrap_or(&[]));
+
+    let kt = threshold_value(&tholder, intive)?;
+    let nt = threshold_value(&ntholder, intive)?;
+    let toad = toad_value(&toader, intive)?;
+
+    let keys: Vec<Value> = keys.iter().map(|key| dat!(*key)).collect();
+    let ndigs: Vec<Value> = ndigs.iter().map(|dig| dat!(*dig)).collect();
+    let cuts: Vec<Value> = cuts.iter().map(|wit| dat!(*wit)).collect();
+    let adds: Vec<Value> = adds.iter().map(|wit| dat!(*wit)).collect();
+
+    let ked = dat!({
+        "v": vs,
+        "t": ilk,
+        "d": "",
+        "i": pre,
+        "s": &sner.numh()?,
+        "p": dig,
+        "kt": kt,
+        "k": keys.as_slice(),
+        "nt": nt,
+        "n": ndigs.as_slice(),
+        "bt": toad,
+        "br": cuts.as_slice(),
+        "ba": adds.as_slice(),
+        "a": data.as_slice()
+    });
+    let (_, ked) = Saider::saidify(&ked, None, None, None, None)?;
+
+    Serder::new_with_ked(&ked, None, None)
+}
+
+pub fn interact(
+    pre: &str,             // identifier prefix qb64
+    dig: &str,             // said of prior event
+    sn: Option<u128>,      // sequence number
+    data: Option<&[Seal]>, // anchored seals
+    version: Option<&Version>,
+    kind: Option<&str>,
+) -> Result<Serder> {
+    let sn = sn.unwrap_or(1);
+    let version = version.unwrap_or(CURRENT_VERSION);
+    let kind = kind.unwrap_or(Serialage::JSON);
+
+    let vs = &versify(None, Some(version), Some(kind), Some(0))?;
+    let ilk = Ilkage::ixn;
+
+    let sner = Number::new_with_num(sn)?;
+    if sner.num()? < 1 {
+        return err!(Error::Value(format!("invalid sn = {sn} for interaction")));
+    }
+
+    let data = Seal::to_values(data.unwrap_or(&[]));
+
+    let ked = dat!({
+        "v": vs,
+        "t": ilk,
+        "d": "",
+        "i": pre,
+        "s": &sner.numh()?,
+        "p": dig,
+        "a": data.as_slice()
+    });
+    let (_, ked) = Saider::saidify(&ked, None, None, None, None)?;
+
+    Serder::new_with_ked(&ked, None, None)
+}
+
+pub fn query(
+    route: Option<&str>,       // namespaced path of query
+    reply_route: Option<&str>, // namespaced path of the expected reply
+    query: Option<&Value>,     // query parameters
+    stamp: Option<&str>,       // iso8601 timestamp, defaults to now
+    version: Option<&Version>,
+    kind: Option<&str>,
+) -> Result<Serder> {
+    let version = version.unwrap_or(CURRENT_VERSION);
+    let kind = kind.unwrap_or(Serialage::JSON);
+
+    let vs = &versify(None, Some(version), Some(kind), Some(0))?;
+    let ilk = Ilkage::qry;
+
+    let empty = dat!({});
+    let query = query.unwrap_or(&empty);
+
+    let ked = dat!({
+        "v": vs,
+        "t": ilk,
+        "d": "",
+        "dt": &stamp_value(stamp)?,
+        "r": route.unwrap_or(""),
+        "rr": reply_route.unwrap_or(""),
+        "q": query.clone()
+    });
+    let (_, ked) = Saider::saidify(&ked, None, None, None, None)?;
+
+    Serder::new_with_ked(&ked, None, None)
+}
+
+pub fn reply(
+    route: Option<&str>,  // namespaced path of reply
+    data: Option<&Value>, // attribute section of reply
+    stamp: Option<&str>,  // iso8601 timestamp, defaults to now
+    version: Option<&Version>,
+    kind: Option<&str>,
+) -> Result<Serder> {
+    let version = version.unwrap_or(CURRENT_VERSION);
+    let kind = kind.unwrap_or(Serialage::JSON);
+
+    let vs = &versify(None, Some(version), Some(kind), Some(0))?;
+    let ilk = Ilkage::rpy;
+
+    let empty = dat!({});
+    let data = data.unwrap_or(&empty);
+
+    let ked = dat!({
+        "v": vs,
+        "t": ilk,
+        "d": "",
+        "dt": &stamp_value(stamp)?,
+        "r": route.unwrap_or(""),
+        "a": data.clone()
+    });
+    let (_, ked) = Saider::saidify(&ked, None, None, None, None)?;
+
+    Serder::new_with_ked(&ked, None, None)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn registry_incept(
+    pre: &str,             // issuer identifier prefix qb64
+    toad: Option<u128>,    // backer threshold number
+    baks: Option<&[&str]>, // backer identifier prefixes qb64
+    nonce: Option<&str>,   // unique nonce, defaults to a random salt
+    cnfg: Option<&[&str]>, // configuration traits
+    version: Option<&Version>,
+    kind: Option<&str>,
+    code: Option<&str>,
+) -> Result<Serder> {
+    let version = version.unwrap_or(CURRENT_VERSION);
+    let kind = kind.unwrap_or(Serialage::JSON);
+    let code = code.unwrap_or(matter::Codex::Blake3_256);
+
+    let vs = &versify(None, Some(version), Some(kind), Some(0))?;
+    let ilk = Ilkage::vcp;
+    let sner = Number::new_with_num(0)?;
+
+    let cnfg = cnfg.unwrap_or(&[]);
+    let traits = Traits::new(cnfg)?;
+
+    let baks = baks.unwrap_or(&[]);
+    let toader = Number::new_with_num(WitnessConfig::new(baks, toad)?.toad())?;
+
+    let nonce = match nonce {
+        Some(nonce) => nonce.to_string(),
+        None => Salter::new_with_defaults(None)?.qb64()?,
+    };
+
+    let baks: Vec<Value> = baks.iter().map(|bak| dat!(*bak)).collect();
+    let cnfg: Vec<Value> = cnfg.iter().map(|cfg| dat!(*cfg)).collect();
+
+    let mut ked = dat!({
+        "v": vs,
+        "t": ilk,
+        "d": "",
+        "i": "",
+        "ii": pre,
+        "s": &sner.numh()?,
+        "c": cnfg.as_slice(),
+        "bt": &toader.numh()?,
+        "b": baks.as_slice(),
+        "n": &nonce
+    });
+
+    let prefixer = Prefixer::new_with_ked(&ked, None, Some(code))?;
+    if !prefixer.digestive() {
+        return err!(Error::Value(format!(
+            "invalid derivation code = {c} for registry, must be digestive",
+            c = prefixer.code()
+        )));
+    }
+
+    ked[Ids::i] = dat!(&prefixer.qb64()?);
+    ked[Ids::d] = dat!(&prefixer.qb64()?);
+
+    let serder = Serder::new_with_ked(&ked, None, None)?;
+    traits.validate_registry(&serder)?;
+
+    Ok(serder)
+}
+
+pub fn prod(
+    route: Option<&str>,       // namespaced path of prod
+    reply_route: Option<&str>, // namespaced path of the expected bare
+    paths: &[Pather],          // paths of the requested SAD content
+    stamp: Option<&str>,       // iso8601 timestamp, defaults to now
+    version: Option<&Version>,
+    kind: Option<&str>,
+) -> Result<Serder> {
+    let version = version.unwrap_or(CURRENT_VERSION);
+    let kind = kind.unwrap_or(Serialage::JSON);
+
+    let vs = &versify(None, Some(version), Some(kind), Some(0))?;
+    let ilk = Ilkage::pro;
+
+    if paths.is_empty() {
+        return err!(Error::Value("prod requires at least one path".to_string()));
+    }
+
+    let mut ps: Vec<Value> = vec![];
+    for pather in paths {
+        ps.push(dat!(&pather.qb64()?));
+    }
+
+    let ked = dat!({
+        "v": vs,
+        "t": ilk,
+        "d": "",
+        "dt": &stamp_value(stamp)?,
+        "r": route.unwrap_or(""),
+        "rr": reply_route.unwrap_or(""),
+        "q": {
+            "p": ps.as_slice()
+        }
+    });
+    let (_, ked) = Saider::saidify(&ked, None, None, None, None)?;
+
+    Serder::new_with_ked(&ked, None, None)
+}
+
+pub fn bare(
+    route: Option<&str>, // namespaced path of bare, usually the reply route of a prod
+    sads: &[(Pather, Value)], // SAD content, keyed by the path it was requested at
+    stamp: Option<&str>, // iso8601 timestamp, defaults to now
+    version: Option<&Version>,
+    kind: Option<&str>,
+) -> Result<Serder> {
+    let version = version.unwrap_or(CURRENT_VERSION);
+    let kind = kind.unwrap_or(Serialage::JSON);
+
+    let vs = &versify(None, Some(version), Some(kind), Some(0))?;
+    let ilk = Ilkage::bar;
+
+    let mut data = dat!({});
+    for (pather, sad) in sads {
+        let saider = Saider::new_with_qb64(&sad[Ids::d].to_string()?)?;
+        if !saider.verify(sad, Some(true), None, None, None, None)? {
+            return err!(Error::Value(format!(
+                "invalid said = {d} for sad at path = {p}",
+                d = saider.qb64()?,
+                p = pather.qb64()?
+            )));
+        }
+
+        data[pather.qb64()?.as_str()] = sad.clone();
+    }
+
+    let ked = dat!({
+        "v": vs,
+        "t": ilk,
+        "d": "",
+        "dt": &stamp_value(stamp)?,
+        "r": route.unwrap_or(""),
+        "a": data
+    });
+    let (_, ked) = Saider::saidify(&ked, None, None, None, None)?;
+
+    Serder::new_with_ked(&ked, None, None)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        cesr::core::{
+            common::{Ilkage, Tierage},
+            diger::Diger,
+            matter::{tables as matter, Matter},
+            sadder::Sadder,
+            salter::Salter,
+        },
+        data::dat,
+        eventing::event::{incept, interact, query, reply, rotate},
+        seal::Seal,
+    };
+
+    #[test]
+    fn inception() {
+        let aids = &[
+            "BEy_EvE8OUMqj0AgCJ3wOCOrIVHVtwubYAysPyaAv9VI",
+            "BC9Df6ssUZQFQZJYVUyfudw4WTQsugGcvVD_Z4ChFGE4",
+            "BEejlxZytU7gjUwtgkmNKmBWiFPKSsXjk_uxzoun8dtK",
+        ];
+
+        let srdr = incept(
+            &[aids[0]],
+            None,
+            None,
+            None,
+            None,
+            Some(&[aids[1], aids[2]]),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(srdr.ilk().unwrap(), Ilkage::icp);
+        assert_eq!(srdr.bn().unwrap(), 2);
+        assert!(srdr.prior().unwrap().is_none());
+        assert!(srdr.delpre().unwrap().is_none());
+
+        // duplicate witnesses, even when not adjacent
+        assert!(incept(
+            &[aids[0]],
+            None,
+            None,
+            None,
+            None,
+            Some(&[aids[1], aids[2], aids[1]]),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .is_err());
+
+        // toad out of range
+        assert!(incept(
+            &[aids[0]],
+            None,
+            None,
+            None,
+            Some(3),
+            Some(&[aids[1], aids[2]]),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn rotation_and_interaction() {
+        let salter = Salter::new_with_raw(b"0123456789abcdef", None, Some(Tierage::min)).unwrap();
+        let signers = salter.signers(Some(3), None, Some("rot"), None, None, None, None).unwrap();
+        let keys: Vec<String> = signers.iter().map(|s| s.verfer().qb64().unwrap()).collect();
+        let digs: Vec<String> = signers
+            .iter()
+            .map(|s| {
+                Diger::new_with_ser(&s.verfer().qb64b().unwrap(), None).unwrap().qb64().unwrap()
+            })
+            .collect();
+
+        let wits = &[
+            "BC9Df6ssUZQFQZJYVUyfudw4WTQsugGcvVD_Z4ChFGE4",
+            "BEejlxZytU7gjUwtgkmNKmBWiFPKSsXjk_uxzoun8dtK",
+        ];
+
+        let icp = incept(
+            &[&keys[0]],
+            None,
+            Some(&[&digs[1]]),
+            None,
+            None,
+            Some(wits),
+            None,
+            None,
+            None,
+            None,
+            Some(matter::Codex::Blake3_256),
+            None,
+            None,
+        )
+        .unwrap();
+        let pre = icp.pre().unwrap();
+        assert_eq!(pre, icp.said().unwrap());
+
+        let rot = rotate(
+            &pre,
+            &[&keys[1]],
+            &icp.said().unwrap(),
+            None,
+            None,
+            None,
+            Some(&[&digs[2]]),
+            None,
+            None,
+            Some(wits),
+            Some(&[wits[0]]),
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(rot.ilk().unwrap(), Ilkage::rot);
+        assert_eq!(rot.sn().unwrap(), 1);
+        assert_eq!(rot.prior().unwrap().unwrap(), icp.said().unwrap());
+        assert_eq!(rot.bn().unwrap(), 1);
+        assert_eq!(rot.ked()["br"], dat!([wits[0]]));
+
+        // cutting a non-witness
+        assert!(rotate(
+            &pre,
+            &[&keys[1]],
+            &icp.said().unwrap(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(&[wits[0]]),
+            Some(&[wits[1]]),
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .is_err());
+
+        // adding an existing witness
+        assert!(rotate(
+            &pre,
+            &[&keys[1]],
+            &icp.said().unwrap(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(wits),
+            None,
+            Some(&[wits[1]]),
+            None,
+            None,
+            None,
+            None,
+        )
+        .is_err());
+
+        let seals = [
+            Seal::Event { i: pre.clone(), s: "1".to_string(), d: rot.said().unwrap() },
+            Seal::Digest { d: digs[0].clone() },
+        ];
+        let ixn = interact(&pre, &rot.said().unwrap(), Some(2), Some(&seals), None, None).unwrap();
+        assert_eq!(ixn.ilk().unwrap(), Ilkage::ixn);
+        assert_eq!(ixn.sn().unwrap(), 2);
+        assert_eq!(ixn.prior().unwrap().unwrap(), rot.said().unwrap());
+        assert!(!ixn.est().unwrap());
+        assert_eq!(ixn.seals().unwrap(), seals);
+        assert_eq!(ixn.ked()["a"][1], dat!({ "d": &digs[0] }));
+        assert!(rot.seals().unwrap().is_empty());
+
+        assert!(interact(&pre, &rot.said().unwrap(), Some(0), None, None, None).is_err());
+    }
+
+    #[test]
+    fn query_and_reply() {
+        let stamp = "2021-01-01T00:00:00.000000+00:00";
+        let pre = "BEy_EvE8OUMqj0AgCJ3wOCOrIVHVtwubYAysPyaAv9VI";
+
+        let qry =
+            query(Some("ksn"), Some("/ksn"), Some(&dat!({ "i": pre })), Some(stamp), None, None)
+                .unwrap();
+        assert_eq!(qry.ilk().unwrap(), Ilkage::qry);
+        assert_eq!(qry.ked()["q"]["i"], dat!(pre));
+        assert_eq!(qry.ked()["dt"], dat!(stamp));
+
+        let rpy =
+            reply(Some("/end/role/add"), Some(&dat!({ "cid": pre })), Some(stamp), None, None)
+                .unwrap();
+        assert_eq!(rpy.ilk().unwrap(), Ilkage::rpy);
+        assert_eq!(rpy.ked()["r"], dat!("/end/role/add"));
+        assert_eq!(rpy.ked()["a"]["cid"], dat!(pre));
+    }
+}
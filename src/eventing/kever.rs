@@ -0,0 +1,891 @@
+use crate::{
+    cesr::core::{
+        common::{Ids, Ilkage, CURRENT_VERSION},
+        dater::Dater,
+        diger::Diger,
+        indexer::{tables as indexer, Indexer},
+        matter::Matter,
+        number::Number,
+        sadder::Sadder,
+        serder::Serder,
+        siger::Siger,
+        tholder::Tholder,
+        verfer::Verfer,
+    },
+    error::{err, Error, Result},
+    eventing::{
+        state::{strings, KeyStateRecord, StateEstEvent},
+        traiting::Traits,
+        witnessing::{has_duplicates, WitnessConfig},
+    },
+    prefexing::prefixer::Prefixer,
+};
+
+const ICP_LABELS: &[&str] = &[
+    Ids::v,
+    Ids::t,
+    Ids::d,
+    Ids::i,
+    Ids::s,
+    Ids::kt,
+    Ids::k,
+    Ids::nt,
+    Ids::n,
+    Ids::bt,
+    Ids::b,
+    Ids::c,
+    Ids::a,
+];
+const DIP_LABELS: &[&str] = &[
+    Ids::v,
+    Ids::t,
+    Ids::d,
+    Ids::i,
+    Ids::s,
+    Ids::kt,
+    Ids::k,
+    Ids::nt,
+    Ids::n,
+    Ids::bt,
+    Ids::b,
+    Ids::c,
+    Ids::a,
+    Ids::di,
+];
+const ROT_LABELS: &[&str] = &[
+    Ids::v,
+    Ids::t,
+    Ids::d,
+    Ids::i,
+    Ids::s,
+    Ids::p,
+    Ids::kt,
+    Ids::k,
+    Ids::nt,
+    Ids::n,
+    Ids::bt,
+    Ids::br,
+    Ids::ba,
+    Ids::a,
+];
+const IXN_LABELS: &[&str] = &[Ids::v, Ids::t, Ids::d, Ids::i, Ids::s, Ids::p, Ids::a];
+
+fn validate_labels(serder: &Serder) -> Result<()> {
+    let ilk = serder.ilk()?;
+    let labels = match ilk.as_str() {
+        Ilkage::icp => ICP_LABELS,
+        Ilkage::dip => DIP_LABELS,
+        Ilkage::rot | Ilkage::drt => ROT_LABELS,
+        Ilkage::ixn => IXN_LABELS,
+        _ => return err!(Error::Validation(format!("unexpected ilk = {ilk} for key event"))),
+    };
+
+    let map = serder.ked().to_map()?;
+    for label in labels {
+        if !map.contains_key(*label) {
+            return err!(Error::Validation(format!("missing label = {label} in {ilk} event")));
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_said(serder: &Serder) -> Result<()> {
+    if !serder.saider().verify(&serder.ked(), Some(true), None, None, None, None)? {
+        return err!(Error::Validation(format!("invalid said = {d}", d = serder.said()?)));
+    }
+
+    Ok(())
+}
+
+/// The verified signatures among `sigers`, one per index. Signatures that do not verify against
+/// the key at their index are dropped.
+pub(crate) fn verify_sigers(
+    serder: &Serder,
+    verfers: &[Verfer],
+    sigers: &[Siger],
+) -> Result<Vec<Siger>> {
+    let mut verified: Vec<Siger> = vec![];
+    for siger in sigers {
+        let index = siger.index();
+        if index as usize >= verfers.len() || verified.iter().any(|v| v.index() == index) {
+            continue;
+        }
+
+        if verfers[index as usize].verify(&siger.raw(), &serder.raw())? {
+            verified.push(siger.clone());
+        }
+    }
+
+    Ok(verified)
+}
+
+pub(crate) fn indices(sigers: &[Siger]) -> Vec<u32> {
+    sigers.iter().map(|siger| siger.index()).collect()
+}
+
+pub(crate) fn satisfied(tholder: &Tholder, indices: &[u32]) -> Result<bool> {
+    // weighted thresholds only weigh indices within their size
+    let indices: Vec<u32> =
+        indices.iter().filter(|i| !tholder.weighted() || **i < tholder.size()).copied().collect();
+    if indices.is_empty() {
+        return Ok(false);
+    }
+
+    tholder.satisfy(&indices)
+}
+
+/// Key state introduced by an establishment event.
+struct Establishment {
+    verfers: Vec<Verfer>,
+    tholder: Tholder,
+    digers: Vec<Diger>,
+    ntholder: Tholder,
+    wits: Vec<String>,
+    toad: u128,
+}
+
+impl Establishment {
+    /// Reads the key state of an establishment event, applying any witness changes to `wits`.
+    fn new(serder: &Serder, wits: &[String]) -> Result<Self> {
+        let verfers = serder.verfers()?;
+        if verfers.is_empty() {
+            return err!(Error::Validation(format!(
+                "no keys in establishment event at sn = {sn}",
+                sn = serder.sn()?
+            )));
+        }
+
+        let tholder = match serder.tholder()? {
+            Some(tholder) => tholder,
+            None => return err!(Error::Validation("missing signing threshold".to_string())),
+        };
+        if tholder.size() as usize > verfers.len() {
+            return err!(Error::Validation(format!(
+                "invalid signing threshold size = {s} for {n} keys",
+                s = tholder.size(),
+                n = verfers.len()
+            )));
+        }
+
+        let digers = serder.digers()?;
+        let ntholder = match serder.ntholder()? {
+            Some(ntholder) => ntholder,
+            None => return err!(Error::Validation("missing next signing threshold".to_string())),
+        };
+        if ntholder.size() as usize > digers.len() {
+            return err!(Error::Validation(format!(
+                "invalid next signing threshold size = {s} for {n} digests",
+                s = ntholder.size(),
+                n = digers.len()
+            )));
+        }
+
+        let ked = serder.ked();
+        let wits = if [Ilkage::icp, Ilkage::dip].contains(&serder.ilk()?.as_str()) {
+            strings(&ked[Ids::b])?
+        } else {
+            let cuts = strings(&ked[Ids::br])?;
+            let adds = strings(&ked[Ids::ba])?;
+
+            let cut_refs: Vec<&str> = cuts.iter().map(|cut| cut.as_str()).collect();
+            let add_refs: Vec<&str> = adds.iter().map(|add| add.as_str()).collect();
+            if has_duplicates(&cut_refs) || cuts.iter().any(|cut| !wits.contains(cut)) {
+                return err!(Error::Validation(format!("invalid cuts = {cuts:?}")));
+            }
+            if has_duplicates(&add_refs)
+                || adds.iter().any(|add| wits.contains(add) || cuts.contains(add))
+            {
+                return err!(Error::Validation(format!("invalid adds = {adds:?}")));
+            }
+
+            let mut newits: Vec<String> =
+                wits.iter().filter(|wit| !cuts.contains(wit)).cloned().collect();
+            newits.extend(adds);
+            newits
+        };
+
+        let toad = match serder.bner()? {
+            Some(bner) => bner.num()?,
+            None => return err!(Error::Validation("missing witness threshold".to_string())),
+        };
+        let wit_refs: Vec<&str> = wits.iter().map(|wit| wit.as_str()).collect();
+        let config = match WitnessConfig::new(&wit_refs, Some(toad)) {
+            Ok(config) => config,
+            Err(e) => return err!(Error::Validation(e.to_string())),
+        };
+
+        Ok(Self { verfers, tholder, digers, ntholder, wits, toad: config.toad() })
+    }
+}
+
+/// Key state of a single identifier, built up by validating each event of its KEL in order.
+/// Updates are atomic: an event that fails validation leaves the state untouched.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Kever {
+    prefixer: Prefixer,
+    serder: Serder,
+    est: Serder,
+    verfers: Vec<Verfer>,
+    tholder: Tholder,
+    digers: Vec<Diger>,
+    ntholder: Tholder,
+    wits: Vec<String>,
+    toad: u128,
+    traits: Traits,
+    delpre: Option<String>,
+}
+
+impl Kever {
+    /// Validates an inception event (icp or dip) and its controller signatures, creating the key
+    /// state of its identifier. Approval of a dip by its delegator is not checked here.
+    pub fn new(serder: &Serder, sigers: &[Siger]) -> Result<Self> {
+        validate_labels(serder)?;
+
+        let ilk = serder.ilk()?;
+        if ![Ilkage::icp, Ilkage::dip].contains(&ilk.as_str()) {
+            return err!(Error::Validation(format!("expected inception, got ilk = {ilk}")));
+        }
+
+        if serder.sn()? != 0 {
+            return err!(Error::Validation(format!(
+                "invalid sn = {sn} for inception",
+                sn = serder.sn()?
+            )));
+        }
+
+        let pre = serder.pre()?;
+        let prefixer = Prefixer::new_with_qb64(&pre)?;
+        if !prefixer.verify(&serder.ked(), Some(true))? {
+            return err!(Error::Validation(format!("invalid prefix = {pre} for inception")));
+        }
+
+        if prefixer.digestive() {
+            if serder.said()? != pre {
+                return err!(Error::Validation(format!(
+                    "said = {d} does not match self-addressing prefix = {pre}",
+                    d = serder.said()?
+                )));
+            }
+        } else {
+            validate_said(serder)?;
+        }
+
+        let establishment = Establishment::new(serder, &[])?;
+        if !prefixer.transferable()
+            && (!establishment.digers.is_empty() || !establishment.wits.is_empty())
+        {
+            return err!(Error::Validation(format!(
+                "non-transferable prefix = {pre} with next digests or witnesses"
+            )));
+        }
+
+        let delpre = serder.delpre()?;
+        if let Some(delpre) = &delpre {
+            if !Prefixer::new_with_qb64(delpre)?.digestive() || !prefixer.digestive() {
+                return err!(Error::Validation(format!(
+                    "invalid delegation of {pre} by {delpre}, prefixes must be digestive"
+                )));
+            }
+        }
+
+        let traits = Traits::new_with_serder(serder)?;
+
+        let indices = indices(&verify_sigers(serder, &establishment.verfers, sigers)?);
+        if !satisfied(&establishment.tholder, &indices)? {
+            return err!(Error::MissingSignatures(format!(
+                "unsatisfied signing threshold for {ilk} of {pre}, verified indices = {indices:?}"
+            )));
+        }
+
+        Ok(Self {
+            prefixer,
+            serder: serder.clone(),
+            est: serder.clone(),
+            verfers: establishment.verfers,
+            tholder: establishment.tholder,
+            digers: establishment.digers,
+            ntholder: establishment.ntholder,
+            wits: establishment.wits,
+            toad: establishment.toad,
+            traits,
+            delpre,
+        })
+    }
+
+    /// Validates the next event of the KEL (rot, drt or ixn) and its controller signatures, then
+    /// applies it to the key state. Rotations must be signed by keys whose digests were committed
+    /// to as next keys by the prior establishment event, satisfying its next threshold.
+    pub fn update(&mut self, serder: &Serder, sigers: &[Siger]) -> Result<()> {
+        validate_labels(serder)?;
+
+        let pre = self.pre()?;
+        if serder.pre()? != pre {
+            return err!(Error::Validation(format!(
+                "mismatched prefix = {p} for kever of {pre}",
+                p = serder.pre()?
+            )));
+        }
+
+        let ilk = serder.ilk()?;
+        let sn = serder.sn()?;
+        let next = self.sn()? + 1;
+        if sn > next {
+            return err!(Error::OutOfOrder(format!(
+                "event at sn = {sn} for {pre}, expected sn = {next}"
+            )));
+        }
+        if sn < next {
+            return err!(Error::Validation(format!(
+                "stale event at sn = {sn} for {pre}, expected sn = {next}"
+            )));
+        }
+
+        validate_said(serder)?;
+
+        if serder.prior()? != Some(self.said()?) {
+            return err!(Error::Validation(format!(
+                "event at sn = {sn} does not chain to prior event {d}",
+                d = self.said()?
+            )));
+        }
+
+        if !self.transferable() {
+            return err!(Error::Validation(format!(
+                "non-transferable or abandoned identifier {pre} cannot accept {ilk} at sn = {sn}"
+            )));
+        }
+
+        self.traits.validate_event(serder)?;
+
+        match ilk.as_str() {
+            Ilkage::rot | Ilkage::drt => {
+                let delegated = self.delpre.is_some();
+                if delegated != (ilk == Ilkage::drt) {
+                    return err!(Error::Validation(format!(
+                        "invalid ilk = {ilk} for {d}delegated identifier {pre}",
+                        d = if delegated { "" } else { "non-" }
+                    )));
+                }
+
+                let establishment = Establishment::new(serder, &self.wits)?;
+
+                let verified = verify_sigers(serder, &establishment.verfers, sigers)?;
+                let indices = indices(&verified);
+                if !satisfied(&establishment.tholder, &indices)? {
+                    return err!(Error::MissingSignatures(format!(
+                        "unsatisfied signing threshold for {ilk} of {pre} at sn = {sn}, verified \
+                        indices = {indices:?}"
+                    )));
+                }
+
+                let ondices = self.exposed(&establishment.verfers, &verified)?;
+                if !satisfied(&self.ntholder, &ondices)? {
+                    return err!(Error::MissingSignatures(format!(
+                        "unsatisfied prior next threshold for {ilk} of {pre} at sn = {sn}, \
+                        exposed indices = {ondices:?}"
+                    )));
+                }
+
+                self.serder = serder.clone();
+                self.est = serder.clone();
+                self.verfers = establishment.verfers;
+                self.tholder = establishment.tholder;
+                self.digers = establishment.digers;
+                self.ntholder = establishment.ntholder;
+                self.wits = establishment.wits;
+                self.toad = establishment.toad;
+            }
+            Ilkage::ixn => {
+                let indices = indices(&verify_sigers(serder, &self.verfers, sigers)?);
+                if !satisfied(&self.tholder, &indices)? {
+                    return err!(Error::MissingSignatures(format!(
+                        "unsatisfied signing threshold for {ilk} of {pre} at sn = {sn}, verified \
+                        indices = {indices:?}"
+                    )));
+                }
+
+                self.serder = serder.clone();
+            }
+            _ => return err!(Error::Validation(format!("unexpected ilk = {ilk} for update"))),
+        }
+
+        Ok(())
+    }
+
+    /// The prior next indices (ondices) of verified rotation signatures whose keys match the
+    /// committed next key digests.
+    fn exposed(&self, verfers: &[Verfer], verified: &[Siger]) -> Result<Vec<u32>> {
+        let mut ondices = vec![];
+        for siger in verified {
+            if indexer::CurrentSigCodex::has_code(&siger.code()) {
+                continue;
+            }
+
+            let ondex = siger.ondex();
+            if ondex as usize >= self.digers.len() || ondices.contains(&ondex) {
+                continue;
+            }
+
+            let diger = &self.digers[ondex as usize];
+            if diger.verify(&verfers[siger.index() as usize].qb64b()?)? {
+                ondices.push(ondex);
+            }
+        }
+
+        Ok(ondices)
+    }
+
+    pub fn prefixer(&self) -> &Prefixer {
+        &self.prefixer
+    }
+
+    pub fn pre(&self) -> Result<String> {
+        self.prefixer.qb64()
+    }
+
+    /// Latest accepted event.
+    pub fn serder(&self) -> &Serder {
+        &self.serder
+    }
+
+    /// Latest accepted establishment event.
+    pub fn est(&self) -> &Serder {
+        &self.est
+    }
+
+    pub fn sn(&self) -> Result<u128> {
+        self.serder.sn()
+    }
+
+    pub fn said(&self) -> Result<String> {
+        self.serder.said()
+    }
+
+    pub fn verfers(&self) -> &[Verfer] {
+        &self.verfers
+    }
+
+    pub fn tholder(&self) -> &Tholder {
+        &self.tholder
+    }
+
+    pub fn digers(&self) -> &[Diger] {
+        &self.digers
+    }
+
+    pub fn ntholder(&self) -> &Tholder {
+        &self.ntholder
+    }
+
+    pub fn wits(&self) -> &[String] {
+        &self.wits
+    }
+
+    pub fn toad(&self) -> u128 {
+        self.toad
+    }
+
+    pub fn traits(&self) -> &Traits {
+        &self.traits
+    }
+
+    pub fn delpre(&self) -> Option<&str> {
+        self.delpre.as_deref()
+    }
+
+    /// False for non-transferable prefixes and abandoned identifiers, which committed to no
+    /// next keys and so may accept no further events.
+    pub fn transferable(&self) -> bool {
+        self.prefixer.transferable() && !self.digers.is_empty()
+    }
+
+    /// The key state record of this key state. `fner` is the first-seen ordinal of the latest
+    /// event and defaults to its sn.
+    pub fn state(&self, fner: Option<u128>, stamp: Option<&str>) -> Result<KeyStateRecord> {
+        let dt = match stamp {
+            Some(stamp) => stamp.to_string(),
+            None => Dater::new(None, None, None, None, None, None)?.dts()?,
+        };
+
+        let ked = self.est.ked();
+        let ee = if [Ilkage::icp, Ilkage::dip].contains(&self.est.ilk()?.as_str()) {
+            StateEstEvent {
+                s: self.est.sner()?.numh()?,
+                d: self.est.said()?,
+                br: vec![],
+                ba: vec![],
+            }
+        } else {
+            StateEstEvent {
+                s: self.est.sner()?.numh()?,
+                d: self.est.said()?,
+                br: strings(&ked[Ids::br])?,
+                ba: strings(&ked[Ids::ba])?,
+            }
+        };
+
+        Ok(KeyStateRecord {
+            vn: CURRENT_VERSION.clone(),
+            i: self.pre()?,
+            s: self.serder.sner()?.numh()?,
+            p: self.serder.prior()?.unwrap_or_default(),
+            d: self.said()?,
+            f: Number::new_with_num(fner.unwrap_or(self.sn()?))?.numh()?,
+            dt,
+            et: self.serder.ilk()?,
+            kt: ked[Ids::kt].clone(),
+            k: strings(&ked[Ids::k])?,
+            nt: ked[Ids::nt].clone(),
+            n: strings(&ked[Ids::n])?,
+            bt: Number::new_with_num(self.toad)?.numh()?,
+            b: self.wits.clone(),
+            c: self.traits.codes().iter().map(|code| code.to_string()).collect(),
+            ee,
+            di: self.delpre.clone().unwrap_or_default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        cesr::core::{
+            common::{Ilkage, Tierage},
+            diger::Diger,
+            indexer::{tables as indexer, Indexer},
+            matter::{tables as matter, Matter},
+            sadder::Sadder,
+            salter::Salter,
+            serder::Serder,
+            siger::Siger,
+        },
+        data::dat,
+        error::Error,
+        eventing::{
+            event::{incept, interact, rotate},
+            kever::Kever,
+            state::KeyStateRecord,
+        },
+        signing::Signer,
+    };
+
+    fn digest(signer: &Signer) -> String {
+        Diger::new_with_ser(&signer.verfer().qb64b().unwrap(), None).unwrap().qb64().unwrap()
+    }
+
+    fn sign(serder: &Serder, signers: &[&Signer]) -> Vec<Siger> {
+        signers
+            .iter()
+            .enumerate()
+            .map(|(i, signer)| signer.sign_indexed(&serder.raw(), false, i as u32, None).unwrap())
+            .collect()
+    }
+
+    fn is_missing_signatures(e: &anyhow::Error) -> bool {
+        matches!(e.downcast_ref::<Error>(), Some(Error::MissingSignatures(_)))
+    }
+
+    #[test]
+    fn single_sig() {
+        let stamp = "2021-01-01T00:00:00.000000+00:00";
+        let salter = Salter::new_with_raw(b"0123456789abcdef", None, Some(Tierage::min)).unwrap();
+        let signers = salter.signers(Some(4), None, Some("kever"), None, None, None, None).unwrap();
+        let keys: Vec<String> = signers.iter().map(|s| s.verfer().qb64().unwrap()).collect();
+
+        let icp = incept(
+            &[&keys[0]],
+            None,
+            Some(&[&digest(&signers[1])]),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(matter::Codex::Blake3_256),
+            None,
+            None,
+        )
+        .unwrap();
+        let pre = icp.pre().unwrap();
+
+        // signed by the wrong key
+        let e = Kever::new(&icp, &sign(&icp, &[&signers[1]])).unwrap_err();
+        assert!(is_missing_signatures(&e));
+        assert!(Kever::new(&icp, &[]).is_err());
+
+        let mut kever = Kever::new(&icp, &sign(&icp, &[&signers[0]])).unwrap();
+        assert_eq!(kever.pre().unwrap(), pre);
+        assert_eq!(kever.sn().unwrap(), 0);
+        assert_eq!(kever.verfers()[0].qb64().unwrap(), keys[0]);
+        assert_eq!(kever.digers()[0].qb64().unwrap(), digest(&signers[1]));
+        assert!(kever.transferable());
+        assert_eq!(kever.delpre(), None);
+
+        let ixn = interact(&pre, &icp.said().unwrap(), Some(1), None, None, None).unwrap();
+        kever.update(&ixn, &sign(&ixn, &[&signers[0]])).unwrap();
+        assert_eq!(kever.sn().unwrap(), 1);
+        assert_eq!(kever.est(), &icp);
+
+        // rotation to keys not committed to by the prior next digests
+        let rot = rotate(
+            &pre,
+            &[&keys[2]],
+            &ixn.said().unwrap(),
+            None,
+            Some(2),
+            None,
+            Some(&[&digest(&signers[3])]),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let before = kever.clone();
+        let e = kever.update(&rot, &sign(&rot, &[&signers[2]])).unwrap_err();
+        assert!(is_missing_signatures(&e));
+        assert_eq!(kever, before);
+
+        let rot = rotate(
+            &pre,
+            &[&keys[1]],
+            &ixn.said().unwrap(),
+            None,
+            Some(2),
+            None,
+            Some(&[&digest(&signers[2])]),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        // current-only signatures do not expose prior next keys
+        let only = signers[1].sign_indexed(&rot.raw(), true, 0, None).unwrap();
+        assert!(kever.update(&rot, &[only]).is_err());
+
+        kever.update(&rot, &sign(&rot, &[&signers[1]])).unwrap();
+        assert_eq!(kever.sn().unwrap(), 2);
+        assert_eq!(kever.est(), &rot);
+        assert_eq!(kever.verfers()[0].qb64().unwrap(), keys[1]);
+        assert_eq!(kever.digers()[0].qb64().unwrap(), digest(&signers[2]));
+
+        // the previous keys no longer sign
+        let ixn = interact(&pre, &rot.said().unwrap(), Some(3), None, None, None).unwrap();
+        assert!(kever.update(&ixn, &sign(&ixn, &[&signers[0]])).is_err());
+
+        // out of order, stale and unchained events
+        let ahead = interact(&pre, &rot.said().unwrap(), Some(4), None, None, None).unwrap();
+        let e = kever.update(&ahead, &sign(&ahead, &[&signers[1]])).unwrap_err();
+        assert!(matches!(e.downcast_ref::<Error>(), Some(Error::OutOfOrder(_))));
+        let stale = interact(&pre, &icp.said().unwrap(), Some(1), None, None, None).unwrap();
+        assert!(kever.update(&stale, &sign(&stale, &[&signers[1]])).is_err());
+        let unchained = interact(&pre, &icp.said().unwrap(), Some(3), None, None, None).unwrap();
+        assert!(kever.update(&unchained, &sign(&unchained, &[&signers[1]])).is_err());
+
+        kever.update(&ixn, &sign(&ixn, &[&signers[1]])).unwrap();
+        assert_eq!(kever.said().unwrap(), ixn.said().unwrap());
+
+        // tampered event
+        let mut ked = ixn.ked();
+        ked["s"] = dat!("4");
+        ked["p"] = dat!(&ixn.said().unwrap());
+        let forged = Serder::new_with_ked(&ked, None, None).unwrap();
+        assert!(kever.update(&forged, &sign(&forged, &[&signers[1]])).is_err());
+
+        let kel = [icp.clone(), ixn.clone()];
+        assert!(KeyStateRecord::from_kel(&kel, None, Some(stamp)).is_err());
+
+        let state = kever.state(None, Some(stamp)).unwrap();
+        assert_eq!(state.sn().unwrap(), 3);
+        assert_eq!(state.d, ixn.said().unwrap());
+        assert_eq!(state.et, Ilkage::ixn);
+        assert_eq!(state.ee.d, rot.said().unwrap());
+        assert_eq!(state.k, [keys[1].clone()]);
+    }
+
+    #[test]
+    fn multisig_and_witnesses() {
+        let stamp = "2021-01-01T00:00:00.000000+00:00";
+        let salter = Salter::new_with_raw(b"0123456789abcdef", None, Some(Tierage::min)).unwrap();
+        let signers = salter.signers(Some(6), None, Some("multi"), None, None, None, None).unwrap();
+        let keys: Vec<String> = signers.iter().map(|s| s.verfer().qb64().unwrap()).collect();
+        let wsalter = Salter::new_with_raw(b"fedcba9876543210", None, Some(Tierage::min)).unwrap();
+        let witnesses =
+            wsalter.signers(Some(3), None, Some("wit"), None, Some(false), None, None).unwrap();
+        let wits: Vec<String> = witnesses.iter().map(|w| w.verfer().qb64().unwrap()).collect();
+
+        let icp = incept(
+            &[&keys[0], &keys[1], &keys[2]],
+            Some(&dat!(2)),
+            Some(&[&digest(&signers[3]), &digest(&signers[4]), &digest(&signers[5])]),
+            Some(&dat!(2)),
+            None,
+            Some(&[&wits[0], &wits[1]]),
+            Some(&["DND", "EO"]),
+            None,
+            None,
+            None,
+            Some(matter::Codex::Blake3_256),
+            None,
+            None,
+        )
+        .unwrap();
+        let pre = icp.pre().unwrap();
+
+        // one of three is not enough
+        let sigers = sign(&icp, &[&signers[0], &signers[1], &signers[2]]);
+        let e = Kever::new(&icp, &sigers[..1]).unwrap_err();
+        assert!(is_missing_signatures(&e));
+
+        // duplicated signatures count once
+        let doubled = [sigers[0].clone(), sigers[0].clone()];
+        assert!(Kever::new(&icp, &doubled).is_err());
+
+        let mut kever = Kever::new(&icp, &sigers[1..]).unwrap();
+        assert_eq!(kever.wits(), [wits[0].clone(), wits[1].clone()]);
+        assert_eq!(kever.toad(), 2);
+        assert!(kever.traits().est_only());
+
+        // establishment only
+        let ixn = interact(&pre, &icp.said().unwrap(), None, None, None, None).unwrap();
+        assert!(kever.update(&ixn, &sign(&ixn, &[&signers[0], &signers[1]])).is_err());
+
+        let rot = rotate(
+            &pre,
+            &[&keys[3], &keys[4], &keys[5]],
+            &icp.said().unwrap(),
+            None,
+            None,
+            Some(&dat!(2)),
+            Some(&[&digest(&signers[0])]),
+            None,
+            None,
+            Some(&[&wits[0], &wits[1]]),
+            Some(&[&wits[0]]),
+            Some(&[&wits[2]]),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        // signed by two of the new keys, exposing two of the three prior next digests
+        let sigers = sign(&rot, &[&signers[3], &signers[4], &signers[5]]);
+        kever.update(&rot, &sigers[1..]).unwrap();
+        assert_eq!(kever.wits(), [wits[1].clone(), wits[2].clone()]);
+        assert_eq!(kever.digers().len(), 1);
+
+        let state = kever.state(Some(1), Some(stamp)).unwrap();
+        assert_eq!(state.c, ["DND", "EO"]);
+        let kel = [icp, rot];
+        assert_eq!(state, KeyStateRecord::from_kel(&kel, None, Some(stamp)).unwrap());
+    }
+
+    #[test]
+    fn partial_rotation() {
+        let salter = Salter::new_with_raw(b"0123456789abcdef", None, Some(Tierage::min)).unwrap();
+        let signers = salter.signers(Some(8), None, Some("part"), None, None, None, None).unwrap();
+        let keys: Vec<String> = signers.iter().map(|s| s.verfer().qb64().unwrap()).collect();
+
+        // two of three committed next keys rotate, the third is held in reserve
+        let icp = incept(
+            &[&keys[0]],
+            None,
+            Some(&[&digest(&signers[1]), &digest(&signers[2]), &digest(&signers[3])]),
+            Some(&dat!(2)),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(matter::Codex::Blake3_256),
+            None,
+            None,
+        )
+        .unwrap();
+        let pre = icp.pre().unwrap();
+        let mut kever = Kever::new(&icp, &sign(&icp, &[&signers[0]])).unwrap();
+
+        // exposes the third and first next keys, adding a key that was never committed to
+        let rot = rotate(
+            &pre,
+            &[&keys[3], &keys[1], &keys[4]],
+            &icp.said().unwrap(),
+            None,
+            Some(1),
+            Some(&dat!(2)),
+            Some(&[&digest(&signers[2]), &digest(&signers[5])]),
+            Some(&dat!(1)),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let raw = rot.raw();
+        let third = signers[3].sign_indexed(&raw, false, 0, Some(2)).unwrap();
+        let first = signers[1].sign_indexed(&raw, false, 1, Some(0)).unwrap();
+        let added = signers[4].sign_indexed(&raw, true, 2, None).unwrap();
+        assert_eq!(third.code(), indexer::Codex::Ed25519_Big);
+        assert_eq!(added.code(), indexer::Codex::Ed25519_Crt);
+
+        // the signing threshold alone is not enough
+        let e = kever.update(&rot, &[third.clone(), added.clone()]).unwrap_err();
+        assert!(is_missing_signatures(&e));
+
+        // ondices must point at the digests of the signing keys
+        let misplaced = signers[1].sign_indexed(&raw, false, 1, Some(1)).unwrap();
+        assert!(kever.update(&rot, &[third.clone(), misplaced]).is_err());
+        let implied = signers[3].sign_indexed(&raw, false, 0, None).unwrap();
+        assert_eq!(implied.code(), indexer::Codex::Ed25519);
+        assert!(kever.update(&rot, &[implied, first.clone()]).is_err());
+
+        kever.update(&rot, &[third, first, added]).unwrap();
+        assert_eq!(kever.verfers().len(), 3);
+        assert_eq!(kever.digers()[0].qb64().unwrap(), digest(&signers[2]));
+
+        // the reserve key rotates in later
+        let rot = rotate(
+            &pre,
+            &[&keys[2]],
+            &rot.said().unwrap(),
+            None,
+            Some(2),
+            None,
+            Some(&[&digest(&signers[6])]),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        kever.update(&rot, &sign(&rot, &[&signers[2]])).unwrap();
+        assert_eq!(kever.verfers()[0].qb64().unwrap(), keys[2]);
+    }
+}
@@ -0,0 +1,2075 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+#[cfg(feature = "database")]
+use crate::database::Database;
+use crate::{
+    cesr::core::{
+        cigar::Cigar,
+        common::{Ids, Ilkage},
+        dater::Dater,
+        indexer::Indexer,
+        matter::Matter,
+        number::Number,
+        sadder::Sadder,
+        seqner::Seqner,
+        serder::Serder,
+        siger::Siger,
+        verfer::Verfer,
+    },
+    error::{err, Error, Result},
+    eventing::{
+        escrowing::{Escrow, EscrowConfig},
+        kever::{indices, satisfied, verify_sigers, Kever},
+        record::EventRecord,
+        witnessing::WitnessConfig,
+    },
+    parsing::parser::{parse, Message},
+    seal::Seal,
+};
+
+/// Outcome of processing a single message.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    /// the event was validated and applied to its key state, or the receipt verified and recorded
+    Accepted,
+    /// the event was already accepted, any new receipts attached to it were recorded
+    Duplicate,
+    /// the message was held back for later processing
+    Escrowed(String),
+    /// the event is validly signed but conflicts with the accepted event at its sequence number,
+    /// evidence of duplicity was recorded
+    Duplicitous(String),
+    /// the message is invalid
+    Rejected(String),
+}
+
+/// Evidence of duplicity, two validly signed events of one identifier at the same sequence number.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Duplicity {
+    pub accepted: EventRecord,    // the event first seen at the sequence number
+    pub conflicting: EventRecord, // the later, conflicting event with its verified signatures
+}
+
+/// Receipt signatures verified against an event, not yet recorded.
+#[derive(Default)]
+struct Receipts {
+    wigers: Vec<Siger>,
+    cigars: Vec<Cigar>,
+    vrcs: Vec<(Seal, Siger)>,
+}
+
+impl Receipts {
+    fn is_empty(&self) -> bool {
+        self.wigers.is_empty() && self.cigars.is_empty() && self.vrcs.is_empty()
+    }
+}
+
+/// The witnesses among `wits` with a verified receipt of `serder` attached to `message`, either
+/// an indexed witness signature or a non-transferable receipt couple.
+fn witnessed(serder: &Serder, wits: &[String], message: &Message) -> Result<Vec<String>> {
+    let verfers =
+        wits.iter().map(|wit| Verfer::new_with_qb64(wit)).collect::<Result<Vec<Verfer>>>()?;
+
+    let mut receipted: Vec<String> = verify_sigers(serder, &verfers, &message.wigers)?
+        .iter()
+        .map(|wiger| wits[wiger.index() as usize].clone())
+        .collect();
+
+    for cigar in &message.cigars {
+        let wit = cigar.verfer().qb64()?;
+        if wits.contains(&wit)
+            && !receipted.contains(&wit)
+            && cigar.verfer().verify(&cigar.raw(), &serder.raw())?
+        {
+            receipted.push(wit);
+        }
+    }
+
+    Ok(receipted)
+}
+
+/// Whether `serder`, at or below the sequence number of `kever`, may be a recovery rotation.
+/// Rotations may supersede the interactions following the latest establishment event, and
+/// delegated rotations may also supersede establishment events anchored earlier.
+fn recoverable(kever: &Kever, serder: &Serder) -> Result<bool> {
+    let ilk = serder.ilk()?;
+    Ok(match ilk.as_str() {
+        Ilkage::rot => serder.sn()? > kever.est().sn()?,
+        Ilkage::drt => kever.delpre().is_some(),
+        _ => false,
+    })
+}
+
+/// The escrows of a `Kevery`, by the condition an event waits on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Hold {
+    OutOfOrder,
+    PartialSignatures,
+    PartialWitnesses,
+    Delegation,
+}
+
+impl Hold {
+    const ALL: [Self; 4] =
+        [Self::OutOfOrder, Self::PartialSignatures, Self::PartialWitnesses, Self::Delegation];
+
+    fn of(e: &anyhow::Error) -> Option<Self> {
+        match e.downcast_ref::<Error>() {
+            Some(Error::OutOfOrder(_)) => Some(Self::OutOfOrder),
+            Some(Error::MissingSignatures(_)) => Some(Self::PartialSignatures),
+            Some(Error::MissingReceipts(_)) => Some(Self::PartialWitnesses),
+            Some(Error::MissingDelegation(_)) => Some(Self::Delegation),
+            _ => None,
+        }
+    }
+
+    /// The name of the escrow in a `Database`.
+    fn name(&self) -> &'static str {
+        match self {
+            Self::OutOfOrder => "ooes",
+            Self::PartialSignatures => "pses",
+            Self::PartialWitnesses => "pwes",
+            Self::Delegation => "pdes",
+        }
+    }
+}
+
+/// Processes key event messages, maintaining a `Kever` and an accepted KEL for each identifier.
+/// Events that cannot be validated yet are escrowed and reprocessed whenever another event is
+/// accepted. With a `Database`, accepted events, key states and escrows are written through to
+/// it.
+#[derive(Debug, Default)]
+pub struct Kevery {
+    kevers: HashMap<String, Kever>,
+    kels: HashMap<String, Vec<EventRecord>>,
+    states: HashMap<String, Vec<Kever>>, // key state after each accepted establishment event
+    ooes: Escrow,
+    pses: Escrow,
+    pwes: Escrow,
+    pdes: Escrow,
+    duplicity: HashMap<String, Vec<Duplicity>>,
+    superseded: HashMap<String, Vec<EventRecord>>,
+    prefixes: HashSet<String>, // locally controlled identifiers
+    #[cfg(feature = "database")]
+    db: Option<Box<dyn Database>>,
+}
+
+impl Kevery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn new_with_config(config: &EscrowConfig) -> Self {
+        Self {
+            ooes: Escrow::new(config),
+            pses: Escrow::new(config),
+            pwes: Escrow::new(config),
+            pdes: Escrow::new(config),
+            ..Default::default()
+        }
+    }
+
+    /// Processes events into `db`, loading the accepted and superseded events, key states and
+    /// escrows it holds. Escrow entries dropped through the `_mut` accessors stay in `db` until
+    /// they are next reprocessed.
+    #[cfg(feature = "database")]
+    pub fn new_with_database(db: Box<dyn Database>, config: Option<&EscrowConfig>) -> Result<Self> {
+        let mut kevery = match config {
+            Some(config) => Self::new_with_config(config),
+            None => Self::new(),
+        };
+
+        for pre in db.prefixes()? {
+            let kel = db.kel(&pre)?;
+            for record in &kel {
+                let kever = match kevery.kevers.remove(&pre) {
+                    Some(mut kever) => {
+                        kever.update(&record.serder, &record.sigers)?;
+                        kever
+                    }
+                    None => Kever::new(&record.serder, &record.sigers)?,
+                };
+                if record.serder.est()? {
+                    kevery.states.entry(pre.clone()).or_default().push(kever.clone());
+                }
+                kevery.kevers.insert(pre.clone(), kever);
+            }
+
+            let saids =
+                kel.iter().map(|record| record.serder.said()).collect::<Result<Vec<_>>>()?;
+            let superseded: Vec<EventRecord> = db
+                .fel(&pre)?
+                .into_iter()
+                .filter(|record| record.serder.said().is_ok_and(|said| !saids.contains(&said)))
+                .collect();
+            if !superseded.is_empty() {
+                kevery.superseded.insert(pre.clone(), superseded);
+            }
+            kevery.kels.insert(pre, kel);
+        }
+
+        for hold in Hold::ALL {
+            for entry in db.escrow(hold.name())? {
+                kevery.escrow_mut(hold).restore(entry);
+            }
+        }
+
+        kevery.db = Some(db);
+        Ok(kevery)
+    }
+
+    #[cfg(feature = "database")]
+    pub fn db(&self) -> Option<&dyn Database> {
+        self.db.as_deref()
+    }
+
+    /// Marks `pre` as controlled locally. Its events are accepted without witness receipts, as
+    /// the controller publishes them to its witnesses only once accepted.
+    pub fn add_prefix(&mut self, pre: &str) {
+        self.prefixes.insert(pre.to_string());
+    }
+
+    pub fn local(&self, pre: &str) -> bool {
+        self.prefixes.contains(pre)
+    }
+
+    /// Out-of-order escrow, of events whose prior events have not been accepted.
+    pub fn ooes(&self) -> &Escrow {
+        &self.ooes
+    }
+
+    pub fn ooes_mut(&mut self) -> &mut Escrow {
+        &mut self.ooes
+    }
+
+    /// Partial signature escrow, of events whose verified signatures do not yet satisfy their
+    /// signing thresholds.
+    pub fn pses(&self) -> &Escrow {
+        &self.pses
+    }
+
+    pub fn pses_mut(&mut self) -> &mut Escrow {
+        &mut self.pses
+    }
+
+    /// Partial witness escrow, of events not yet receipted by enough of their witnesses to meet
+    /// the toad.
+    pub fn pwes(&self) -> &Escrow {
+        &self.pwes
+    }
+
+    pub fn pwes_mut(&mut self) -> &mut Escrow {
+        &mut self.pwes
+    }
+
+    /// Partial delegation escrow, of delegated events not yet anchored by their delegators.
+    pub fn pdes(&self) -> &Escrow {
+        &self.pdes
+    }
+
+    pub fn pdes_mut(&mut self) -> &mut Escrow {
+        &mut self.pdes
+    }
+
+    pub fn kever(&self, pre: &str) -> Option<&Kever> {
+        self.kevers.get(pre)
+    }
+
+    /// Whether `pre` has signed conflicting events.
+    pub fn duplicitous(&self, pre: &str) -> bool {
+        self.duplicity.contains_key(pre)
+    }
+
+    /// The evidence of duplicity recorded for `pre`, in order of detection.
+    pub fn duplicity(&self, pre: &str) -> Option<&[Duplicity]> {
+        self.duplicity.get(pre).map(|duplicity| duplicity.as_slice())
+    }
+
+    /// The events of `pre` superseded by recovery rotations, in order of supersession.
+    pub fn superseded(&self, pre: &str) -> Option<&[EventRecord]> {
+        self.superseded.get(pre).map(|superseded| superseded.as_slice())
+    }
+
+    /// The accepted events of `pre`, in order.
+    pub fn kel(&self, pre: &str) -> Option<&[EventRecord]> {
+        self.kels.get(pre).map(|kel| kel.as_slice())
+    }
+
+    /// The accepted event of `pre` at `sn`.
+    pub fn record(&self, pre: &str, sn: u128) -> Option<&EventRecord> {
+        self.kels.get(pre)?.iter().find(|record| record.serder.sn().ok() == Some(sn))
+    }
+
+    /// The latest accepted establishment event of `pre` at or before `sn`.
+    fn establishment(&self, pre: &str, sn: u128) -> Option<&EventRecord> {
+        self.kels.get(pre)?.iter().rev().find(|record| {
+            record.serder.sn().is_ok_and(|s| s <= sn) && record.serder.est().unwrap_or(false)
+        })
+    }
+
+    fn record_mut(&mut self, pre: &str, sn: u128) -> Option<&mut EventRecord> {
+        self.kels.get_mut(pre)?.iter_mut().find(|record| record.serder.sn().ok() == Some(sn))
+    }
+
+    /// The accepted and superseded events of `pre`, in first-seen order.
+    pub fn fel(&self, pre: &str) -> Vec<&EventRecord> {
+        let kel = self.kel(pre).unwrap_or_default();
+        let superseded = self.superseded(pre).unwrap_or_default();
+
+        let mut fel: Vec<&EventRecord> = kel.iter().chain(superseded).collect();
+        fel.sort_by_key(|record| record.first_seen.as_ref().and_then(|(s, _)| s.sn().ok()));
+        fel
+    }
+
+    /// The first-seen ordinal of the next event of `pre` to be accepted.
+    fn next_ordinal(&self, pre: &str) -> Result<u128> {
+        let mut next = 0;
+        for record in self.fel(pre) {
+            if let Some((seqner, _)) = &record.first_seen {
+                next = next.max(seqner.sn()? + 1);
+            }
+        }
+
+        Ok(next)
+    }
+
+    /// Serializes the accepted events of `pre` in order, each with its attachments and
+    /// first-seen replay couple, for cloning the KEL with `process_cloned_stream`.
+    pub fn replay(&self, pre: &str) -> Result<String> {
+        let mut stream = "".to_string();
+        for record in self.kel(pre).unwrap_or_default() {
+            stream += &record.messagize()?;
+        }
+
+        Ok(stream)
+    }
+
+    /// Serializes the KEL of `pre` for moving it between nodes, preceded by the KELs of its
+    /// delegators, so that the stream can be imported on its own.
+    pub fn export(&self, pre: &str) -> Result<String> {
+        if self.kel(pre).is_none() {
+            return err!(Error::Value(format!("unknown identifier {pre}")));
+        }
+
+        let mut chain = vec![pre.to_string()];
+        while let Some(delpre) = self.kever(&chain[chain.len() - 1]).and_then(|k| k.delpre()) {
+            if chain.iter().any(|p| p == delpre) {
+                break;
+            }
+            chain.push(delpre.to_string());
+        }
+
+        let mut stream = "".to_string();
+        for pre in chain.iter().rev() {
+            stream += &self.replay(pre)?;
+        }
+
+        Ok(stream)
+    }
+
+    /// Imports an exported stream, processing it as cloned from a trusted source. Fails unless
+    /// every event of the stream is accepted, events accepted before the failure are kept.
+    /// Returns the identifiers of the imported events, in order.
+    pub fn import(&mut self, stream: &str) -> Result<Vec<String>> {
+        let messages = parse(stream)?;
+        for message in &messages {
+            self.process_cloned(message);
+        }
+
+        let mut pres: Vec<String> = vec![];
+        for message in &messages {
+            let serder = &message.serder;
+            let pre = serder.pre()?;
+            let sn = serder.sn()?;
+            match self.record(&pre, sn) {
+                Some(record) if record.serder.said()? == serder.said()? => {}
+                _ => {
+                    return err!(Error::Validation(format!(
+                        "event {d} at sn = {sn} for {pre} not accepted on import",
+                        d = serder.said()?
+                    )))
+                }
+            }
+
+            if !pres.contains(&pre) {
+                pres.push(pre);
+            }
+        }
+
+        Ok(pres)
+    }
+
+    /// Writes the export of `pre` to the file at `path`.
+    pub fn export_file(&self, pre: &str, path: &Path) -> Result<()> {
+        std::fs::write(path, self.export(pre)?)?;
+        Ok(())
+    }
+
+    /// Imports an exported stream from the file at `path`.
+    pub fn import_file(&mut self, path: &Path) -> Result<Vec<String>> {
+        self.import(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parses and processes every message of a text domain CESR stream, returning an outcome
+    /// per message. Fails only if the stream cannot be parsed.
+    pub fn process_stream(&mut self, stream: &str) -> Result<Vec<Outcome>> {
+        Ok(parse(stream)?.iter().map(|message| self.process(message)).collect())
+    }
+
+    /// Like `process_stream`, for a stream replayed from a trusted source, honoring its
+    /// first-seen replay couples.
+    pub fn process_cloned_stream(&mut self, stream: &str) -> Result<Vec<Outcome>> {
+        Ok(parse(stream)?.iter().map(|message| self.process_cloned(message)).collect())
+    }
+
+    /// Processes a key event (icp, rot, ixn, dip or drt) or receipt (rct). First-seen replay
+    /// couples are ignored, events are first seen when accepted here.
+    pub fn process(&mut self, message: &Message) -> Outcome {
+        let mut message = message.clone();
+        message.frcs.clear();
+        self.process_message(message)
+    }
+
+    /// Processes a message replayed from a trusted source, keeping the first-seen ordinal and
+    /// datetime of its replay couple for the event.
+    pub fn process_cloned(&mut self, message: &Message) -> Outcome {
+        self.process_message(message.clone())
+    }
+
+    fn process_message(&mut self, mut message: Message) -> Outcome {
+        let result = match message.serder.ilk() {
+            Ok(ilk) => match ilk.as_str() {
+                Ilkage::icp | Ilkage::rot | Ilkage::ixn | Ilkage::dip | Ilkage::drt => {
+                    self.merge_partial(&mut message);
+                    self.process_event(&message)
+                }
+                Ilkage::rct => self.process_receipt(&message),
+                _ => err!(Error::Validation(format!("unsupported ilk = {ilk}"))),
+            },
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(Outcome::Accepted) => {
+                if let Ok(said) = message.serder.said() {
+                    self.unescrow(&said);
+                }
+                self.process_escrows();
+                Outcome::Accepted
+            }
+            Ok(outcome) => outcome,
+            Err(e) => self.escrow(&message, e),
+        }
+    }
+
+    /// Prepends the signatures and witness receipts escrowed for a partially signed or witnessed
+    /// event to those of `message`, a later delivery of the same event.
+    fn merge_partial(&self, message: &mut Message) {
+        let said = match message.serder.said() {
+            Ok(said) => said,
+            Err(_) => return,
+        };
+
+        for escrow in [&self.pses, &self.pwes] {
+            if let Some(entry) = escrow.get(&said) {
+                let escrowed = &entry.message;
+                message.sigers.splice(0..0, escrowed.sigers.iter().cloned());
+                message.wigers.splice(0..0, escrowed.wigers.iter().cloned());
+                message.cigars.splice(0..0, escrowed.cigars.iter().cloned());
+            }
+        }
+    }
+
+    /// Keeps only the verified witness receipts attached to `message`, one per witness.
+    fn verified_receipts(&self, message: &mut Message) -> Result<()> {
+        let kever = self.apply(&message.serder, &message.sigers)?;
+        let wits = kever.wits();
+        let verfers =
+            wits.iter().map(|wit| Verfer::new_with_qb64(wit)).collect::<Result<Vec<Verfer>>>()?;
+        message.wigers = verify_sigers(&message.serder, &verfers, &message.wigers)?;
+
+        let mut cigars: Vec<Cigar> = vec![];
+        for cigar in &message.cigars {
+            let verfer = cigar.verfer();
+            if wits.contains(&verfer.qb64()?)
+                && !cigars.iter().any(|c| c.verfer() == verfer)
+                && verfer.verify(&cigar.raw(), &message.serder.raw())?
+            {
+                cigars.push(cigar.clone());
+            }
+        }
+        message.cigars = cigars;
+
+        Ok(())
+    }
+
+    /// The keys that sign `serder`, its own for establishment events, the current keys of its
+    /// identifier otherwise.
+    fn signing_verfers(&self, serder: &Serder) -> Result<Vec<Verfer>> {
+        if serder.est()? {
+            return serder.verfers();
+        }
+
+        let pre = serder.pre()?;
+        match self.kever(&pre) {
+            Some(kever) => Ok(kever.verfers().to_vec()),
+            None => err!(Error::OutOfOrder(format!("no key state for {pre}"))),
+        }
+    }
+
+    fn escrow_mut(&mut self, hold: Hold) -> &mut Escrow {
+        match hold {
+            Hold::OutOfOrder => &mut self.ooes,
+            Hold::PartialSignatures => &mut self.pses,
+            Hold::PartialWitnesses => &mut self.pwes,
+            Hold::Delegation => &mut self.pdes,
+        }
+    }
+
+    /// Drops the event with `said` from every escrow.
+    fn unescrow(&mut self, said: &str) {
+        for hold in Hold::ALL {
+            self.escrow_mut(hold).remove(said);
+            #[cfg(feature = "database")]
+            self.remove_escrowed(hold, said);
+        }
+    }
+
+    /// Writes the escrowed entry of `message` in the escrow of `hold` through to the database,
+    /// dropping it from memory if the database refuses it.
+    #[cfg(feature = "database")]
+    fn put_escrowed(&mut self, hold: Hold, message: &Message) -> Result<()> {
+        let said = message.serder.said()?;
+        let entry = match self.escrow_mut(hold).get(&said) {
+            Some(entry) => entry.clone(),
+            None => return Ok(()),
+        };
+
+        if let Some(db) = &mut self.db {
+            if let Err(e) = db.put_escrow(hold.name(), &entry) {
+                self.escrow_mut(hold).remove(&said);
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Drops the entry of `said` from the escrow of `hold` in the database. An entry left behind
+    /// by a failed removal is dropped again when it is next reprocessed.
+    #[cfg(feature = "database")]
+    fn remove_escrowed(&mut self, hold: Hold, said: &str) {
+        if let Some(db) = &mut self.db {
+            let _ = db.remove_escrow(hold.name(), said);
+        }
+    }
+
+    /// Escrows `message` if it failed for a reason that may resolve later, merging it into any
+    /// earlier delivery. Partially signed or witnessed events keep only their verified signatures
+    /// and receipts.
+    fn escrow(&mut self, message: &Message, e: anyhow::Error) -> Outcome {
+        let reason = e.to_string();
+        let hold = match Hold::of(&e) {
+            Some(hold) => hold,
+            None => return Outcome::Rejected(reason),
+        };
+
+        let mut message = message.clone();
+        if hold == Hold::PartialSignatures {
+            let verified = self
+                .signing_verfers(&message.serder)
+                .and_then(|verfers| verify_sigers(&message.serder, &verfers, &message.sigers));
+            match verified {
+                Ok(sigers) if !sigers.is_empty() => message.sigers = sigers,
+                Ok(_) => return Outcome::Rejected(format!("{reason}, no verified signatures")),
+                Err(e) => return Outcome::Rejected(e.to_string()),
+            }
+        }
+
+        if hold == Hold::PartialWitnesses {
+            if let Err(e) = self.verified_receipts(&mut message) {
+                return Outcome::Rejected(e.to_string());
+            }
+        }
+
+        let escrow = self.escrow_mut(hold);
+        let escrowed = match escrow.merge(&message, &reason) {
+            Ok(true) => Ok(true),
+            Ok(false) => escrow.add(&message, &reason),
+            Err(e) => Err(e),
+        };
+        #[cfg(feature = "database")]
+        let escrowed = match escrowed {
+            Ok(true) => self.put_escrowed(hold, &message).map(|_| true),
+            escrowed => escrowed,
+        };
+
+        match escrowed {
+            Ok(true) => Outcome::Escrowed(reason),
+            Ok(false) => Outcome::Rejected(format!("{reason}, escrow full")),
+            Err(e) => Outcome::Rejected(e.to_string()),
+        }
+    }
+
+    /// Drops timed out escrow entries and reprocesses the rest until no more events are
+    /// accepted, returning the outcome of each entry that left its escrow, keyed by said. Called
+    /// whenever an event is accepted.
+    pub fn process_escrows(&mut self) -> Vec<(String, Outcome)> {
+        let mut outcomes = vec![];
+        loop {
+            let mut accepted = false;
+            for hold in Hold::ALL {
+                for entry in self.escrow_mut(hold).expire() {
+                    let said = entry.said().unwrap_or_default();
+                    #[cfg(feature = "database")]
+                    self.remove_escrowed(hold, &said);
+                    let reason = format!("escrow timed out, {r}", r = entry.reason);
+                    outcomes.push((said, Outcome::Rejected(reason)));
+                }
+
+                for entry in self.escrow_mut(hold).take() {
+                    let said = entry.said().unwrap_or_default();
+                    let outcome = match self.process_event(&entry.message) {
+                        Ok(outcome) => {
+                            if outcome == Outcome::Accepted {
+                                // a later delivery may still be held in another escrow
+                                self.unescrow(&said);
+                                accepted = true;
+                            }
+                            outcome
+                        }
+                        Err(e) => match Hold::of(&e) {
+                            Some(held) if held == hold => {
+                                self.escrow_mut(hold).restore(entry);
+                                continue;
+                            }
+                            _ => self.escrow(&entry.message, e),
+                        },
+                    };
+
+                    #[cfg(feature = "database")]
+                    self.remove_escrowed(hold, &said);
+                    outcomes.push((said, outcome));
+                }
+            }
+
+            if !accepted {
+                break;
+            }
+        }
+
+        outcomes
+    }
+
+    fn process_event(&mut self, message: &Message) -> Result<Outcome> {
+        let serder = &message.serder;
+        let pre = serder.pre()?;
+        let sn = serder.sn()?;
+
+        // an event at or below the current sequence number is either a duplicate, a recovery
+        // rotation superseding accepted events, or a conflict
+        let recovery = match self.kevers.get(&pre) {
+            Some(kever) if sn <= kever.sn()? => {
+                let accepted = match self.record(&pre, sn) {
+                    Some(record) => record.serder.said()?,
+                    None => "".to_string(),
+                };
+                if accepted == serder.said()? || !recoverable(kever, serder)? {
+                    return self.process_duplicate(message);
+                }
+
+                true
+            }
+            _ => false,
+        };
+
+        let kever = self.apply(serder, &message.sigers)?;
+        let receipted = witnessed(serder, kever.wits(), message)?;
+        let receipted: Vec<&str> = receipted.iter().map(|wit| wit.as_str()).collect();
+        let wits: Vec<&str> = kever.wits().iter().map(|wit| wit.as_str()).collect();
+        let config = WitnessConfig::new(&wits, Some(kever.toad()))?;
+        if !self.local(&pre) && !config.satisfied(&receipted)? {
+            return err!(Error::MissingReceipts(format!(
+                "unsatisfied toad = {t} for {ilk} of {pre} at sn = {sn}, receipted by {receipted:?}",
+                t = kever.toad(),
+                ilk = serder.ilk()?
+            )));
+        }
+
+        let source = match kever.delpre() {
+            Some(delpre) if [Ilkage::dip, Ilkage::drt].contains(&serder.ilk()?.as_str()) => {
+                Some(self.validate_delegation(serder, delpre, message)?)
+            }
+            _ => None,
+        };
+
+        if recovery && !self.supersedes(&pre, sn, source.as_ref())? {
+            return self.process_conflict(message);
+        }
+
+        // after applying the event, the key state holds the keys that signed it
+        let sigers = verify_sigers(serder, kever.verfers(), &message.sigers)?;
+        let mut record = EventRecord::new(serder, sigers, kever.wits());
+        record.source = source;
+        record.first_seen = match message.frcs.first() {
+            Some(frc) => Some(frc.clone()),
+            None => Some((
+                Seqner::new_with_sn(self.next_ordinal(&pre)?)?,
+                Dater::new(None, None, None, None, None, None)?,
+            )),
+        };
+
+        // written through before being applied, so that a failed write accepts nothing
+        #[cfg(feature = "database")]
+        if let (Some(db), Some((seqner, dater))) = (&mut self.db, &record.first_seen) {
+            db.put_record(&record)?;
+            db.put_state(&kever.state(Some(seqner.sn()?), Some(&dater.dts()?))?)?;
+        }
+
+        if recovery {
+            let kel = self.kels.entry(pre.clone()).or_default();
+            let index = kel.iter().position(|record| record.serder.sn().ok() == Some(sn));
+            let superseded = kel.split_off(index.unwrap_or(kel.len()));
+            self.superseded.entry(pre.clone()).or_default().extend(superseded);
+            if let Some(states) = self.states.get_mut(&pre) {
+                states.retain(|state| state.sn().is_ok_and(|s| s < sn));
+            }
+        }
+
+        if serder.est()? {
+            self.states.entry(pre.clone()).or_default().push(kever.clone());
+        }
+        self.kevers.insert(pre.clone(), kever);
+        self.kels.entry(pre.clone()).or_default().push(record);
+
+        let receipts = self.verify_receipts(&pre, sn, message)?;
+        self.record_receipts(&pre, sn, receipts)?;
+
+        Ok(Outcome::Accepted)
+    }
+
+    /// Validates `serder` against a copy of the key state of its identifier, returning the key
+    /// state it results in. The copy is committed once the event is witnessed. Recovery
+    /// rotations are validated against the key state preceding the events they supersede.
+    fn apply(&self, serder: &Serder, sigers: &[Siger]) -> Result<Kever> {
+        let pre = serder.pre()?;
+        let sn = serder.sn()?;
+        match self.kevers.get(&pre) {
+            Some(kever) if sn <= kever.sn()? => match self.state_before(&pre, sn)? {
+                Some(mut kever) => {
+                    kever.update(serder, sigers)?;
+                    Ok(kever)
+                }
+                None => err!(Error::Validation(format!("invalid recovery at sn = {sn} for {pre}"))),
+            },
+            Some(kever) => {
+                let mut kever = kever.clone();
+                kever.update(serder, sigers)?;
+                Ok(kever)
+            }
+            None => {
+                if ![Ilkage::icp, Ilkage::dip].contains(&serder.ilk()?.as_str()) {
+                    return err!(Error::OutOfOrder(format!(
+                        "event at sn = {sn} for {pre} without inception",
+                        sn = serder.sn()?
+                    )));
+                }
+
+                Kever::new(serder, sigers)
+            }
+        }
+    }
+
+    /// Whether a delegated recovery rotation of `pre` at `sn`, anchored by `source`, may
+    /// supersede the accepted events from `sn` on. Interactions may always be superseded, while
+    /// superseded establishment events must have been anchored earlier in the delegator's KEL.
+    fn supersedes(&self, pre: &str, sn: u128, source: Option<&Seal>) -> Result<bool> {
+        let position = |seal: Option<&Seal>| -> Result<Option<u128>> {
+            match seal {
+                Some(Seal::Source { s, .. }) => Ok(Some(Number::new_with_numh(s)?.num()?)),
+                _ => Ok(None),
+            }
+        };
+
+        let anchored = position(source)?;
+        for record in self.kel(pre).unwrap_or_default() {
+            if record.serder.sn()? < sn || !record.serder.est()? {
+                continue;
+            }
+
+            match (position(record.source.as_ref())?, anchored) {
+                (Some(earlier), Some(later)) if earlier < later => {}
+                _ => return Ok(false),
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Finds the event of `delpre` anchoring the delegated event `serder`, checking first the
+    /// seal sources attached to `message` and then the rest of the delegator's KEL. Returns the
+    /// source seal of the anchoring event.
+    fn validate_delegation(
+        &self,
+        serder: &Serder,
+        delpre: &str,
+        message: &Message,
+    ) -> Result<Seal> {
+        let pre = serder.pre()?;
+        let delegator = match self.kever(delpre) {
+            Some(delegator) => delegator,
+            None => {
+                return err!(Error::MissingDelegation(format!(
+                    "unknown delegator {delpre} of {pre}"
+                )))
+            }
+        };
+        delegator.traits().validate_delegation(serder)?;
+
+        let seal = Seal::Event { i: pre.clone(), s: serder.sner()?.numh()?, d: serder.said()? };
+        let kel = self.kel(delpre).unwrap_or_default();
+
+        let mut sourced = vec![];
+        for source in message.sscs.iter().chain(&message.ssts) {
+            let (s, d) = match source {
+                Seal::Source { s, d } => (s, d),
+                Seal::Event { i, s, d } if i == delpre => (s, d),
+                _ => continue,
+            };
+            let sn = Number::new_with_numh(s)?.num()?;
+            if let Some(record) = self.record(delpre, sn) {
+                if record.serder.said()? == *d {
+                    sourced.push(record);
+                }
+            }
+        }
+
+        for record in sourced.into_iter().chain(kel) {
+            let anchors = record.serder.ked()[Ids::a].to_vec().unwrap_or_default();
+            if anchors.iter().any(|anchor| Seal::from_value(anchor).ok().as_ref() == Some(&seal)) {
+                delegator.traits().validate_anchor(&record.serder)?;
+                return Ok(Seal::Source {
+                    s: record.serder.sner()?.numh()?,
+                    d: record.serder.said()?,
+                });
+            }
+        }
+
+        err!(Error::MissingDelegation(format!(
+            "no anchor for {ilk} of {pre} at sn = {sn} by delegator {delpre}",
+            ilk = serder.ilk()?,
+            sn = serder.sn()?
+        )))
+    }
+
+    fn process_duplicate(&mut self, message: &Message) -> Result<Outcome> {
+        let serder = &message.serder;
+        let pre = serder.pre()?;
+        let sn = serder.sn()?;
+
+        let said = match self.record(&pre, sn) {
+            Some(record) => record.serder.said()?,
+            None => {
+                return err!(Error::Validation(format!("missing event at sn = {sn} for {pre}")))
+            }
+        };
+
+        if said != serder.said()? {
+            return self.process_conflict(message);
+        }
+
+        let receipts = self.verify_receipts(&pre, sn, message)?;
+        self.record_receipts(&pre, sn, receipts)?;
+
+        Ok(Outcome::Duplicate)
+    }
+
+    /// Records duplicity if the event of `message`, conflicting with an accepted event, is validly
+    /// signed under the key state its identifier had before that sequence number.
+    fn process_conflict(&mut self, message: &Message) -> Result<Outcome> {
+        let serder = &message.serder;
+        let pre = serder.pre()?;
+        let sn = serder.sn()?;
+        let said = serder.said()?;
+
+        let superseded = self.superseded(&pre).unwrap_or_default();
+        if superseded.iter().any(|record| record.serder.said().ok().as_ref() == Some(&said)) {
+            return err!(Error::Validation(format!(
+                "superseded event {said} at sn = {sn} for {pre}"
+            )));
+        }
+
+        // conflicting events are never escrowed, whatever fails them
+        let kever = match self.state_before(&pre, sn)? {
+            Some(mut kever) => kever.update(serder, &message.sigers).map(|_| kever),
+            None => Kever::new(serder, &message.sigers),
+        };
+        let kever = match kever {
+            Ok(kever) => kever,
+            Err(e) => {
+                return err!(Error::Validation(format!(
+                    "invalid conflicting event {said} at sn = {sn} for {pre}, {e}"
+                )))
+            }
+        };
+
+        let accepted = match self.record(&pre, sn) {
+            Some(record) => record.clone(),
+            None => {
+                return err!(Error::Validation(format!("missing event at sn = {sn} for {pre}")))
+            }
+        };
+        let sigers = verify_sigers(serder, kever.verfers(), &message.sigers)?;
+        let conflicting = EventRecord::new(serder, sigers, kever.wits());
+
+        let reason = format!(
+            "conflicting event {said} at sn = {sn} for {pre}, accepted {d}",
+            d = accepted.serder.said()?
+        );
+        let duplicity = self.duplicity.entry(pre).or_default();
+        if !duplicity.iter().any(|d| d.conflicting.serder.said().ok().as_ref() == Some(&said)) {
+            duplicity.push(Duplicity { accepted, conflicting });
+        }
+
+        Ok(Outcome::Duplicitous(reason))
+    }
+
+    /// The key state of `pre` before the event at `sn`, or `None` before inception. Only the
+    /// interactions after the latest establishment event before `sn` are replayed.
+    fn state_before(&self, pre: &str, sn: u128) -> Result<Option<Kever>> {
+        let states = self.states.get(pre).map(|states| states.as_slice()).unwrap_or_default();
+        let mut kever = match states.iter().rev().find(|state| state.sn().is_ok_and(|s| s < sn)) {
+            Some(state) => state.clone(),
+            None => return Ok(None),
+        };
+
+        let kel = self.kel(pre).unwrap_or_default();
+        let from = kever.sn()?;
+        for record in
+            kel.iter().filter(|record| record.serder.sn().is_ok_and(|s| s > from && s < sn))
+        {
+            kever.update(&record.serder, &record.sigers)?;
+        }
+
+        Ok(Some(kever))
+    }
+
+    fn process_receipt(&mut self, message: &Message) -> Result<Outcome> {
+        let ked = message.serder.ked();
+        let pre = ked[Ids::i].to_string()?;
+        let sn = Number::new_with_numh(&ked[Ids::s].to_string()?)?.num()?;
+        let said = ked[Ids::d].to_string()?;
+
+        match self.record(&pre, sn) {
+            Some(record) if record.serder.said()? == said => {}
+            None if self.pwes.get(&said).is_some() => {
+                return self.process_escrowed_receipt(message)
+            }
+            _ => {
+                return err!(Error::Validation(format!(
+                    "receipt for unknown event {said} at sn = {sn} of {pre}"
+                )))
+            }
+        }
+
+        let receipts = self.verify_receipts(&pre, sn, message)?;
+        if receipts.is_empty() {
+            return err!(Error::Validation(format!(
+                "no verified receipt signatures for event {said} of {pre}"
+            )));
+        }
+        self.record_receipts(&pre, sn, receipts)?;
+
+        Ok(Outcome::Accepted)
+    }
+
+    /// Adds the witness receipts of `message` to the event awaiting them in the partial witness
+    /// escrow, accepting it if its toad is now met. Receipts from non-witnesses are refused.
+    fn process_escrowed_receipt(&mut self, message: &Message) -> Result<Outcome> {
+        let said = message.serder.said()?;
+        let mut event = match self.pwes.get(&said) {
+            Some(entry) => entry.message.clone(),
+            None => return err!(Error::Validation(format!("no escrowed event {said}"))),
+        };
+
+        let kever = self.apply(&event.serder, &event.sigers)?;
+        for cigar in &message.cigars {
+            let wit = cigar.verfer().qb64()?;
+            if !kever.wits().contains(&wit) {
+                return err!(Error::Validation(format!("receipt from non-witness = {wit}")));
+            }
+        }
+
+        event.wigers.extend(message.wigers.iter().cloned());
+        event.cigars.extend(message.cigars.iter().cloned());
+
+        match self.process_event(&event) {
+            Ok(outcome) => Ok(outcome),
+            Err(e) => Ok(self.escrow(&event, e)),
+        }
+    }
+
+    /// Verifies the receipt signatures attached to `message` against the accepted event of `pre`
+    /// at `sn`. Witness signatures are indexed into the witnesses designated for the event and
+    /// transferable receipts must satisfy the validator's signing threshold at the sealed event.
+    fn verify_receipts(&self, pre: &str, sn: u128, message: &Message) -> Result<Receipts> {
+        let record = match self.record(pre, sn) {
+            Some(record) => record,
+            None => {
+                return err!(Error::Validation(format!("missing event at sn = {sn} for {pre}")))
+            }
+        };
+        let ser = record.serder.raw();
+
+        let mut receipts = Receipts::default();
+
+        let verfers = record
+            .wits
+            .iter()
+            .map(|wit| Verfer::new_with_qb64(wit))
+            .collect::<Result<Vec<Verfer>>>()?;
+        receipts.wigers = verify_sigers(&record.serder, &verfers, &message.wigers)?;
+
+        for cigar in &message.cigars {
+            let verfer = cigar.verfer();
+            if verfer.transferable()
+                || receipts.cigars.iter().any(|c| c.verfer().qb64().ok() == verfer.qb64().ok())
+            {
+                continue;
+            }
+
+            if verfer.verify(&cigar.raw(), &ser)? {
+                receipts.cigars.push(cigar.clone());
+            }
+        }
+
+        let mut groups: Vec<(Seal, Vec<Siger>)> = message.tsgs.clone();
+        for (seal, siger) in &message.trqs {
+            groups.push((seal.clone(), vec![siger.clone()]));
+        }
+
+        for (seal, sigers) in groups {
+            let (seal, est) = match &seal {
+                Seal::Event { i, s, d } => {
+                    let sn = Number::new_with_numh(s)?.num()?;
+                    // a seal at an interaction is signed under the latest establishment event
+                    let sealed = match self.record(i, sn) {
+                        Some(record) => record.serder.said()?,
+                        None => continue,
+                    };
+                    match self.establishment(i, sn) {
+                        Some(est) if sealed == *d => (seal, est.serder.clone()),
+                        _ => continue,
+                    }
+                }
+                Seal::Last { i } => match self.kever(i) {
+                    Some(kever) => {
+                        let est = kever.est();
+                        let seal =
+                            Seal::Event { i: i.clone(), s: est.sner()?.numh()?, d: est.said()? };
+                        (seal, est.clone())
+                    }
+                    None => continue,
+                },
+                _ => continue,
+            };
+
+            let tholder = match est.tholder()? {
+                Some(tholder) => tholder,
+                None => continue,
+            };
+
+            let verified = verify_sigers(&record.serder, &est.verfers()?, &sigers)?;
+            if !satisfied(&tholder, &indices(&verified))? {
+                continue;
+            }
+
+            for siger in verified {
+                receipts.vrcs.push((seal.clone(), siger));
+            }
+        }
+
+        Ok(receipts)
+    }
+
+    fn record_receipts(&mut self, pre: &str, sn: u128, receipts: Receipts) -> Result<()> {
+        if receipts.is_empty() {
+            return Ok(());
+        }
+
+        let mut record = match self.record(pre, sn) {
+            Some(record) => record.clone(),
+            None => {
+                return err!(Error::Validation(format!("missing event at sn = {sn} for {pre}")))
+            }
+        };
+
+        for wiger in receipts.wigers {
+            if !record.wigers.iter().any(|w| w.index() == wiger.index()) {
+                record.wigers.push(wiger);
+            }
+        }
+
+        for cigar in receipts.cigars {
+            let wit = cigar.verfer().qb64()?;
+            if !record.cigars.iter().any(|c| c.verfer().qb64().ok().as_ref() == Some(&wit)) {
+                record.cigars.push(cigar);
+            }
+        }
+
+        for vrc in receipts.vrcs {
+            if !record.vrcs.contains(&vrc) {
+                record.vrcs.push(vrc);
+            }
+        }
+
+        #[cfg(feature = "database")]
+        if let Some(db) = &mut self.db {
+            db.put_record(&record)?;
+        }
+        if let Some(accepted) = self.record_mut(pre, sn) {
+            *accepted = record;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::{
+        cesr::core::{
+            common::{Ilkage, Tierage},
+            diger::Diger,
+            indexer::Indexer,
+            matter::{tables as matter, Matter},
+            sadder::Sadder,
+            salter::Salter,
+            seqner::Seqner,
+            serder::Serder,
+            siger::Siger,
+        },
+        eventing::{
+            escrowing::EscrowConfig,
+            event::{incept, interact, query, rotate},
+            kever::indices,
+            kevery::{Kevery, Outcome},
+        },
+        messaging::messagize,
+        parsing::parser::{parse, Message},
+        receipting::receipt,
+        seal::Seal,
+        signing::Signer,
+    };
+
+    fn digest(signer: &Signer) -> String {
+        Diger::new_with_ser(&signer.verfer().qb64b().unwrap(), None).unwrap().qb64().unwrap()
+    }
+
+    fn sign(serder: &Serder, signer: &Signer) -> Vec<Siger> {
+        vec![signer.sign_indexed(&serder.raw(), false, 0, None).unwrap()]
+    }
+
+    fn is_rejected(outcome: &Outcome) -> bool {
+        matches!(outcome, Outcome::Rejected(_))
+    }
+
+    fn is_escrowed(outcome: &Outcome) -> bool {
+        matches!(outcome, Outcome::Escrowed(_))
+    }
+
+    /// Signed messages of a KEL of inception, rotation and interaction without witnesses.
+    fn kel(salter: &Salter) -> (Vec<Serder>, Vec<String>) {
+        let signers = salter.signers(Some(3), None, Some("kel"), None, None, None, None).unwrap();
+
+        let icp = incept(
+            &[&signers[0].verfer().qb64().unwrap()],
+            None,
+            Some(&[&digest(&signers[1])]),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(matter::Codex::Blake3_256),
+            None,
+            None,
+        )
+        .unwrap();
+        let pre = icp.pre().unwrap();
+        let rot = rotate(
+            &pre,
+            &[&signers[1].verfer().qb64().unwrap()],
+            &icp.said().unwrap(),
+            None,
+            None,
+            None,
+            Some(&[&digest(&signers[2])]),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let ixn = interact(&pre, &rot.said().unwrap(), Some(2), None, None, None).unwrap();
+
+        let msgs = vec![
+            messagize(&icp, Some(&sign(&icp, &signers[0])), None, None, None).unwrap(),
+            messagize(&rot, Some(&sign(&rot, &signers[1])), None, None, None).unwrap(),
+            messagize(&ixn, Some(&sign(&ixn, &signers[1])), None, None, None).unwrap(),
+        ];
+
+        (vec![icp, rot, ixn], msgs)
+    }
+
+    #[test]
+    fn processing() {
+        let salter = Salter::new_with_raw(b"0123456789abcdef", None, Some(Tierage::min)).unwrap();
+        let signers =
+            salter.signers(Some(3), None, Some("kevery"), None, None, None, None).unwrap();
+        let witnesses =
+            salter.signers(Some(2), None, Some("wit"), None, Some(false), None, None).unwrap();
+        let wits: Vec<String> = witnesses.iter().map(|w| w.verfer().qb64().unwrap()).collect();
+
+        let icp = incept(
+            &[&signers[0].verfer().qb64().unwrap()],
+            None,
+            Some(&[&digest(&signers[1])]),
+            None,
+            None,
+            Some(&[&wits[0], &wits[1]]),
+            None,
+            None,
+            None,
+            None,
+            Some(matter::Codex::Blake3_256),
+            None,
+            None,
+        )
+        .unwrap();
+        let pre = icp.pre().unwrap();
+
+        let rot = rotate(
+            &pre,
+            &[&signers[1].verfer().qb64().unwrap()],
+            &icp.said().unwrap(),
+            None,
+            None,
+            None,
+            Some(&[&digest(&signers[2])]),
+            None,
+            None,
+            Some(&[&wits[0], &wits[1]]),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let ixn = interact(&pre, &rot.said().unwrap(), Some(2), None, None, None).unwrap();
+
+        let witnessed = |serder: &Serder, signer: &Signer| {
+            let wigers: Vec<Siger> = witnesses
+                .iter()
+                .enumerate()
+                .map(|(i, w)| w.sign_indexed(&serder.raw(), false, i as u32, None).unwrap())
+                .collect();
+            messagize(serder, Some(&sign(serder, signer)), None, Some(&wigers), None).unwrap()
+        };
+        let stream = witnessed(&icp, &signers[0])
+            + &witnessed(&rot, &signers[1])
+            + &witnessed(&ixn, &signers[1]);
+
+        let mut kevery = Kevery::new();
+        assert_eq!(kevery.process_stream(&stream).unwrap(), vec![Outcome::Accepted; 3]);
+        assert_eq!(kevery.kever(&pre).unwrap().sn().unwrap(), 2);
+        assert_eq!(kevery.kel(&pre).unwrap().len(), 3);
+        assert_eq!(kevery.record(&pre, 0).unwrap().wigers.len(), 2);
+        assert_eq!(kevery.record(&pre, 1).unwrap().serder, rot);
+        assert_eq!(kevery.record(&pre, 1).unwrap().wits, wits);
+
+        // replaying the same events
+        assert_eq!(kevery.process_stream(&stream).unwrap(), vec![Outcome::Duplicate; 3]);
+        assert_eq!(kevery.kel(&pre).unwrap().len(), 3);
+
+        // non-transferable receipts of the inception by the other witness
+        let rct = receipt(&pre, 0, &icp.said().unwrap(), None, None).unwrap();
+        let cigar = witnesses[1].sign_unindexed(&icp.raw()).unwrap();
+        let msg = messagize(&rct, None, None, None, Some(&[cigar])).unwrap();
+        assert_eq!(kevery.process_stream(&msg).unwrap(), [Outcome::Accepted]);
+        assert_eq!(kevery.record(&pre, 0).unwrap().cigars.len(), 1);
+
+        // receipt signed over the wrong event, or of an unknown event
+        let cigar = witnesses[1].sign_unindexed(&rot.raw()).unwrap();
+        let msg = messagize(&rct, None, None, None, Some(std::slice::from_ref(&cigar))).unwrap();
+        assert!(is_rejected(&kevery.process_stream(&msg).unwrap()[0]));
+        let unknown = receipt(&pre, 5, &icp.said().unwrap(), None, None).unwrap();
+        let msg = messagize(&unknown, None, None, None, Some(&[cigar])).unwrap();
+        assert!(is_rejected(&kevery.process_stream(&msg).unwrap()[0]));
+
+        // conflicting, badly signed and out of order events
+        let data = [Seal::Digest { d: icp.said().unwrap() }];
+        let conflict =
+            interact(&pre, &rot.said().unwrap(), Some(2), Some(&data), None, None).unwrap();
+        let ahead = interact(&pre, &ixn.said().unwrap(), Some(4), None, None, None).unwrap();
+        let next = interact(&pre, &ixn.said().unwrap(), Some(3), None, None, None).unwrap();
+        let stream = messagize(&conflict, Some(&sign(&conflict, &signers[1])), None, None, None)
+            .unwrap()
+            + &messagize(&ahead, Some(&sign(&ahead, &signers[1])), None, None, None).unwrap()
+            + &messagize(&next, Some(&sign(&next, &signers[0])), None, None, None).unwrap();
+        let outcomes = kevery.process_stream(&stream).unwrap();
+        assert!(matches!(outcomes[0], Outcome::Duplicitous(_)));
+        assert!(is_escrowed(&outcomes[1]));
+        assert!(is_rejected(&outcomes[2]));
+        assert_eq!(kevery.kever(&pre).unwrap().said().unwrap(), ixn.said().unwrap());
+        assert!(kevery.ooes().get(&ahead.said().unwrap()).is_some());
+
+        // the conflicting event is kept as evidence of duplicity, once
+        assert!(kevery.duplicitous(&pre));
+        let msg =
+            messagize(&conflict, Some(&sign(&conflict, &signers[1])), None, None, None).unwrap();
+        assert!(matches!(kevery.process_stream(&msg).unwrap()[0], Outcome::Duplicitous(_)));
+        let duplicity = kevery.duplicity(&pre).unwrap();
+        assert_eq!(duplicity.len(), 1);
+        assert_eq!(duplicity[0].accepted.serder, ixn);
+        assert_eq!(duplicity[0].conflicting.serder, conflict);
+        assert_eq!(duplicity[0].conflicting.sigers.len(), 1);
+
+        // conflicting events that are not validly signed are not duplicity
+        let forged = interact(&pre, &icp.said().unwrap(), Some(1), None, None, None).unwrap();
+        let msg = messagize(&forged, Some(&sign(&forged, &signers[1])), None, None, None).unwrap();
+        assert!(is_rejected(&kevery.process_stream(&msg).unwrap()[0]));
+        assert_eq!(kevery.duplicity(&pre).unwrap().len(), 1);
+        assert!(kevery.pses().is_empty());
+
+        // unsupported messages
+        let qry = query(None, None, None, None, None, None).unwrap();
+        let msg = messagize(&qry, Some(&sign(&qry, &signers[1])), None, None, None).unwrap();
+        assert!(is_rejected(&kevery.process_stream(&msg).unwrap()[0]));
+        assert!(kevery.process_stream("-AAB").is_err());
+
+        // transferable receipt from another controller, sealed to its inception
+        let validator =
+            &salter.signers(Some(1), None, Some("val"), None, None, None, None).unwrap()[0];
+        let vcp = incept(
+            &[&validator.verfer().qb64().unwrap()],
+            None,
+            Some(&[&digest(&signers[2])]),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(matter::Codex::Blake3_256),
+            None,
+            None,
+        )
+        .unwrap();
+        let msg = messagize(&vcp, Some(&sign(&vcp, validator)), None, None, None).unwrap();
+        assert_eq!(kevery.process_stream(&msg).unwrap(), [Outcome::Accepted]);
+
+        let seal =
+            Seal::Event { i: vcp.pre().unwrap(), s: "0".to_string(), d: vcp.said().unwrap() };
+        let rct = receipt(&pre, 2, &ixn.said().unwrap(), None, None).unwrap();
+        let msg = messagize(&rct, Some(&sign(&ixn, validator)), Some(&seal), None, None).unwrap();
+        assert_eq!(kevery.process_stream(&msg).unwrap(), [Outcome::Accepted]);
+        let vrcs = &kevery.record(&pre, 2).unwrap().vrcs;
+        assert_eq!(vrcs.len(), 1);
+        assert_eq!(vrcs[0].0, seal);
+
+        // sealed to an interaction, verified under the inception before it
+        let vxn =
+            interact(&vcp.pre().unwrap(), &vcp.said().unwrap(), None, None, None, None).unwrap();
+        let msg = messagize(&vxn, Some(&sign(&vxn, validator)), None, None, None).unwrap();
+        assert_eq!(kevery.process_stream(&msg).unwrap(), [Outcome::Accepted]);
+        let seal =
+            Seal::Event { i: vxn.pre().unwrap(), s: "1".to_string(), d: vxn.said().unwrap() };
+        let msg = messagize(&rct, Some(&sign(&ixn, validator)), Some(&seal), None, None).unwrap();
+        assert_eq!(kevery.process_stream(&msg).unwrap(), [Outcome::Accepted]);
+        let vrcs = &kevery.record(&pre, 2).unwrap().vrcs;
+        assert_eq!(vrcs.len(), 2);
+        assert_eq!(vrcs[1].0, seal);
+
+        // signed by a key that is not the validator's
+        let msg = messagize(&rct, Some(&sign(&ixn, &signers[0])), Some(&seal), None, None).unwrap();
+        assert!(is_rejected(&kevery.process_stream(&msg).unwrap()[0]));
+    }
+
+    #[test]
+    fn out_of_order() {
+        let salter = Salter::new_with_raw(b"0123456789abcdef", None, Some(Tierage::min)).unwrap();
+        let (kel, msgs) = kel(&salter);
+        let pre = kel[0].pre().unwrap();
+
+        let mut kevery = Kevery::new();
+        let outcomes = kevery.process_stream(&(msgs[2].clone() + &msgs[1] + &msgs[2])).unwrap();
+        assert!(outcomes.iter().all(is_escrowed));
+        assert_eq!(kevery.ooes().len(), 2);
+        assert!(kevery.kever(&pre).is_none());
+
+        // inception releases the escrowed rotation, which releases the interaction
+        assert_eq!(kevery.process_stream(&msgs[0]).unwrap(), [Outcome::Accepted]);
+        assert!(kevery.ooes().is_empty());
+        assert_eq!(kevery.kever(&pre).unwrap().sn().unwrap(), 2);
+        assert_eq!(kevery.kel(&pre).unwrap().len(), 3);
+
+        // escrowed entries time out
+        let config = EscrowConfig { timeout: Duration::ZERO, limit: 1, ..Default::default() };
+        let mut kevery = Kevery::new_with_config(&config);
+        assert!(is_escrowed(&kevery.process_stream(&msgs[1]).unwrap()[0]));
+        // full for this identifier, but not for others
+        assert!(is_rejected(&kevery.process_stream(&msgs[2]).unwrap()[0]));
+        let other = Salter::new_with_raw(b"fedcba9876543210", None, Some(Tierage::min)).unwrap();
+        let (_, others) = self::kel(&other);
+        assert!(is_escrowed(&kevery.process_stream(&others[1]).unwrap()[0]));
+
+        let icp = Message { serder: kel[0].clone(), ..Default::default() };
+        assert!(is_rejected(&kevery.process(&icp)));
+        assert_eq!(kevery.process_stream(&msgs[0]).unwrap(), [Outcome::Accepted]);
+        assert!(kevery.ooes().is_empty());
+        assert_eq!(kevery.kever(&pre).unwrap().sn().unwrap(), 0);
+
+        // purged entries are not reprocessed
+        let mut kevery = Kevery::new();
+        kevery.process_stream(&msgs[1]).unwrap();
+        assert_eq!(kevery.ooes().entries()[0].said().unwrap(), kel[1].said().unwrap());
+        assert_eq!(kevery.ooes_mut().purge(Some(&pre)), 1);
+        kevery.process_stream(&msgs[0]).unwrap();
+        assert_eq!(kevery.kever(&pre).unwrap().sn().unwrap(), 0);
+        assert!(kevery.process_escrows().is_empty());
+
+        // an event accepted from one escrow is dropped from the others
+        let mut kevery = Kevery::new();
+        kevery.process_stream(&(msgs[0].clone() + &msgs[1])).unwrap();
+        let ixn = parse(&msgs[2]).unwrap().remove(0);
+        assert!(kevery.ooes_mut().add(&ixn, "out of order").unwrap());
+        assert!(kevery.pses_mut().add(&ixn, "partially signed").unwrap());
+        assert_eq!(kevery.process_escrows(), [(kel[2].said().unwrap(), Outcome::Accepted)]);
+        assert!(kevery.ooes().is_empty());
+        assert!(kevery.pses().is_empty());
+
+        // a later delivery with forged signatures does not displace the escrowed ones
+        let mut kevery = Kevery::new();
+        kevery.process_stream(&msgs[1]).unwrap();
+        let forger = &other.signers(Some(1), None, None, None, None, None, None).unwrap()[0];
+        let forged =
+            Message { serder: kel[1].clone(), sigers: sign(&kel[1], forger), ..Default::default() };
+        assert!(is_escrowed(&kevery.process(&forged)));
+        assert_eq!(kevery.ooes().len(), 1);
+        assert_eq!(kevery.ooes().get(&kel[1].said().unwrap()).unwrap().message.sigers.len(), 2);
+        kevery.process_stream(&msgs[0]).unwrap();
+        assert!(kevery.ooes().is_empty());
+        assert_eq!(kevery.kever(&pre).unwrap().sn().unwrap(), 1);
+    }
+
+    #[test]
+    fn partial_signatures() {
+        let salter = Salter::new_with_raw(b"0123456789abcdef", None, Some(Tierage::min)).unwrap();
+        let signers = salter.signers(Some(4), None, Some("pse"), None, None, None, None).unwrap();
+        let keys: Vec<String> = signers[..3].iter().map(|s| s.verfer().qb64().unwrap()).collect();
+        let keys: Vec<&str> = keys.iter().map(|key| key.as_str()).collect();
+
+        // two of three signatures required
+        let icp = incept(
+            &keys,
+            None,
+            Some(&[&digest(&signers[3])]),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(matter::Codex::Blake3_256),
+            None,
+            None,
+        )
+        .unwrap();
+        let pre = icp.pre().unwrap();
+        let ixn = interact(&pre, &icp.said().unwrap(), None, None, None, None).unwrap();
+        let signed = |serder: &Serder, indices: &[u32]| {
+            let sigers: Vec<Siger> = indices
+                .iter()
+                .map(|i| signers[*i as usize].sign_indexed(&serder.raw(), false, *i, None).unwrap())
+                .collect();
+            messagize(serder, Some(&sigers), None, None, None).unwrap()
+        };
+
+        let mut kevery = Kevery::new();
+        // signatures from outside the key list are not escrowed
+        let forged = signers[3].sign_indexed(&icp.raw(), false, 1, None).unwrap();
+        let msg = messagize(&icp, Some(&[forged]), None, None, None).unwrap();
+        assert!(is_rejected(&kevery.process_stream(&msg).unwrap()[0]));
+        assert!(kevery.pses().is_empty());
+
+        let outcomes = kevery.process_stream(&(signed(&icp, &[0]) + &signed(&icp, &[0]))).unwrap();
+        assert!(outcomes.iter().all(is_escrowed));
+        assert_eq!(kevery.pses().len(), 1);
+        assert_eq!(kevery.pses().get(&icp.said().unwrap()).unwrap().message.sigers.len(), 1);
+
+        // the interaction waits on the inception
+        assert!(is_escrowed(&kevery.process_stream(&signed(&ixn, &[1, 2])).unwrap()[0]));
+        assert_eq!(kevery.ooes().len(), 1);
+
+        // a second member's signature completes the inception, releasing the interaction
+        assert_eq!(kevery.process_stream(&signed(&icp, &[1])).unwrap(), [Outcome::Accepted]);
+        assert!(kevery.pses().is_empty());
+        assert!(kevery.ooes().is_empty());
+        assert_eq!(kevery.kever(&pre).unwrap().sn().unwrap(), 1);
+        let record = kevery.record(&pre, 0).unwrap();
+        assert_eq!(indices(&record.sigers), [0, 1]);
+
+        // signatures escrowed before an event times out are dropped
+        let config = EscrowConfig { timeout: Duration::ZERO, ..Default::default() };
+        let mut kevery = Kevery::new_with_config(&config);
+        assert!(is_escrowed(&kevery.process_stream(&signed(&icp, &[2])).unwrap()[0]));
+        assert!(kevery.process_escrows().iter().all(|(_, outcome)| is_rejected(outcome)));
+        assert!(kevery.pses().is_empty());
+        assert!(is_escrowed(&kevery.process_stream(&signed(&icp, &[0])).unwrap()[0]));
+        assert_eq!(kevery.pses_mut().purge(Some(&pre)), 1);
+    }
+
+    #[test]
+    fn partial_witnesses() {
+        let salter = Salter::new_with_raw(b"0123456789abcdef", None, Some(Tierage::min)).unwrap();
+        let signers = salter.signers(Some(2), None, Some("pwe"), None, None, None, None).unwrap();
+        let witnesses =
+            salter.signers(Some(4), None, Some("wit"), None, Some(false), None, None).unwrap();
+        let wits: Vec<String> = witnesses.iter().map(|w| w.verfer().qb64().unwrap()).collect();
+
+        // two of three witness receipts required, the fourth signer is not a witness
+        let icp = incept(
+            &[&signers[0].verfer().qb64().unwrap()],
+            None,
+            Some(&[&digest(&signers[1])]),
+            None,
+            Some(2),
+            Some(&[&wits[0], &wits[1], &wits[2]]),
+            None,
+            None,
+            None,
+            None,
+            Some(matter::Codex::Blake3_256),
+            None,
+            None,
+        )
+        .unwrap();
+        let pre = icp.pre().unwrap();
+        let ixn = interact(&pre, &icp.said().unwrap(), None, None, None, None).unwrap();
+        let wiger = |serder: &Serder, i: u32| {
+            witnesses[i as usize].sign_indexed(&serder.raw(), false, i, None).unwrap()
+        };
+        let rct = receipt(&pre, 0, &icp.said().unwrap(), None, None).unwrap();
+        let receipted = |signer: &Signer, ser: &[u8]| {
+            let cigar = signer.sign_unindexed(ser).unwrap();
+            messagize(&rct, None, None, None, Some(&[cigar])).unwrap()
+        };
+
+        let mut kevery = Kevery::new();
+        let msg =
+            messagize(&icp, Some(&sign(&icp, &signers[0])), None, Some(&[wiger(&icp, 0)]), None)
+                .unwrap();
+        assert!(is_escrowed(&kevery.process_stream(&msg).unwrap()[0]));
+        assert_eq!(kevery.pwes().len(), 1);
+        assert!(kevery.kever(&pre).is_none());
+
+        // receipts from non-witnesses are refused, receipts that do not verify are not kept
+        assert!(is_rejected(
+            &kevery.process_stream(&receipted(&witnesses[3], &icp.raw())).unwrap()[0]
+        ));
+        assert!(is_escrowed(
+            &kevery.process_stream(&receipted(&witnesses[1], &ixn.raw())).unwrap()[0]
+        ));
+        let entry = kevery.pwes().get(&icp.said().unwrap()).unwrap();
+        assert_eq!(entry.message.wigers.len(), 1);
+        assert!(entry.message.cigars.is_empty());
+
+        // the interaction waits on the inception
+        let msg = messagize(
+            &ixn,
+            Some(&sign(&ixn, &signers[0])),
+            None,
+            Some(&[wiger(&ixn, 0), wiger(&ixn, 2)]),
+            None,
+        )
+        .unwrap();
+        assert!(is_escrowed(&kevery.process_stream(&msg).unwrap()[0]));
+
+        // a second witness receipt meets the toad
+        assert_eq!(
+            kevery.process_stream(&receipted(&witnesses[1], &icp.raw())).unwrap(),
+            [Outcome::Accepted]
+        );
+        assert!(kevery.pwes().is_empty());
+        assert!(kevery.ooes().is_empty());
+        assert_eq!(kevery.kever(&pre).unwrap().sn().unwrap(), 1);
+        let record = kevery.record(&pre, 0).unwrap();
+        assert_eq!(record.wigers.len(), 1);
+        assert_eq!(record.cigars.len(), 1);
+        assert_eq!(kevery.record(&pre, 1).unwrap().wigers.len(), 2);
+
+        // a fully receipted delivery is accepted directly
+        let mut kevery = Kevery::new();
+        let msg = messagize(
+            &icp,
+            Some(&sign(&icp, &signers[0])),
+            None,
+            Some(&[wiger(&icp, 1), wiger(&icp, 2)]),
+            None,
+        )
+        .unwrap();
+        assert_eq!(kevery.process_stream(&msg).unwrap(), [Outcome::Accepted]);
+    }
+
+    #[test]
+    fn delegation() {
+        let salter = Salter::new_with_raw(b"0123456789abcdef", None, Some(Tierage::min)).unwrap();
+        let signers = salter.signers(Some(8), None, Some("del"), None, None, None, None).unwrap();
+        let inception = |signer: &Signer, next: &Signer, cnfg: Option<&[&str]>, delpre| {
+            incept(
+                &[&signer.verfer().qb64().unwrap()],
+                None,
+                Some(&[&digest(next)]),
+                None,
+                None,
+                None,
+                cnfg,
+                None,
+                None,
+                None,
+                Some(matter::Codex::Blake3_256),
+                None,
+                delpre,
+            )
+            .unwrap()
+        };
+        let anchor = |serder: &Serder| Seal::Event {
+            i: serder.pre().unwrap(),
+            s: serder.sner().unwrap().numh().unwrap(),
+            d: serder.said().unwrap(),
+        };
+        let signed = |serder: &Serder, signer: &Signer| {
+            messagize(serder, Some(&sign(serder, signer)), None, None, None).unwrap()
+        };
+
+        // delegator, delegate and a delegate of the delegate
+        let icp = inception(&signers[0], &signers[1], None, None);
+        let delpre = icp.pre().unwrap();
+        let dip = inception(&signers[2], &signers[3], None, Some(&delpre));
+        let pre = dip.pre().unwrap();
+        let subdip = inception(&signers[4], &signers[5], None, Some(&pre));
+        let ixn = interact(&delpre, &icp.said().unwrap(), None, Some(&[anchor(&dip)]), None, None)
+            .unwrap();
+        let subixn =
+            interact(&pre, &dip.said().unwrap(), None, Some(&[anchor(&subdip)]), None, None)
+                .unwrap();
+        let drt = rotate(
+            &pre,
+            &[&signers[3].verfer().qb64().unwrap()],
+            &subixn.said().unwrap(),
+            Some(Ilkage::drt),
+            Some(2),
+            None,
+            Some(&[&digest(&signers[6])]),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let drtixn =
+            interact(&delpre, &ixn.said().unwrap(), Some(2), Some(&[anchor(&drt)]), None, None)
+                .unwrap();
+
+        let mut kevery = Kevery::new();
+        assert!(is_escrowed(&kevery.process_stream(&signed(&dip, &signers[2])).unwrap()[0]));
+        assert_eq!(kevery.process_stream(&signed(&icp, &signers[0])).unwrap(), [Outcome::Accepted]);
+        assert_eq!(kevery.pdes().len(), 1);
+        assert!(kevery.kever(&pre).is_none());
+
+        // the anchoring interaction releases the delegated inception
+        assert_eq!(kevery.process_stream(&signed(&ixn, &signers[0])).unwrap(), [Outcome::Accepted]);
+        assert!(kevery.pdes().is_empty());
+        assert_eq!(kevery.kever(&pre).unwrap().delpre(), Some(delpre.as_str()));
+        let source = Seal::Source { s: "1".to_string(), d: ixn.said().unwrap() };
+        assert_eq!(kevery.record(&pre, 0).unwrap().source, Some(source));
+
+        // delegation chains
+        let subpre = subdip.pre().unwrap();
+        assert!(is_escrowed(&kevery.process_stream(&signed(&subdip, &signers[4])).unwrap()[0]));
+        assert_eq!(
+            kevery.process_stream(&signed(&subixn, &signers[2])).unwrap(),
+            [Outcome::Accepted]
+        );
+        assert_eq!(kevery.kever(&subpre).unwrap().delpre(), Some(pre.as_str()));
+
+        // delegated rotation with its seal source attached, anchored later
+        let source = Seal::Source { s: "2".to_string(), d: drtixn.said().unwrap() };
+        let msg = signed(&drt, &signers[3]) + &Seal::group(std::slice::from_ref(&source)).unwrap();
+        assert!(is_escrowed(&kevery.process_stream(&msg).unwrap()[0]));
+        assert_eq!(
+            kevery.process_stream(&signed(&drtixn, &signers[0])).unwrap(),
+            [Outcome::Accepted]
+        );
+        assert_eq!(kevery.kever(&pre).unwrap().sn().unwrap(), 2);
+        assert_eq!(kevery.record(&pre, 2).unwrap().source, Some(source));
+
+        // delegators may refuse delegation
+        let dnd = inception(&signers[6], &signers[7], Some(&["DND"]), None);
+        let dndpre = dnd.pre().unwrap();
+        let refused = inception(&signers[7], &signers[6], None, Some(&dndpre));
+        let dndixn =
+            interact(&dndpre, &dnd.said().unwrap(), None, Some(&[anchor(&refused)]), None, None)
+                .unwrap();
+        let stream = signed(&dnd, &signers[6]) + &signed(&dndixn, &signers[6]);
+        assert_eq!(kevery.process_stream(&stream).unwrap(), vec![Outcome::Accepted; 2]);
+        assert!(is_rejected(&kevery.process_stream(&signed(&refused, &signers[7])).unwrap()[0]));
+        assert!(kevery.pdes().is_empty());
+    }
+
+    #[test]
+    fn recovery() {
+        let salter = Salter::new_with_raw(b"0123456789abcdef", None, Some(Tierage::min)).unwrap();
+        let signers = salter.signers(Some(8), None, Some("rec"), None, None, None, None).unwrap();
+        let signed = |serder: &Serder, signer: &Signer| {
+            messagize(serder, Some(&sign(serder, signer)), None, None, None).unwrap()
+        };
+        let rotation = |pre: &str, dig: &str, ilk, key: &Signer, next: &Signer| {
+            rotate(
+                pre,
+                &[&key.verfer().qb64().unwrap()],
+                dig,
+                Some(ilk),
+                Some(1),
+                None,
+                Some(&[&digest(next)]),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap()
+        };
+
+        // a rotation to the next keys supersedes interactions signed with compromised keys
+        let (kel, msgs) = kel(&salter);
+        let pre = kel[0].pre().unwrap();
+        let ixn = interact(&pre, &kel[0].said().unwrap(), Some(1), None, None, None).unwrap();
+        let ixn2 = interact(&pre, &ixn.said().unwrap(), Some(2), None, None, None).unwrap();
+        let compromised =
+            salter.signers(Some(1), None, Some("kel"), None, None, None, None).unwrap();
+
+        let mut kevery = Kevery::new();
+        let stream =
+            msgs[0].clone() + &signed(&ixn, &compromised[0]) + &signed(&ixn2, &compromised[0]);
+        assert_eq!(kevery.process_stream(&stream).unwrap(), vec![Outcome::Accepted; 3]);
+        assert_eq!(kevery.process_stream(&msgs[1]).unwrap(), [Outcome::Accepted]);
+        assert_eq!(kevery.kever(&pre).unwrap().said().unwrap(), kel[1].said().unwrap());
+        assert_eq!(kevery.kel(&pre).unwrap().len(), 2);
+        let superseded = kevery.superseded(&pre).unwrap();
+        assert_eq!(superseded.len(), 2);
+        assert_eq!(superseded[0].serder, ixn);
+        let fel: Vec<Serder> = kevery.fel(&pre).iter().map(|r| r.serder.clone()).collect();
+        assert_eq!(fel, [kel[0].clone(), ixn.clone(), ixn2.clone(), kel[1].clone()]);
+        assert!(!kevery.duplicitous(&pre));
+
+        // superseded events are not accepted again, and continue from the recovery
+        let outcomes = kevery.process_stream(&(signed(&ixn, &compromised[0]) + &msgs[2])).unwrap();
+        assert!(is_rejected(&outcomes[0]));
+        assert_eq!(outcomes[1], Outcome::Accepted);
+        assert!(!kevery.duplicitous(&pre));
+
+        // prior key states are cached at establishment events and replayed from there
+        let sns: Vec<u128> = kevery.states[&pre].iter().map(|s| s.sn().unwrap()).collect();
+        assert_eq!(sns, [0, 1]);
+        let before = kevery.state_before(&pre, 2).unwrap().unwrap();
+        assert_eq!(before.said().unwrap(), kel[1].said().unwrap());
+        assert_eq!(&kevery.state_before(&pre, 3).unwrap().unwrap(), kevery.kever(&pre).unwrap());
+        assert!(kevery.state_before(&pre, 0).unwrap().is_none());
+
+        // establishment events are only superseded by delegated rotations anchored later
+        let icp = incept(
+            &[&signers[0].verfer().qb64().unwrap()],
+            None,
+            Some(&[&digest(&signers[1])]),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(matter::Codex::Blake3_256),
+            None,
+            None,
+        )
+        .unwrap();
+        let delpre = icp.pre().unwrap();
+        let dip = incept(
+            &[&signers[2].verfer().qb64().unwrap()],
+            None,
+            Some(&[&digest(&signers[3])]),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(matter::Codex::Blake3_256),
+            None,
+            Some(&delpre),
+        )
+        .unwrap();
+        let pre = dip.pre().unwrap();
+        let dig = dip.said().unwrap();
+        let drt = rotation(&pre, &dig, Ilkage::drt, &signers[3], &signers[4]);
+        let recovered = rotation(&pre, &dig, Ilkage::drt, &signers[3], &signers[5]);
+        let stale = rotation(&pre, &dig, Ilkage::drt, &signers[3], &signers[6]);
+
+        let anchor = |serders: &[&Serder]| -> Vec<Seal> {
+            serders
+                .iter()
+                .map(|serder| Seal::Event {
+                    i: serder.pre().unwrap(),
+                    s: serder.sner().unwrap().numh().unwrap(),
+                    d: serder.said().unwrap(),
+                })
+                .collect()
+        };
+        let mut stream = signed(&icp, &signers[0]);
+        let mut dig = icp.said().unwrap();
+        for (sn, anchored) in [vec![&dip], vec![&drt, &stale], vec![&recovered]].iter().enumerate()
+        {
+            let ixn =
+                interact(&delpre, &dig, Some(sn as u128 + 1), Some(&anchor(anchored)), None, None)
+                    .unwrap();
+            dig = ixn.said().unwrap();
+            stream += &signed(&ixn, &signers[0]);
+        }
+        stream += &signed(&dip, &signers[2]);
+        stream += &signed(&drt, &signers[3]);
+
+        let mut kevery = Kevery::new();
+        let outcomes = kevery.process_stream(&stream).unwrap();
+        assert!(outcomes.iter().all(|outcome| *outcome == Outcome::Accepted));
+        assert_eq!(kevery.kever(&pre).unwrap().said().unwrap(), drt.said().unwrap());
+
+        assert_eq!(
+            kevery.process_stream(&signed(&recovered, &signers[3])).unwrap(),
+            [Outcome::Accepted]
+        );
+        assert_eq!(kevery.kever(&pre).unwrap().said().unwrap(), recovered.said().unwrap());
+        assert_eq!(kevery.superseded(&pre).unwrap()[0].serder, drt);
+
+        // a rotation anchored before the one it would supersede is duplicity instead
+        let outcomes = kevery.process_stream(&signed(&stale, &signers[3])).unwrap();
+        assert!(matches!(outcomes[0], Outcome::Duplicitous(_)));
+        assert_eq!(kevery.kever(&pre).unwrap().said().unwrap(), recovered.said().unwrap());
+    }
+
+    #[test]
+    fn first_seen() {
+        let salter = Salter::new_with_raw(b"0123456789abcdef", None, Some(Tierage::min)).unwrap();
+        let (kel, msgs) = kel(&salter);
+        let pre = kel[0].pre().unwrap();
+        let ordinals = |kevery: &Kevery| -> Vec<u128> {
+            kevery
+                .fel(&pre)
+                .iter()
+                .map(|record| record.first_seen.as_ref().unwrap().0.sn().unwrap())
+                .collect()
+        };
+
+        let mut kevery = Kevery::new();
+        kevery.process_stream(&msgs.concat()).unwrap();
+        assert_eq!(ordinals(&kevery), [0, 1, 2]);
+
+        // the replay attaches first-seen couples, honored when cloning
+        let replay = kevery.replay(&pre).unwrap();
+        let messages = parse(&replay).unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[2].frcs[0].0.sn().unwrap(), 2);
+
+        let mut clone = Kevery::new();
+        let outcomes = clone.process_cloned_stream(&replay).unwrap();
+        assert_eq!(outcomes, vec![Outcome::Accepted; 3]);
+        assert_eq!(clone.kel(&pre), kevery.kel(&pre));
+        assert_eq!(clone.replay(&pre).unwrap(), replay);
+
+        // untrusted replays are first seen locally
+        let mut message = messages[0].clone();
+        message.frcs[0].0 = Seqner::new_with_sn(7).unwrap();
+        let mut kevery = Kevery::new();
+        assert_eq!(kevery.process(&message), Outcome::Accepted);
+        assert_eq!(ordinals(&kevery), [0]);
+
+        let mut kevery = Kevery::new();
+        assert_eq!(kevery.process_cloned(&message), Outcome::Accepted);
+        assert_eq!(kevery.process_stream(&msgs[1]).unwrap(), [Outcome::Accepted]);
+        assert_eq!(ordinals(&kevery), [7, 8]);
+    }
+
+    #[test]
+    fn export_import() {
+        let salter = Salter::new_with_raw(b"0123456789abcdef", None, Some(Tierage::min)).unwrap();
+        let signers = salter.signers(Some(4), None, Some("exp"), None, None, None, None).unwrap();
+        let witness =
+            &salter.signers(Some(1), None, Some("wit"), None, Some(false), None, None).unwrap()[0];
+        let wit = witness.verfer().qb64().unwrap();
+
+        let icp = incept(
+            &[&signers[0].verfer().qb64().unwrap()],
+            None,
+            Some(&[&digest(&signers[1])]),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(matter::Codex::Blake3_256),
+            None,
+            None,
+        )
+        .unwrap();
+        let delpre = icp.pre().unwrap();
+        let dip = incept(
+            &[&signers[2].verfer().qb64().unwrap()],
+            None,
+            Some(&[&digest(&signers[3])]),
+            None,
+            None,
+            Some(&[&wit]),
+            None,
+            None,
+            None,
+            None,
+            Some(matter::Codex::Blake3_256),
+            None,
+            Some(&delpre),
+        )
+        .unwrap();
+        let pre = dip.pre().unwrap();
+        let seal = Seal::Event { i: pre.clone(), s: "0".to_string(), d: dip.said().unwrap() };
+        let ixn = interact(&delpre, &icp.said().unwrap(), None, Some(&[seal]), None, None).unwrap();
+        let cigar = witness.sign_unindexed(&dip.raw()).unwrap();
+
+        let stream = messagize(&icp, Some(&sign(&icp, &signers[0])), None, None, None).unwrap()
+            + &messagize(&ixn, Some(&sign(&ixn, &signers[0])), None, None, None).unwrap()
+            + &messagize(&dip, Some(&sign(&dip, &signers[2])), None, None, Some(&[cigar])).unwrap();
+        let mut kevery = Kevery::new();
+        assert_eq!(kevery.process_stream(&stream).unwrap(), vec![Outcome::Accepted; 3]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("kel.cesr");
+        kevery.export_file(&pre, &path).unwrap();
+        assert!(kevery.export("Eunknown").is_err());
+
+        // delegator first, with receipts, sources and first-seen data intact
+        let mut imported = Kevery::new();
+        assert_eq!(imported.import_file(&path).unwrap(), [delpre.clone(), pre.clone()]);
+        assert_eq!(imported.kel(&delpre), kevery.kel(&delpre));
+        assert_eq!(imported.kel(&pre), kevery.kel(&pre));
+        assert_eq!(imported.record(&pre, 0).unwrap().cigars.len(), 1);
+        assert_eq!(imported.export(&pre).unwrap(), kevery.export(&pre).unwrap());
+
+        // importing again changes nothing
+        assert_eq!(imported.import_file(&path).unwrap().len(), 2);
+        assert_eq!(imported.kel(&pre).unwrap().len(), 1);
+
+        // a stream missing the delegator, or with a forged signature, does not import
+        let export = kevery.export(&pre).unwrap();
+        let replay = kevery.replay(&pre).unwrap();
+        assert!(Kevery::new().import(&replay).is_err());
+        let sig = sign(&icp, &signers[0])[0].qb64().unwrap();
+        let forged = sign(&icp, &signers[1])[0].qb64().unwrap();
+        assert!(Kevery::new().import(&export.replacen(&sig, &forged, 1)).is_err());
+        assert!(Kevery::new().import(&export[..export.len() - 4]).is_err());
+        assert!(Kevery::new().import_file(&dir.path().join("missing.cesr")).is_err());
+    }
+
+    #[cfg(feature = "database")]
+    #[test]
+    fn database() {
+        use std::fs;
+
+        use crate::database::file::FileDatabase;
+
+        let salter = Salter::new_with_raw(b"0123456789abcdef", None, Some(Tierage::min)).unwrap();
+        let (kel, msgs) = kel(&salter);
+        let pre = kel[0].pre().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let open = || {
+            let db = FileDatabase::open(dir.path()).unwrap();
+            Kevery::new_with_database(Box::new(db), None).unwrap()
+        };
+
+        // accepted events, key states and escrows are written through
+        let mut kevery = open();
+        assert_eq!(kevery.process_stream(&msgs[0]).unwrap(), [Outcome::Accepted]);
+        assert!(is_escrowed(&kevery.process_stream(&msgs[2]).unwrap()[0]));
+        let db = kevery.db().unwrap();
+        assert_eq!(db.kel(&pre).unwrap(), kevery.kel(&pre).unwrap());
+        assert_eq!(db.state(&pre).unwrap().unwrap().s, "0");
+        assert_eq!(db.escrow("ooes").unwrap()[0].said().unwrap(), kel[2].said().unwrap());
+        drop(kevery);
+
+        // and loaded again, continuing from where they were
+        let mut kevery = open();
+        assert_eq!(kevery.kever(&pre).unwrap().sn().unwrap(), 0);
+        assert_eq!(kevery.ooes().len(), 1);
+        assert_eq!(kevery.process_stream(&msgs[1]).unwrap(), [Outcome::Accepted]);
+        assert_eq!(kevery.kever(&pre).unwrap().sn().unwrap(), 2);
+        assert!(kevery.db().unwrap().escrow("ooes").unwrap().is_empty());
+        drop(kevery);
+
+        let kevery = open();
+        assert_eq!(kevery.kel(&pre).unwrap().len(), 3);
+        assert!(kevery.ooes().is_empty());
+        let ordinals: Vec<u128> = kevery
+            .fel(&pre)
+            .iter()
+            .map(|record| record.first_seen.as_ref().unwrap().0.sn().unwrap())
+            .collect();
+        assert_eq!(ordinals, [0, 1, 2]);
+        assert_eq!(kevery.db().unwrap().state(&pre).unwrap().unwrap().s, "2");
+
+        // an event the database refuses is not accepted
+        let dir = tempfile::tempdir().unwrap();
+        let db = FileDatabase::open(dir.path()).unwrap();
+        fs::create_dir(dir.path().join("kels").join(format!("{pre}.cesr"))).unwrap();
+        let mut kevery = Kevery::new_with_database(Box::new(db), None).unwrap();
+        assert!(is_rejected(&kevery.process_stream(&msgs[0]).unwrap()[0]));
+        assert!(kevery.kever(&pre).is_none());
+        assert!(kevery.kel(&pre).is_none());
+    }
+}
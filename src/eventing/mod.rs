@@ -0,0 +1,9 @@
+pub mod escrowing;
+pub mod event;
+pub mod kever;
+pub mod kevery;
+pub mod record;
+pub mod state;
+pub mod traiting;
+pub mod validating;
+pub mod witnessing;
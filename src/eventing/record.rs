@@ -0,0 +1,77 @@
+use crate::{
+    cesr::core::{
+        cigar::Cigar,
+        counter::{tables as counter, Counter},
+        dater::Dater,
+        indexer::Indexer,
+        matter::Matter,
+        seqner::Seqner,
+        serder::Serder,
+        siger::Siger,
+    },
+    error::Result,
+    messaging::messagize,
+    seal::Seal,
+};
+
+/// An accepted key event with the signatures and receipts verified for it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventRecord {
+    pub serder: Serder,
+    pub sigers: Vec<Siger>,                  // controller indexed signatures
+    pub wits: Vec<String>,                   // witnesses designated by the key state at this event
+    pub wigers: Vec<Siger>,                  // witness indexed signatures
+    pub cigars: Vec<Cigar>,                  // non-transferable receipts
+    pub vrcs: Vec<(Seal, Siger)>,            // transferable receipts, event seal of the validator
+    pub source: Option<Seal>,                // delegator event anchoring a delegated event
+    pub first_seen: Option<(Seqner, Dater)>, // first-seen ordinal and datetime, if accepted
+}
+
+impl EventRecord {
+    pub(crate) fn new(serder: &Serder, sigers: Vec<Siger>, wits: &[String]) -> Self {
+        Self {
+            serder: serder.clone(),
+            sigers,
+            wits: wits.to_vec(),
+            wigers: vec![],
+            cigars: vec![],
+            vrcs: vec![],
+            source: None,
+            first_seen: None,
+        }
+    }
+
+    /// Serializes the event with all of its attachments, as replayed to clone a KEL, including
+    /// its first-seen replay couple if it was accepted.
+    pub fn messagize(&self) -> Result<String> {
+        let wigers = (!self.wigers.is_empty()).then_some(self.wigers.as_slice());
+        let cigars = (!self.cigars.is_empty()).then_some(self.cigars.as_slice());
+        let mut message = messagize(&self.serder, Some(&self.sigers), None, wigers, cigars)?;
+
+        if !self.vrcs.is_empty() {
+            message += &Counter::new_with_code_and_count(
+                counter::Codex::TransReceiptQuadruples,
+                self.vrcs.len() as u32,
+            )?
+            .qb64()?;
+            for (seal, siger) in &self.vrcs {
+                message += &seal.qb64()?;
+                message += &siger.qb64()?;
+            }
+        }
+
+        if let Some(source) = &self.source {
+            message += &Seal::group(std::slice::from_ref(source))?;
+        }
+
+        if let Some((seqner, dater)) = &self.first_seen {
+            message +=
+                &Counter::new_with_code_and_count(counter::Codex::FirstSeenReplayCouples, 1)?
+                    .qb64()?;
+            message += &seqner.qb64()?;
+            message += &dater.qb64()?;
+        }
+
+        Ok(message)
+    }
+}
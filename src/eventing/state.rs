@@ -0,0 +1,471 @@
+use crate::{
+    cesr::core::{
+        cigar::Cigar,
+        common::{Ids, Ilkage, Version, CURRENT_VERSION},
+        dater::Dater,
+        indexer::Indexer,
+        matter::Matter,
+        number::Number,
+        sadder::Sadder,
+        serder::Serder,
+        siger::Siger,
+        tholder::Tholder,
+        verfer::Verfer,
+    },
+    data::{dat, Value},
+    error::{err, Error, Result},
+    eventing::event,
+};
+
+const KSN_ROUTE: &str = "/ksn/";
+
+pub(crate) fn strings(value: &Value) -> Result<Vec<String>> {
+    value.to_vec()?.iter().map(|v| v.to_string()).collect()
+}
+
+fn values(strings: &[String]) -> Vec<Value> {
+    strings.iter().map(|s| dat!(s)).collect()
+}
+
+/// The latest establishment event of a key state.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateEstEvent {
+    pub s: String,       // sequence number of latest establishment event (hex)
+    pub d: String,       // said of latest establishment event
+    pub br: Vec<String>, // witnesses cut by latest establishment event
+    pub ba: Vec<String>, // witnesses added by latest establishment event
+}
+
+/// Key state of an identifier, as conveyed in `ksn` replies.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyStateRecord {
+    pub vn: Version,       // version of the record
+    pub i: String,         // identifier prefix
+    pub s: String,         // sequence number of latest event (hex)
+    pub p: String,         // said of prior event, empty at inception
+    pub d: String,         // said of latest event
+    pub f: String,         // first-seen ordinal of latest event (hex)
+    pub dt: String,        // iso8601 timestamp of the record
+    pub et: String,        // ilk of latest event
+    pub kt: Value,         // current signing threshold
+    pub k: Vec<String>,    // current signing keys
+    pub nt: Value,         // next signing threshold
+    pub n: Vec<String>,    // next key digests
+    pub bt: String,        // witness threshold (hex)
+    pub b: Vec<String>,    // current witnesses
+    pub c: Vec<String>,    // configuration traits
+    pub ee: StateEstEvent, // latest establishment event
+    pub di: String,        // delegator prefix, empty if not delegated
+}
+
+impl KeyStateRecord {
+    /// Derives the key state from a KEL that has already been validated. Events must be ordered
+    /// by sequence number, starting at inception.
+    pub fn from_kel(kel: &[Serder], fner: Option<u128>, stamp: Option<&str>) -> Result<Self> {
+        let icp = match kel.first() {
+            Some(icp) => icp,
+            None => return err!(Error::Value("empty kel".to_string())),
+        };
+
+        let ilk = icp.ilk()?;
+        if ![Ilkage::icp, Ilkage::dip].contains(&ilk.as_str()) || icp.sn()? != 0 {
+            return err!(Error::Validation(format!(
+                "kel must begin with inception, got ilk = {ilk} sn = {sn}",
+                sn = icp.sn()?
+            )));
+        }
+
+        let pre = icp.pre()?;
+        let ked = icp.ked();
+        let cnfg = strings(&ked[Ids::c])?;
+        let delpre = icp.delpre()?.unwrap_or_default();
+
+        let mut wits = strings(&ked[Ids::b])?;
+        let mut est = icp;
+        let mut ee =
+            StateEstEvent { s: icp.sner()?.numh()?, d: icp.said()?, br: vec![], ba: vec![] };
+
+        let mut last = icp;
+        for serder in &kel[1..] {
+            if serder.pre()? != pre {
+                return err!(Error::Validation(format!(
+                    "mismatched prefix = {p} in kel of {pre}",
+                    p = serder.pre()?
+                )));
+            }
+
+            if serder.sn()? != last.sn()? + 1 {
+                return err!(Error::Validation(format!(
+                    "non-contiguous sn = {sn} after {l}",
+                    sn = serder.sn()?,
+                    l = last.sn()?
+                )));
+            }
+
+            if serder.prior()? != Some(last.said()?) {
+                return err!(Error::Validation(format!(
+                    "event at sn = {sn} does not chain to prior event {d}",
+                    sn = serder.sn()?,
+                    d = last.said()?
+                )));
+            }
+
+            if serder.est()? {
+                let ked = serder.ked();
+                let cuts = strings(&ked[Ids::br])?;
+                let adds = strings(&ked[Ids::ba])?;
+
+                wits.retain(|wit| !cuts.contains(wit));
+                wits.extend(adds.iter().cloned());
+
+                est = serder;
+                ee = StateEstEvent {
+                    s: serder.sner()?.numh()?,
+                    d: serder.said()?,
+                    br: cuts,
+                    ba: adds,
+                };
+            }
+
+            last = serder;
+        }
+
+        let fner = fner.unwrap_or(kel.len() as u128 - 1);
+        let dt = match stamp {
+            Some(stamp) => stamp.to_string(),
+            None => Dater::new(None, None, None, None, None, None)?.dts()?,
+        };
+
+        let eked = est.ked();
+        let bt = match est.bner()? {
+            Some(bner) => bner.numh()?,
+            None => return err!(Error::Validation("missing witness threshold".to_string())),
+        };
+
+        Ok(Self {
+            vn: CURRENT_VERSION.clone(),
+            i: pre,
+            s: last.sner()?.numh()?,
+            p: last.prior()?.unwrap_or_default(),
+            d: last.said()?,
+            f: Number::new_with_num(fner)?.numh()?,
+            dt,
+            et: last.ilk()?,
+            kt: eked[Ids::kt].clone(),
+            k: strings(&eked[Ids::k])?,
+            nt: eked[Ids::nt].clone(),
+            n: strings(&eked[Ids::n])?,
+            bt,
+            b: wits,
+            c: cnfg,
+            ee,
+            di: delpre,
+        })
+    }
+
+    pub fn from_value(ksn: &Value) -> Result<Self> {
+        let vn = ksn["vn"].to_vec()?;
+        if vn.len() != 2 {
+            return err!(Error::Value(format!("invalid version = {vn:?}")));
+        }
+
+        let part = |value: &Value| -> Result<u8> {
+            match u8::try_from(value.to_i64()?) {
+                Ok(part) => Ok(part),
+                Err(_) => err!(Error::Value(format!("invalid version = {vn:?}"))),
+            }
+        };
+
+        let ee = &ksn["ee"];
+
+        Ok(Self {
+            vn: Version { major: part(&vn[0])?, minor: part(&vn[1])? },
+            i: ksn["i"].to_string()?,
+            s: ksn["s"].to_string()?,
+            p: ksn["p"].to_string()?,
+            d: ksn["d"].to_string()?,
+            f: ksn["f"].to_string()?,
+            dt: ksn["dt"].to_string()?,
+            et: ksn["et"].to_string()?,
+            kt: ksn["kt"].clone(),
+            k: strings(&ksn["k"])?,
+            nt: ksn["nt"].clone(),
+            n: strings(&ksn["n"])?,
+            bt: ksn["bt"].to_string()?,
+            b: strings(&ksn["b"])?,
+            c: strings(&ksn["c"])?,
+            ee: StateEstEvent {
+                s: ee["s"].to_string()?,
+                d: ee["d"].to_string()?,
+                br: strings(&ee["br"])?,
+                ba: strings(&ee["ba"])?,
+            },
+            di: ksn["di"].to_string()?,
+        })
+    }
+
+    pub fn to_value(&self) -> Value {
+        dat!({
+            "vn": [self.vn.major as i64, self.vn.minor as i64],
+            "i": &self.i,
+            "s": &self.s,
+            "p": &self.p,
+            "d": &self.d,
+            "f": &self.f,
+            "dt": &self.dt,
+            "et": &self.et,
+            "kt": self.kt.clone(),
+            "k": values(&self.k).as_slice(),
+            "nt": self.nt.clone(),
+            "n": values(&self.n).as_slice(),
+            "bt": &self.bt,
+            "b": values(&self.b).as_slice(),
+            "c": values(&self.c).as_slice(),
+            "ee": {
+                "s": &self.ee.s,
+                "d": &self.ee.d,
+                "br": values(&self.ee.br).as_slice(),
+                "ba": values(&self.ee.ba).as_slice()
+            },
+            "di": &self.di
+        })
+    }
+
+    pub fn sn(&self) -> Result<u128> {
+        Number::new_with_numh(&self.s)?.num()
+    }
+
+    pub fn toad(&self) -> Result<u128> {
+        Number::new_with_numh(&self.bt)?.num()
+    }
+
+    /// Builds a `ksn` reply carrying this record. `src` is the identifier of the replier (the
+    /// controller itself or one of its witnesses) and defaults to the record's prefix.
+    pub fn reply(
+        &self,
+        src: Option<&str>,
+        stamp: Option<&str>,
+        version: Option<&Version>,
+        kind: Option<&str>,
+    ) -> Result<Serder> {
+        let src = src.unwrap_or(&self.i);
+        let route = format!("{KSN_ROUTE}{src}");
+
+        event::reply(Some(&route), Some(&self.to_value()), stamp, version, kind)
+    }
+
+    pub fn from_reply(serder: &Serder) -> Result<Self> {
+        if serder.ilk()? != Ilkage::rpy {
+            return err!(Error::Validation(format!(
+                "invalid ilk = {i} for ksn",
+                i = serder.ilk()?
+            )));
+        }
+
+        let route = serder.ked()[Ids::r].to_string()?;
+        if !route.starts_with(KSN_ROUTE) {
+            return err!(Error::Validation(format!("invalid route = {route} for ksn")));
+        }
+
+        Self::from_value(&serder.ked()[Ids::a])
+    }
+
+    /// Verifies that `serder` is a `ksn` reply carrying this record, signed by enough of either
+    /// the current keys (indexed signatures) or the witnesses (non-indexed signatures). The source
+    /// of the route must be the signer, the identifier itself or a witness that signed.
+    pub fn verify(
+        &self,
+        serder: &Serder,
+        sigers: Option<&[Siger]>,
+        cigars: Option<&[Cigar]>,
+    ) -> Result<bool> {
+        if Self::from_reply(serder)? != *self {
+            return Ok(false);
+        }
+
+        let route = serder.ked()[Ids::r].to_string()?;
+        let src = &route[KSN_ROUTE.len()..];
+        let ser = serder.raw();
+
+        if let Some(sigers) = sigers.filter(|_| src == self.i) {
+            let mut indices: Vec<u32> = vec![];
+            for siger in sigers {
+                let index = siger.index();
+                if index as usize >= self.k.len() {
+                    continue;
+                }
+
+                let verfer = Verfer::new_with_qb64(&self.k[index as usize])?;
+                if verfer.verify(&siger.raw(), &ser)? && !indices.contains(&index) {
+                    indices.push(index);
+                }
+            }
+
+            let tholder = Tholder::new_with_sith(&self.kt)?;
+            if !indices.is_empty() && tholder.satisfy(&indices)? {
+                return Ok(true);
+            }
+        }
+
+        if let Some(cigars) = cigars {
+            let mut wits: Vec<String> = vec![];
+            for cigar in cigars {
+                let wit = cigar.verfer().qb64()?;
+                if !self.b.contains(&wit) || wits.contains(&wit) {
+                    continue;
+                }
+
+                if cigar.verfer().verify(&cigar.raw(), &ser)? {
+                    wits.push(wit);
+                }
+            }
+
+            let toad = self.toad()?;
+            if toad > 0 && wits.len() as u128 >= toad && wits.iter().any(|wit| wit == src) {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        cesr::core::{
+            common::{Ilkage, Tierage},
+            diger::Diger,
+            matter::{tables as matter, Matter},
+            sadder::Sadder,
+            salter::Salter,
+        },
+        data::dat,
+        eventing::{
+            event::{incept, interact, rotate},
+            state::KeyStateRecord,
+        },
+        signing::Signer,
+    };
+
+    fn digest(signer: &Signer) -> String {
+        Diger::new_with_ser(&signer.verfer().qb64b().unwrap(), None).unwrap().qb64().unwrap()
+    }
+
+    #[test]
+    fn key_state() {
+        let stamp = "2021-01-01T00:00:00.000000+00:00";
+        let salter = Salter::new_with_raw(b"0123456789abcdef", None, Some(Tierage::min)).unwrap();
+        let signers = salter.signers(Some(3), None, Some("ksn"), None, None, None, None).unwrap();
+        let wsalter = Salter::new_with_raw(b"fedcba9876543210", None, Some(Tierage::min)).unwrap();
+        let witnesses =
+            wsalter.signers(Some(3), None, Some("wit"), None, Some(false), None, None).unwrap();
+        let wits: Vec<String> = witnesses.iter().map(|w| w.verfer().qb64().unwrap()).collect();
+
+        let icp = incept(
+            &[&signers[0].verfer().qb64().unwrap()],
+            None,
+            Some(&[&digest(&signers[1])]),
+            None,
+            None,
+            Some(&[&wits[0], &wits[1]]),
+            Some(&["EO"]),
+            None,
+            None,
+            None,
+            Some(matter::Codex::Blake3_256),
+            None,
+            None,
+        )
+        .unwrap();
+        let pre = icp.pre().unwrap();
+
+        let rot = rotate(
+            &pre,
+            &[&signers[1].verfer().qb64().unwrap()],
+            &icp.said().unwrap(),
+            None,
+            None,
+            None,
+            Some(&[&digest(&signers[2])]),
+            None,
+            None,
+            Some(&[&wits[0], &wits[1]]),
+            Some(&[&wits[0]]),
+            Some(&[&wits[2]]),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let ixn = interact(&pre, &rot.said().unwrap(), Some(2), None, None, None).unwrap();
+
+        let kel = [icp.clone(), rot.clone(), ixn.clone()];
+        let ksr = KeyStateRecord::from_kel(&kel, None, Some(stamp)).unwrap();
+        assert_eq!(ksr.i, pre);
+        assert_eq!(ksr.sn().unwrap(), 2);
+        assert_eq!(ksr.p, rot.said().unwrap());
+        assert_eq!(ksr.d, ixn.said().unwrap());
+        assert_eq!(ksr.f, "2");
+        assert_eq!(ksr.et, Ilkage::ixn);
+        assert_eq!(ksr.k, [signers[1].verfer().qb64().unwrap()]);
+        assert_eq!(ksr.n, [digest(&signers[2])]);
+        assert_eq!(ksr.b, [wits[1].clone(), wits[2].clone()]);
+        assert_eq!(ksr.toad().unwrap(), 2);
+        assert_eq!(ksr.c, ["EO"]);
+        assert_eq!(ksr.ee.s, "1");
+        assert_eq!(ksr.ee.d, rot.said().unwrap());
+        assert_eq!(ksr.ee.br, [wits[0].clone()]);
+        assert_eq!(ksr.ee.ba, [wits[2].clone()]);
+        assert_eq!(ksr.di, "");
+
+        assert_eq!(KeyStateRecord::from_value(&ksr.to_value()).unwrap(), ksr);
+        let mut value = ksr.to_value();
+        value["vn"] = dat!([1, 256]);
+        assert!(KeyStateRecord::from_value(&value).is_err());
+
+        // controller signed
+        let rpy = ksr.reply(None, Some(stamp), None, None).unwrap();
+        assert_eq!(rpy.ked()["r"], dat!(&format!("/ksn/{pre}")));
+        assert_eq!(KeyStateRecord::from_reply(&rpy).unwrap(), ksr);
+
+        let siger = signers[1].sign_indexed(&rpy.raw(), false, 0, None).unwrap();
+        assert!(ksr.verify(&rpy, Some(&[siger]), None).unwrap());
+
+        let stale = signers[0].sign_indexed(&rpy.raw(), false, 0, None).unwrap();
+        assert!(!ksr.verify(&rpy, Some(&[stale]), None).unwrap());
+
+        // witness signed, toad of 2 must be met by current witnesses
+        let rpy = ksr.reply(Some(&wits[1]), Some(stamp), None, None).unwrap();
+        let cigars: Vec<_> =
+            witnesses.iter().map(|w| w.sign_unindexed(&rpy.raw()).unwrap()).collect();
+        assert!(ksr.verify(&rpy, None, Some(&cigars[1..])).unwrap());
+        assert!(!ksr.verify(&rpy, None, Some(&cigars[..2])).unwrap());
+        assert!(!ksr.verify(&rpy, None, Some(&[cigars[1].clone(), cigars[1].clone()])).unwrap());
+
+        // the source of the route must be the signer
+        let siger = signers[1].sign_indexed(&rpy.raw(), false, 0, None).unwrap();
+        assert!(!ksr.verify(&rpy, Some(&[siger]), None).unwrap());
+        let rpy = ksr.reply(None, Some(stamp), None, None).unwrap();
+        let cigars: Vec<_> =
+            witnesses.iter().map(|w| w.sign_unindexed(&rpy.raw()).unwrap()).collect();
+        assert!(!ksr.verify(&rpy, None, Some(&cigars[1..])).unwrap());
+        let rpy = ksr.reply(Some(&wits[0]), Some(stamp), None, None).unwrap();
+        let cigars: Vec<_> =
+            witnesses.iter().map(|w| w.sign_unindexed(&rpy.raw()).unwrap()).collect();
+        assert!(!ksr.verify(&rpy, None, Some(&cigars[1..])).unwrap());
+
+        // a reply carrying a different record does not verify this one
+        let older = KeyStateRecord::from_kel(&kel[..2], None, Some(stamp)).unwrap();
+        let rpy = older.reply(None, Some(stamp), None, None).unwrap();
+        let siger = signers[1].sign_indexed(&rpy.raw(), false, 0, None).unwrap();
+        assert!(!ksr.verify(&rpy, Some(&[siger]), None).unwrap());
+
+        // broken chain
+        assert!(KeyStateRecord::from_kel(&[icp.clone(), ixn], None, None).is_err());
+        assert!(KeyStateRecord::from_kel(&[rot], None, None).is_err());
+        assert!(KeyStateRecord::from_kel(&[], None, None).is_err());
+    }
+}
@@ -0,0 +1,300 @@
+use crate::{
+    cesr::core::{
+        common::{Ids, Ilkage},
+        sadder::Sadder,
+        serder::Serder,
+    },
+    error::{err, Error, Result},
+};
+
+#[allow(non_snake_case)]
+#[allow(non_upper_case_globals)]
+pub mod Codex {
+    pub const EstOnly: &str = "EO"; // only establishment events in KEL, no interactions
+    pub const DoNotDelegate: &str = "DND"; // identifier may not act as a delegator
+    pub const NoBackers: &str = "NB"; // registry may not have backers
+}
+
+/// Configuration traits, as found in the `c` field of inception events.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Traits {
+    est_only: bool,
+    do_not_delegate: bool,
+    no_backers: bool,
+    codes: Vec<&'static str>, // in the order of the event
+}
+
+impl Traits {
+    pub fn new(cnfg: &[&str]) -> Result<Self> {
+        let mut traits = Self::default();
+
+        for code in cnfg {
+            let code = match *code {
+                Codex::EstOnly => {
+                    traits.est_only = true;
+                    Codex::EstOnly
+                }
+                Codex::DoNotDelegate => {
+                    traits.do_not_delegate = true;
+                    Codex::DoNotDelegate
+                }
+                Codex::NoBackers => {
+                    traits.no_backers = true;
+                    Codex::NoBackers
+                }
+                _ => return err!(Error::Value(format!("unknown configuration trait = {code}"))),
+            };
+            traits.codes.push(code);
+        }
+
+        Ok(traits)
+    }
+
+    /// Reads the traits of an inception event (icp, dip or vcp).
+    pub fn new_with_serder(serder: &Serder) -> Result<Self> {
+        let ked = serder.ked();
+
+        let label = Ids::c;
+        if !ked.to_map()?.contains_key(label) {
+            return Ok(Self::default());
+        }
+
+        let cnfg =
+            ked[label].to_vec()?.iter().map(|c| c.to_string()).collect::<Result<Vec<_>>>()?;
+        let cnfg: Vec<&str> = cnfg.iter().map(|c| c.as_str()).collect();
+
+        Self::new(&cnfg)
+    }
+
+    pub fn est_only(&self) -> bool {
+        self.est_only
+    }
+
+    pub fn do_not_delegate(&self) -> bool {
+        self.do_not_delegate
+    }
+
+    pub fn no_backers(&self) -> bool {
+        self.no_backers
+    }
+
+    /// The trait codes, in the order they were configured.
+    pub fn codes(&self) -> Vec<&'static str> {
+        self.codes.clone()
+    }
+
+    /// Validates an event in the KEL of an identifier with these traits.
+    pub fn validate_event(&self, serder: &Serder) -> Result<()> {
+        if self.est_only && serder.ilk()? == Ilkage::ixn {
+            return err!(Error::Validation(format!(
+                "interaction event at sn = {sn} for establishment only identifier {pre}",
+                sn = serder.sn()?,
+                pre = serder.pre()?
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Validates that an event of an identifier with these traits may anchor seals on behalf of
+    /// another identifier, as a delegator or issuer does.
+    pub fn validate_anchor(&self, anchor: &Serder) -> Result<()> {
+        if self.est_only && !anchor.est()? {
+            return err!(Error::Validation(format!(
+                "non-establishment anchor at sn = {sn} for establishment only identifier {pre}",
+                sn = anchor.sn()?,
+                pre = anchor.pre()?
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Validates a delegated event against the traits of its delegator.
+    pub fn validate_delegation(&self, serder: &Serder) -> Result<()> {
+        let ilk = serder.ilk()?;
+        if self.do_not_delegate && [Ilkage::dip, Ilkage::drt].contains(&ilk.as_str()) {
+            return err!(Error::Validation(format!(
+                "delegator does not allow delegation of {ilk} for {pre}",
+                pre = serder.pre()?
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Validates a registry event (vcp or vrt) against the traits of its registry.
+    pub fn validate_registry(&self, serder: &Serder) -> Result<()> {
+        if !self.no_backers {
+            return Ok(());
+        }
+
+        let ked = serder.ked();
+        let labels: &[&str] = match serder.ilk()?.as_str() {
+            Ilkage::vcp => &[Ids::b],
+            Ilkage::vrt => &[Ids::ba],
+            _ => &[],
+        };
+
+        for label in labels {
+            if !ked[*label].to_vec()?.is_empty() {
+                return err!(Error::Validation(format!(
+                    "backers in {label} of registry {pre} with no backers trait",
+                    pre = serder.pre()?
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        cesr::core::{
+            common::Tierage,
+            matter::{tables as matter, Matter},
+            sadder::Sadder,
+            salter::Salter,
+        },
+        eventing::{
+            event::{incept, interact, registry_incept, rotate},
+            traiting::{Codex, Traits},
+        },
+        seal::Seal,
+    };
+
+    #[test]
+    fn codes() {
+        let traits = Traits::new(&[Codex::NoBackers, Codex::EstOnly]).unwrap();
+        assert!(traits.est_only());
+        assert!(!traits.do_not_delegate());
+        assert!(traits.no_backers());
+        assert_eq!(traits.codes(), [Codex::NoBackers, Codex::EstOnly]);
+
+        assert_eq!(Traits::new(&[]).unwrap(), Traits::default());
+        assert!(Traits::new(&["XX"]).is_err());
+    }
+
+    #[test]
+    fn enforcement() {
+        let salter = Salter::new_with_raw(b"0123456789abcdef", None, Some(Tierage::min)).unwrap();
+        let signers = salter.signers(Some(2), None, Some("trait"), None, None, None, None).unwrap();
+        let keys: Vec<String> = signers.iter().map(|s| s.verfer().qb64().unwrap()).collect();
+
+        let icp = incept(
+            &[&keys[0]],
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(&[Codex::EstOnly, Codex::DoNotDelegate]),
+            None,
+            None,
+            None,
+            Some(matter::Codex::Blake3_256),
+            None,
+            None,
+        )
+        .unwrap();
+        let pre = icp.pre().unwrap();
+        let traits = Traits::new_with_serder(&icp).unwrap();
+        assert_eq!(traits.codes(), [Codex::EstOnly, Codex::DoNotDelegate]);
+
+        // unknown traits are refused by the builder
+        assert!(incept(
+            &[&keys[0]],
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(&["XX"]),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .is_err());
+
+        // EO forbids interaction and requires anchoring in establishment events
+        let ixn = interact(&pre, &icp.said().unwrap(), None, None, None, None).unwrap();
+        assert!(traits.validate_event(&ixn).is_err());
+        assert!(traits.validate_anchor(&ixn).is_err());
+        assert!(Traits::default().validate_event(&ixn).is_ok());
+
+        let rot = rotate(
+            &pre,
+            &[&keys[1]],
+            &icp.said().unwrap(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(&[Seal::Digest { d: icp.said().unwrap() }]),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert!(traits.validate_event(&rot).is_ok());
+        assert!(traits.validate_anchor(&rot).is_ok());
+
+        // DND rejects delegated inception under this delegator
+        let dip = incept(
+            &[&keys[1]],
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(&pre),
+        )
+        .unwrap();
+        assert!(traits.validate_delegation(&dip).is_err());
+        assert!(Traits::default().validate_delegation(&dip).is_ok());
+        assert!(traits.validate_delegation(&rot).is_ok());
+
+        // NB disallows backers in registries
+        let wit = "BC9Df6ssUZQFQZJYVUyfudw4WTQsugGcvVD_Z4ChFGE4";
+        assert!(registry_incept(
+            &pre,
+            None,
+            Some(&[wit]),
+            None,
+            Some(&[Codex::NoBackers]),
+            None,
+            None,
+            None
+        )
+        .is_err());
+
+        let vcp =
+            registry_incept(&pre, None, None, None, Some(&[Codex::NoBackers]), None, None, None)
+                .unwrap();
+        let traits = Traits::new_with_serder(&vcp).unwrap();
+        assert!(traits.no_backers());
+        assert!(traits.validate_registry(&vcp).is_ok());
+
+        let backed =
+            registry_incept(&pre, None, Some(&[wit]), None, None, None, None, None).unwrap();
+        assert_eq!(backed.bn().unwrap(), 1);
+        assert!(Traits::new_with_serder(&backed).unwrap().validate_registry(&backed).is_ok());
+        assert!(traits.validate_registry(&backed).is_err());
+    }
+}
@@ -0,0 +1,179 @@
+use crate::{
+    cesr::core::{
+        common::{Ids, Ilkage},
+        matter::Matter,
+        sadder::Sadder,
+        saider::Saider,
+        serder::Serder,
+    },
+    data::Value,
+    error::{err, Error, Result},
+    pathing::pather::Pather,
+};
+
+fn validate_said(serder: &Serder) -> Result<()> {
+    if !serder.saider().verify(&serder.ked(), Some(true), None, None, None, None)? {
+        return err!(Error::Validation(format!("invalid said = {d}", d = serder.said()?)));
+    }
+
+    Ok(())
+}
+
+fn validate_ilk(serder: &Serder, ilk: &str) -> Result<()> {
+    if serder.ilk()? != ilk {
+        return err!(Error::Validation(format!(
+            "unexpected ilk = {i}, expected {ilk}",
+            i = serder.ilk()?
+        )));
+    }
+
+    Ok(())
+}
+
+/// Validates a prod, returning the paths of the requested SAD content.
+pub fn validate_prod(serder: &Serder) -> Result<Vec<Pather>> {
+    validate_ilk(serder, Ilkage::pro)?;
+    validate_said(serder)?;
+
+    let ked = serder.ked();
+    let label = Ids::q;
+    if !ked.to_map()?.contains_key(label) {
+        return err!(Error::Validation("missing query in prod".to_string()));
+    }
+
+    let mut paths = vec![];
+    for path in ked[label]["p"].to_vec()? {
+        paths.push(Pather::new_with_qb64(&path.to_string()?)?);
+    }
+
+    if paths.is_empty() {
+        return err!(Error::Validation("no paths in prod".to_string()));
+    }
+
+    Ok(paths)
+}
+
+/// Validates a bare, returning its SAD content keyed by path. Each SAD must verify against its
+/// own said. When the prod being answered is supplied, the bare must be routed to the prod's reply
+/// route and may only return content at requested paths.
+pub fn validate_bare(serder: &Serder, prod: Option<&Serder>) -> Result<Vec<(Pather, Value)>> {
+    validate_ilk(serder, Ilkage::bar)?;
+    validate_said(serder)?;
+
+    let ked = serder.ked();
+    let mut sads = vec![];
+    for (path, sad) in ked[Ids::a].to_map()? {
+        let pather = Pather::new_with_qb64(&path)?;
+        if pather.qb64()? != path {
+            return err!(Error::Validation(format!("invalid path = {path}")));
+        }
+
+        let saider = Saider::new_with_qb64(&sad[Ids::d].to_string()?)?;
+        if !saider.verify(&sad, Some(true), None, None, None, None)? {
+            return err!(Error::Validation(format!(
+                "invalid said = {d} for sad at path = {path}",
+                d = saider.qb64()?
+            )));
+        }
+
+        sads.push((pather, sad));
+    }
+
+    if let Some(prod) = prod {
+        let requested = validate_prod(prod)?;
+
+        let rr = prod.ked()[Ids::rr].to_string()?;
+        let route = ked[Ids::r].to_string()?;
+        if !rr.is_empty() && rr != route {
+            return err!(Error::Validation(format!(
+                "bare route = {route} does not match prod reply route = {rr}"
+            )));
+        }
+
+        for (pather, _) in &sads {
+            if !requested.contains(pather) {
+                return err!(Error::Validation(format!(
+                    "unrequested path = {p} in bare",
+                    p = pather.qb64()?
+                )));
+            }
+        }
+    }
+
+    Ok(sads)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        cesr::core::{
+            common::Ilkage, matter::Matter, sadder::Sadder, saider::Saider, serder::Serder,
+        },
+        data::dat,
+        eventing::{
+            event::{bare, prod},
+            validating::{validate_bare, validate_prod},
+        },
+        pathing::pather::Pather,
+    };
+
+    #[test]
+    fn prod_and_bare() {
+        let stamp = "2021-01-01T00:00:00.000000+00:00";
+        let path = Pather::new_with_path(&dat!(["a", "b"])).unwrap();
+        let other = Pather::new_with_path(&dat!(["c"])).unwrap();
+
+        let pro = prod(
+            Some("/sad"),
+            Some("/sad/reply"),
+            std::slice::from_ref(&path),
+            Some(stamp),
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(pro.ilk().unwrap(), Ilkage::pro);
+        assert_eq!(pro.ked()["q"]["p"], dat!([&path.qb64().unwrap()]));
+        assert_eq!(validate_prod(&pro).unwrap(), std::slice::from_ref(&path));
+        assert!(prod(None, None, &[], None, None, None).is_err());
+
+        let (_, sad) =
+            Saider::saidify(&dat!({ "d": "", "name": "John Jones" }), None, None, None, None)
+                .unwrap();
+        let (_, unrequested) =
+            Saider::saidify(&dat!({ "d": "", "name": "Jane Jones" }), None, None, None, None)
+                .unwrap();
+
+        let bar = bare(Some("/sad/reply"), &[(path.clone(), sad.clone())], Some(stamp), None, None)
+            .unwrap();
+        assert_eq!(bar.ilk().unwrap(), Ilkage::bar);
+        assert_eq!(validate_bare(&bar, None).unwrap(), [(path.clone(), sad.clone())]);
+        assert_eq!(validate_bare(&bar, Some(&pro)).unwrap(), [(path.clone(), sad.clone())]);
+
+        // wrong route
+        let bar = bare(Some("/elsewhere"), &[(path.clone(), sad.clone())], Some(stamp), None, None)
+            .unwrap();
+        assert!(validate_bare(&bar, None).is_ok());
+        assert!(validate_bare(&bar, Some(&pro)).is_err());
+
+        // unrequested path
+        let bar =
+            bare(Some("/sad/reply"), &[(other, unrequested)], Some(stamp), None, None).unwrap();
+        assert!(validate_bare(&bar, Some(&pro)).is_err());
+
+        // tampered sad
+        let mut tampered = sad.clone();
+        tampered["name"] = dat!("Mallory");
+        assert!(bare(None, &[(path.clone(), tampered.clone())], None, None, None).is_err());
+
+        let mut ked = bar.ked();
+        ked["a"] = dat!({ &path.qb64().unwrap(): tampered });
+        let (_, ked) = Saider::saidify(&ked, None, None, None, None).unwrap();
+        let forged = Serder::new_with_ked(&ked, None, None).unwrap();
+        assert!(validate_bare(&forged, None).is_err());
+
+        // ilk mismatch
+        assert!(validate_prod(&bar).is_err());
+        assert!(validate_bare(&pro, None).is_err());
+    }
+}
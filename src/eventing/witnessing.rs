@@ -0,0 +1,189 @@
+use crate::error::{err, Error, Result};
+
+pub(crate) fn has_duplicates(items: &[&str]) -> bool {
+    let mut unique = items.to_vec();
+    unique.sort_unstable();
+    unique.dedup();
+
+    unique.len() != items.len()
+}
+
+/// Computes a sufficient immune (ample) witness threshold for `n` witnesses tolerating `f` faulty
+/// witnesses, per KAWA. When `f` is `None` the maximum tolerable `f` for `n` is used. Weak
+/// thresholds (the default) are the smallest satisfying the agreement constraints, strong
+/// thresholds the largest.
+pub fn ample(n: u128, f: Option<u128>, weak: Option<bool>) -> Result<u128> {
+    let weak = weak.unwrap_or(true);
+    if let Some(f) = f {
+        let m1 = (n + f + 2) / 2;
+        let m2 = n.saturating_sub(f);
+
+        if m2 < m1 && n > 0 {
+            return err!(Error::Value(format!("invalid f={f}, too big for n={n}")));
+        }
+
+        if weak {
+            Ok(*[n, m1, m2].iter().min().unwrap_or(&n))
+        } else {
+            Ok(std::cmp::min(n, std::cmp::max(m1, m2)))
+        }
+    } else {
+        let f1 = std::cmp::max(1, n.saturating_sub(1) / 3);
+        let f2 = std::cmp::max(1, n.saturating_sub(1).div_ceil(3));
+
+        if weak {
+            Ok(*[n, (n + f1 + 2) / 2, (n + f2 + 2) / 2].iter().min().unwrap_or(&n))
+        } else {
+            Ok(std::cmp::min(n, std::cmp::max(n.saturating_sub(f1), (n + f1 + 2) / 2)))
+        }
+    }
+}
+
+/// A witness pool and its threshold of accountable duplicity (toad).
+#[derive(Debug, Clone, PartialEq)]
+pub struct WitnessConfig {
+    wits: Vec<String>,
+    toad: u128,
+}
+
+impl WitnessConfig {
+    /// Validates `toad` against `wits`, defaulting it to the weak `ample` threshold. An empty
+    /// pool requires a zero toad, otherwise it must lie in `1..=wits.len()`.
+    pub fn new(wits: &[&str], toad: Option<u128>) -> Result<Self> {
+        if has_duplicates(wits) {
+            return err!(Error::Value(format!("invalid wits = {wits:?}, has duplicates")));
+        }
+
+        let n = wits.len() as u128;
+        let toad = match toad {
+            Some(toad) => toad,
+            None if n == 0 => 0,
+            None => ample(n, None, None)?,
+        };
+
+        if (n == 0 && toad != 0) || (n > 0 && (toad < 1 || toad > n)) {
+            return err!(Error::Value(format!("invalid toad = {toad} for wits = {wits:?}")));
+        }
+
+        Ok(Self { wits: wits.iter().map(|wit| wit.to_string()).collect(), toad })
+    }
+
+    pub fn wits(&self) -> &[String] {
+        &self.wits
+    }
+
+    pub fn toad(&self) -> u128 {
+        self.toad
+    }
+
+    /// The largest number of faulty witnesses `f` for which this toad still guarantees agreement,
+    /// that is `ceil((n + f + 1) / 2) <= toad <= n - f`, or `None` if no such `f` exists.
+    pub fn fault_tolerance(&self) -> Option<u128> {
+        let n = self.wits.len() as u128;
+        if n == 0 {
+            return Some(0);
+        }
+
+        if 2 * self.toad < n + 1 {
+            return None;
+        }
+
+        Some(std::cmp::min(n - self.toad, 2 * self.toad - n - 1))
+    }
+
+    /// Counts the distinct witnesses among `receipts`, which must all be members of the pool.
+    pub fn tally(&self, receipts: &[&str]) -> Result<usize> {
+        let mut receipted: Vec<&str> = vec![];
+        for receipt in receipts {
+            if !self.wits.iter().any(|wit| wit == receipt) {
+                return err!(Error::Validation(format!("receipt from non-witness = {receipt}")));
+            }
+
+            if !receipted.contains(receipt) {
+                receipted.push(receipt);
+            }
+        }
+
+        Ok(receipted.len())
+    }
+
+    /// Whether `receipts` from witnesses meet the toad.
+    pub fn satisfied(&self, receipts: &[&str]) -> Result<bool> {
+        Ok(self.tally(receipts)? as u128 >= self.toad)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::eventing::witnessing::{ample, WitnessConfig};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0, 0, 0)]
+    #[case(1, 1, 1)]
+    #[case(2, 2, 2)]
+    #[case(3, 3, 3)]
+    #[case(4, 3, 3)]
+    #[case(5, 4, 4)]
+    #[case(6, 4, 5)]
+    #[case(7, 5, 5)]
+    #[case(10, 7, 7)]
+    #[case(13, 9, 9)]
+    fn ample_default_f(#[case] n: u128, #[case] weak: u128, #[case] strong: u128) {
+        assert_eq!(ample(n, None, None).unwrap(), weak);
+        assert_eq!(ample(n, None, Some(false)).unwrap(), strong);
+    }
+
+    #[rstest]
+    #[case(4, 0, 3, 4)]
+    #[case(4, 1, 3, 3)]
+    #[case(7, 1, 5, 6)]
+    #[case(7, 2, 5, 5)]
+    fn ample_with_f(#[case] n: u128, #[case] f: u128, #[case] weak: u128, #[case] strong: u128) {
+        assert_eq!(ample(n, Some(f), None).unwrap(), weak);
+        assert_eq!(ample(n, Some(f), Some(false)).unwrap(), strong);
+    }
+
+    #[test]
+    fn ample_invalid_f() {
+        assert!(ample(4, Some(2), None).is_err());
+        assert!(ample(7, Some(3), Some(false)).is_err());
+    }
+
+    #[test]
+    fn witness_config() {
+        let wits = ["BAAA", "BBBB", "BCCC", "BDDD"];
+
+        let config = WitnessConfig::new(&wits, None).unwrap();
+        assert_eq!(config.toad(), 3);
+        assert_eq!(config.wits(), wits);
+        assert_eq!(config.fault_tolerance(), Some(1));
+
+        assert!(!config.satisfied(&["BAAA", "BBBB"]).unwrap());
+        assert!(config.satisfied(&["BAAA", "BBBB", "BDDD"]).unwrap());
+
+        // duplicate receipts are counted once
+        assert_eq!(config.tally(&["BAAA", "BBBB", "BAAA"]).unwrap(), 2);
+        assert!(!config.satisfied(&["BAAA", "BBBB", "BBBB"]).unwrap());
+
+        // receipts from outside the pool are refused
+        assert!(config.tally(&["BAAA", "BEEE"]).is_err());
+        assert!(config.satisfied(&["BEEE"]).is_err());
+
+        // toads too low to guarantee agreement have no fault tolerance
+        let config = WitnessConfig::new(&wits, Some(2)).unwrap();
+        assert_eq!(config.fault_tolerance(), None);
+        assert!(config.satisfied(&["BCCC", "BDDD"]).unwrap());
+        assert_eq!(WitnessConfig::new(&wits, Some(4)).unwrap().fault_tolerance(), Some(0));
+
+        let config = WitnessConfig::new(&[], None).unwrap();
+        assert_eq!(config.toad(), 0);
+        assert_eq!(config.fault_tolerance(), Some(0));
+        assert!(config.satisfied(&[]).unwrap());
+
+        assert!(WitnessConfig::new(&[], Some(1)).is_err());
+        assert!(WitnessConfig::new(&wits, Some(0)).is_err());
+        assert!(WitnessConfig::new(&wits, Some(5)).is_err());
+        assert!(WitnessConfig::new(&["BAAA", "BAAA"], None).is_err());
+    }
+}
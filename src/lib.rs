@@ -15,6 +15,12 @@ pub mod prefexing;
 #[macro_use]
 #[cfg(feature = "pathing")]
 pub mod pathing;
+#[cfg(feature = "database")]
+pub mod database;
+#[cfg(feature = "eventing")]
+pub mod eventing;
+#[cfg(feature = "parsing")]
+pub mod parsing;
 
 pub mod data;
 pub mod error;
@@ -3,6 +3,7 @@ use crate::{
         cigar::Cigar,
         counter::{tables as counter, Counter},
         indexer::Indexer,
+        matter::Matter,
         sadder::Sadder,
         serder::Serder,
         siger::Siger,
@@ -11,7 +12,9 @@ use crate::{
     seal::Seal,
 };
 
-fn messagize(
+/// Serializes `serder` with its attachments as a text domain CESR message. When `seal` is given,
+/// `sigers` are attached as a transferable indexed signature group for the signer it identifies.
+pub fn messagize(
     serder: &Serder,
     sigers: Option<&[Siger]>,
     seal: Option<&Seal>,
@@ -27,17 +30,13 @@ fn messagize(
 
     if let Some(sigers) = sigers {
         if let Some(seal) = seal {
-            if seal.last {
-                atc += &Counter::new_with_code_and_count(counter::Codex::TransLastIdxSigGroups, 1)?
-                    .qb64()?;
-                atc += &seal.i();
-            } else {
-                atc += &Counter::new_with_code_and_count(counter::Codex::TransIdxSigGroups, 1)?
-                    .qb64()?;
-                atc += &seal.i();
-                atc += &seal.s()?;
-                atc += &seal.d();
-            }
+            let code = match seal {
+                Seal::Event { .. } => counter::Codex::TransIdxSigGroups,
+                Seal::Last { .. } => counter::Codex::TransLastIdxSigGroups,
+                _ => return err!(Error::Value(format!("invalid signer seal {seal:?}"))),
+            };
+            atc += &Counter::new_with_code_and_count(code, 1)?.qb64()?;
+            atc += &seal.qb64()?;
         }
 
         atc += &Counter::new_with_code_and_count(
@@ -60,7 +59,17 @@ fn messagize(
         }
     }
 
-    // todo: complete this
+    if let Some(cigars) = cigars {
+        atc += &Counter::new_with_code_and_count(
+            counter::Codex::NonTransReceiptCouples,
+            cigars.len() as u32,
+        )?
+        .qb64()?;
+        for cigar in cigars {
+            atc += &cigar.verfer().qb64()?;
+            atc += &cigar.qb64()?;
+        }
+    }
 
     Ok(message + &atc)
 }
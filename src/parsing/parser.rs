@@ -0,0 +1,442 @@
+use crate::{
+    cesr::core::{
+        cigar::Cigar,
+        common::{sniff, MINIMUM_SNIFF_SIZE},
+        counter::{tables as counter, Counter},
+        dater::Dater,
+        indexer::Indexer,
+        matter::Matter,
+        sadder::Sadder,
+        seqner::Seqner,
+        serder::Serder,
+        siger::Siger,
+        verfer::Verfer,
+    },
+    error::{err, Error, Result},
+    seal::Seal,
+};
+
+/// A message parsed from a CESR stream, with its attachments sorted by kind.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Message {
+    pub serder: Serder,
+    pub sigers: Vec<Siger>,            // controller indexed signatures
+    pub wigers: Vec<Siger>,            // witness indexed signatures
+    pub cigars: Vec<Cigar>,            // non-transferable receipt couples, verfers attached
+    pub trqs: Vec<(Seal, Siger)>,      // transferable receipt quadruples, event seal of signer
+    pub tsgs: Vec<(Seal, Vec<Siger>)>, // transferable indexed signature groups, event or last seal
+    pub frcs: Vec<(Seqner, Dater)>,    // first-seen replay couples
+    pub sscs: Vec<Seal>,               // seal source couples
+    pub ssts: Vec<Seal>,               // seal source triples
+}
+
+fn parse_sigers(qb64: &str, count: u32) -> Result<(Vec<Siger>, usize)> {
+    let mut offset = 0;
+    let mut sigers = vec![];
+    for _ in 0..count {
+        let siger = Siger::new_with_qb64(&qb64[offset..], None)?;
+        offset += siger.qb64()?.len();
+        sigers.push(siger);
+    }
+
+    Ok((sigers, offset))
+}
+
+fn parse_sig_group(qb64: &str) -> Result<(Vec<Siger>, usize)> {
+    let counter = Counter::new_with_qb64(qb64)?;
+    if counter.code() != counter::Codex::ControllerIdxSigs {
+        return err!(Error::UnexpectedCountCode(format!(
+            "expected controller signatures in group, got code = {c}",
+            c = counter.code()
+        )));
+    }
+
+    let offset = counter.qb64()?.len();
+    let (sigers, size) = parse_sigers(&qb64[offset..], counter.count())?;
+
+    Ok((sigers, offset + size))
+}
+
+impl Message {
+    /// Serializes the message with all of its attachments, each kind in its own counted group, so
+    /// that parsing the result yields this message again.
+    pub fn messagize(&self) -> Result<String> {
+        Ok(String::from_utf8(self.serder.raw())? + &self.attachments()?)
+    }
+
+    /// Serializes the attachments of the message alone, as they follow its event.
+    pub fn attachments(&self) -> Result<String> {
+        let mut atc = "".to_string();
+
+        let group = |code: &str, count: usize| -> Result<String> {
+            Counter::new_with_code_and_count(code, count as u32)?.qb64()
+        };
+
+        if !self.sigers.is_empty() {
+            atc += &group(counter::Codex::ControllerIdxSigs, self.sigers.len())?;
+            for siger in &self.sigers {
+                atc += &siger.qb64()?;
+            }
+        }
+
+        for (seal, sigers) in &self.tsgs {
+            let code = match seal {
+                Seal::Event { .. } => counter::Codex::TransIdxSigGroups,
+                Seal::Last { .. } => counter::Codex::TransLastIdxSigGroups,
+                _ => return err!(Error::Value(format!("invalid signer seal {seal:?}"))),
+            };
+            atc += &group(code, 1)?;
+            atc += &seal.qb64()?;
+            atc += &group(counter::Codex::ControllerIdxSigs, sigers.len())?;
+            for siger in sigers {
+                atc += &siger.qb64()?;
+            }
+        }
+
+        if !self.wigers.is_empty() {
+            atc += &group(counter::Codex::WitnessIdxSigs, self.wigers.len())?;
+            for wiger in &self.wigers {
+                atc += &wiger.qb64()?;
+            }
+        }
+
+        if !self.cigars.is_empty() {
+            atc += &group(counter::Codex::NonTransReceiptCouples, self.cigars.len())?;
+            for cigar in &self.cigars {
+                atc += &cigar.verfer().qb64()?;
+                atc += &cigar.qb64()?;
+            }
+        }
+
+        if !self.trqs.is_empty() {
+            atc += &group(counter::Codex::TransReceiptQuadruples, self.trqs.len())?;
+            for (seal, siger) in &self.trqs {
+                atc += &seal.qb64()?;
+                atc += &siger.qb64()?;
+            }
+        }
+
+        if !self.frcs.is_empty() {
+            atc += &group(counter::Codex::FirstSeenReplayCouples, self.frcs.len())?;
+            for (seqner, dater) in &self.frcs {
+                atc += &seqner.qb64()?;
+                atc += &dater.qb64()?;
+            }
+        }
+
+        if !self.sscs.is_empty() {
+            atc += &Seal::group(&self.sscs)?;
+        }
+
+        if !self.ssts.is_empty() {
+            atc += &Seal::group(&self.ssts)?;
+        }
+
+        Ok(atc)
+    }
+
+    /// Parses one counted attachment group from the start of `qb64` into this message, returning
+    /// the number of characters consumed.
+    fn parse_group(&mut self, qb64: &str) -> Result<usize> {
+        let counter = Counter::new_with_qb64(qb64)?;
+        let mut offset = counter.qb64()?.len();
+
+        match counter.code().as_str() {
+            counter::Codex::ControllerIdxSigs => {
+                let (sigers, size) = parse_sigers(&qb64[offset..], counter.count())?;
+                self.sigers.extend(sigers);
+                offset += size;
+            }
+            counter::Codex::WitnessIdxSigs => {
+                let (wigers, size) = parse_sigers(&qb64[offset..], counter.count())?;
+                self.wigers.extend(wigers);
+                offset += size;
+            }
+            counter::Codex::NonTransReceiptCouples => {
+                for _ in 0..counter.count() {
+                    let verfer = Verfer::new_with_qb64(&qb64[offset..])?;
+                    offset += verfer.qb64()?.len();
+                    let cigar = Cigar::new_with_qb64(&qb64[offset..], Some(&verfer))?;
+                    offset += cigar.qb64()?.len();
+                    self.cigars.push(cigar);
+                }
+            }
+            counter::Codex::TransReceiptQuadruples => {
+                for _ in 0..counter.count() {
+                    let (seal, size) = Seal::parse_event(&qb64[offset..])?;
+                    offset += size;
+                    let siger = Siger::new_with_qb64(&qb64[offset..], None)?;
+                    offset += siger.qb64()?.len();
+                    self.trqs.push((seal, siger));
+                }
+            }
+            counter::Codex::FirstSeenReplayCouples => {
+                for _ in 0..counter.count() {
+                    let seqner = Seqner::new_with_qb64(&qb64[offset..])?;
+                    offset += seqner.qb64()?.len();
+                    let dater = Dater::new_with_qb64(&qb64[offset..])?;
+                    offset += dater.qb64()?.len();
+                    self.frcs.push((seqner, dater));
+                }
+            }
+            counter::Codex::TransIdxSigGroups | counter::Codex::TransLastIdxSigGroups => {
+                for _ in 0..counter.count() {
+                    let (seal, size) = if counter.code() == counter::Codex::TransIdxSigGroups {
+                        Seal::parse_event(&qb64[offset..])?
+                    } else {
+                        Seal::parse_last(&qb64[offset..])?
+                    };
+                    offset += size;
+                    let (sigers, size) = parse_sig_group(&qb64[offset..])?;
+                    offset += size;
+                    self.tsgs.push((seal, sigers));
+                }
+            }
+            counter::Codex::SealSourceCouples | counter::Codex::SealSourceTriples => {
+                let (seals, size) = Seal::parse_group(qb64)?;
+                if counter.code() == counter::Codex::SealSourceCouples {
+                    self.sscs.extend(seals);
+                } else {
+                    self.ssts.extend(seals);
+                }
+                offset = size;
+            }
+            counter::Codex::AttachedMaterialQuadlets
+            | counter::Codex::BigAttachedMaterialQuadlets => {
+                let end = offset + counter.count() as usize * 4;
+                if end > qb64.len() {
+                    return err!(Error::Shortage(format!(
+                        "attached material of {n} quadlets exceeds stream",
+                        n = counter.count()
+                    )));
+                }
+
+                while offset < end {
+                    match self.parse_group(&qb64[offset..end]) {
+                        Ok(size) => offset += size,
+                        // the material is all there, so what falls short of it is malformed
+                        Err(e) if matches!(e.downcast_ref(), Some(Error::Shortage(_))) => {
+                            return err!(Error::Value(format!("malformed attached material, {e}")))
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+            }
+            code => {
+                return err!(Error::UnexpectedCountCode(format!(
+                    "unsupported attachment group code = {code}"
+                )))
+            }
+        }
+
+        Ok(offset)
+    }
+}
+
+/// The size of the serialization at the start of `stream`, from its version string. A stream
+/// that ends before the serialization does is short.
+pub(crate) fn serialization_size(stream: &[u8]) -> Result<usize> {
+    if stream.len() < MINIMUM_SNIFF_SIZE {
+        return err!(Error::Shortage(format!(
+            "need more than {n} bytes to sniff",
+            n = stream.len()
+        )));
+    }
+
+    let size = sniff(&stream[..MINIMUM_SNIFF_SIZE])?.size as usize;
+    if stream.len() < size {
+        return err!(Error::Shortage(format!(
+            "serialization of {size} bytes exceeds stream of {n}",
+            n = stream.len()
+        )));
+    }
+
+    Ok(size)
+}
+
+/// Parses one message and its attachments from the start of a text domain CESR `stream`,
+/// returning the message and the number of characters consumed. A stream that ends inside the
+/// message, as a torn write leaves it, fails with `Error::Shortage`.
+pub fn parse_message(stream: &str) -> Result<(Message, usize)> {
+    let size = serialization_size(stream.as_bytes())?;
+    let raw = match stream.get(..size) {
+        Some(raw) => raw,
+        None => {
+            return err!(Error::Value(format!("event of {size} bytes ends inside a character")))
+        }
+    };
+    let serder = Serder::new_with_raw(raw.as_bytes())?;
+    let (message, offset) = parse_attachments(serder, &stream[size..])?;
+
+    Ok((message, size + offset))
+}
+
+/// Parses the attachments of `serder` from the start of `qb64`, returning the message and the
+/// number of characters consumed. Attachments end at the first non-ASCII character.
+pub fn parse_attachments(serder: Serder, qb64: &str) -> Result<(Message, usize)> {
+    // text domain attachments are ASCII, so that every offset into them is a char boundary
+    let qb64 = &qb64[..qb64.find(|c: char| !c.is_ascii()).unwrap_or(qb64.len())];
+    let mut offset = 0;
+    let mut message = Message { serder, ..Default::default() };
+    while qb64[offset..].starts_with('-') {
+        offset += message.parse_group(&qb64[offset..])?;
+    }
+
+    Ok((message, offset))
+}
+
+/// Parses every message of a text domain CESR `stream`.
+pub fn parse(stream: &str) -> Result<Vec<Message>> {
+    let mut messages = vec![];
+    let mut offset = 0;
+    while offset < stream.len() {
+        let (message, size) = parse_message(&stream[offset..])?;
+        messages.push(message);
+        offset += size;
+    }
+
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        cesr::core::{
+            common::Tierage,
+            counter::{tables as counter, Counter},
+            indexer::Indexer,
+            matter::{tables as matter, Matter},
+            sadder::Sadder,
+            salter::Salter,
+            siger::Siger,
+        },
+        error::Error,
+        eventing::event::{incept, interact},
+        messaging::messagize,
+        parsing::parser::{parse, parse_message},
+        seal::Seal,
+    };
+
+    fn qb64s(sigers: &[Siger]) -> Vec<String> {
+        sigers.iter().map(|siger| siger.qb64().unwrap()).collect()
+    }
+
+    #[test]
+    fn messages() {
+        let salter = Salter::new_with_raw(b"0123456789abcdef", None, Some(Tierage::min)).unwrap();
+        let signers = salter.signers(Some(2), None, Some("parse"), None, None, None, None).unwrap();
+        let witness =
+            &salter.signers(Some(1), None, Some("wit"), None, Some(false), None, None).unwrap()[0];
+        let keys: Vec<String> = signers.iter().map(|s| s.verfer().qb64().unwrap()).collect();
+
+        let icp = incept(
+            &[&keys[0], &keys[1]],
+            None,
+            None,
+            None,
+            None,
+            Some(&[&witness.verfer().qb64().unwrap()]),
+            None,
+            None,
+            None,
+            None,
+            Some(matter::Codex::Blake3_256),
+            None,
+            None,
+        )
+        .unwrap();
+        let sigers: Vec<_> = signers
+            .iter()
+            .enumerate()
+            .map(|(i, s)| s.sign_indexed(&icp.raw(), false, i as u32, None).unwrap())
+            .collect();
+        let wiger = witness.sign_indexed(&icp.raw(), false, 0, None).unwrap();
+        let cigar = witness.sign_unindexed(&icp.raw()).unwrap();
+
+        let msg = messagize(
+            &icp,
+            Some(&sigers),
+            None,
+            Some(std::slice::from_ref(&wiger)),
+            Some(std::slice::from_ref(&cigar)),
+        )
+        .unwrap();
+        let (message, size) = parse_message(&msg).unwrap();
+        assert_eq!(size, msg.len());
+        assert_eq!(message.serder, icp);
+        assert_eq!(qb64s(&message.sigers), qb64s(&sigers));
+        assert_eq!(qb64s(&message.wigers), qb64s(&[wiger]));
+        assert_eq!(message.cigars, [cigar]);
+        assert_eq!(message.cigars[0].verfer(), witness.verfer());
+
+        // signatures of another controller, anchored by its latest establishment event
+        let seal =
+            Seal::Event { i: icp.pre().unwrap(), s: "0".to_string(), d: icp.said().unwrap() };
+        let ixn =
+            interact(&icp.pre().unwrap(), &icp.said().unwrap(), None, None, None, None).unwrap();
+        let tsig = signers[0].sign_indexed(&ixn.raw(), false, 0, None).unwrap();
+        let sealed =
+            messagize(&ixn, Some(std::slice::from_ref(&tsig)), Some(&seal), None, None).unwrap();
+
+        // seal source couples, wrapped in an attached material group
+        let source = Seal::Source { s: "0".to_string(), d: icp.said().unwrap() };
+        let atc = Seal::group(std::slice::from_ref(&source)).unwrap();
+        let wrapped = String::from_utf8(ixn.raw()).unwrap()
+            + &Counter::new_with_code_and_count(
+                counter::Codex::AttachedMaterialQuadlets,
+                atc.len() as u32 / 4,
+            )
+            .unwrap()
+            .qb64()
+            .unwrap()
+            + &atc;
+
+        let messages = parse(&(msg.clone() + &sealed + &wrapped)).unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[1].serder, ixn);
+        assert!(messages[1].sigers.is_empty());
+        assert_eq!(messages[1].tsgs.len(), 1);
+        assert_eq!(messages[1].tsgs[0].0, seal);
+        assert_eq!(qb64s(&messages[1].tsgs[0].1), qb64s(&[tsig]));
+        assert_eq!(messages[2].sscs, [source]);
+
+        // messages serialize back to equivalent streams
+        for message in &messages {
+            let (reparsed, _) = parse_message(&message.messagize().unwrap()).unwrap();
+            assert_eq!(&reparsed, message);
+        }
+        assert_eq!(messages[0].messagize().unwrap(), msg);
+        assert_eq!(messages[1].messagize().unwrap(), sealed);
+
+        assert!(parse("").unwrap().is_empty());
+
+        // truncated and unsupported attachments
+        assert!(parse(&msg[..msg.len() - 4]).is_err());
+        let unsupported = String::from_utf8(ixn.raw()).unwrap() + "-JAA";
+        assert!(parse(&unsupported).is_err());
+        let short = String::from_utf8(ixn.raw()).unwrap() + "-VAB";
+        assert!(parse(&short).is_err());
+
+        // streams torn inside a message fall short of it
+        let shortage = |stream: &str| {
+            matches!(parse(stream).unwrap_err().downcast_ref(), Some(Error::Shortage(_)))
+        };
+        for cut in [1, 20, icp.raw().len() - 1, icp.raw().len() + 2, msg.len() - 1] {
+            assert!(shortage(&msg[..cut]));
+        }
+        let torn = wrapped[..wrapped.len() - 4].to_string();
+        assert!(shortage(&torn));
+        let malformed = String::from_utf8(ixn.raw()).unwrap() + "-VAB-AAB";
+        assert!(!shortage(&malformed));
+
+        // non-ASCII attachments are refused rather than sliced
+        let ixn = String::from_utf8(ixn.raw()).unwrap();
+        let icp = String::from_utf8(icp.raw()).unwrap();
+        for atc in ["-VAB-AAé", "é", "-AABé", "-VAB-AA😀"] {
+            assert!(parse(&(ixn.clone() + atc)).is_err());
+            assert!(parse(&(icp.clone() + atc)).is_err());
+        }
+        let wide = icp.replacen("\"a\":[]", "\"a\":[\"é\"]", 1);
+        assert!(parse(&wide).is_err());
+    }
+}
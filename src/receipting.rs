@@ -8,7 +8,8 @@ use crate::{
     error::Result,
 };
 
-fn receipt(
+/// Builds a receipt (rct) for the event of `pre` at `sn` with said `said`.
+pub fn receipt(
     pre: &str,
     sn: u128,
     said: &str,
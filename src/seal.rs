@@ -1,32 +1,259 @@
+#[cfg(feature = "prefixing")]
+use crate::prefexing::prefixer::Prefixer;
 use crate::{
-    cesr::core::{matter::Matter, seqner::Seqner},
-    error::Result,
+    cesr::core::{
+        counter::{tables as counter, Counter},
+        diger::Diger,
+        matter::Matter,
+        seqner::Seqner,
+    },
+    data::{dat, Value},
+    error::{err, Error, Result},
 };
 
+/// Seals anchor external data in the `a` field of key events, or identify the source of an
+/// attachment. Sequence numbers are hex strings, as they appear in events.
 #[derive(Debug, Clone, PartialEq)]
-pub(crate) struct Seal {
-    pub i: String,
-    pub s: String,
-    pub d: String,
-    pub last: bool,
+pub enum Seal {
+    /// digest of external data
+    Digest { d: String },
+    /// merkle tree root digest of external data
+    Root { rd: String },
+    /// sn and said of a source event, the prefix being implied
+    Source { s: String, d: String },
+    /// prefix, sn and said of an event
+    Event { i: String, s: String, d: String },
+    /// prefix whose latest establishment event is implied
+    Last { i: String },
+    /// prefix, sn, ilk and prior said locating an event
+    Location { i: String, s: String, t: String, p: String },
+    /// backer prefix and digest of backer metadata
+    Backer { bi: String, d: String },
+    /// registry prefix and digest of registry metadata
+    Registrar { ri: String, d: String },
+}
+
+fn labels(map: &indexmap::IndexMap<String, Value>) -> Vec<&str> {
+    let mut labels: Vec<&str> = map.keys().map(|key| key.as_str()).collect();
+    labels.sort_unstable();
+    labels
 }
 
 impl Seal {
-    pub fn new(i: &str, s: &str, d: &str, last: Option<bool>) -> Self {
-        let last = last.unwrap_or(false);
+    pub fn to_value(&self) -> Value {
+        match self {
+            Self::Digest { d } => dat!({ "d": d.as_str() }),
+            Self::Root { rd } => dat!({ "rd": rd.as_str() }),
+            Self::Source { s, d } => dat!({ "s": s.as_str(), "d": d.as_str() }),
+            Self::Event { i, s, d } => dat!({ "i": i.as_str(), "s": s.as_str(), "d": d.as_str() }),
+            Self::Last { i } => dat!({ "i": i.as_str() }),
+            Self::Location { i, s, t, p } => {
+                dat!({ "i": i.as_str(), "s": s.as_str(), "t": t.as_str(), "p": p.as_str() })
+            }
+            Self::Backer { bi, d } => dat!({ "bi": bi.as_str(), "d": d.as_str() }),
+            Self::Registrar { ri, d } => dat!({ "ri": ri.as_str(), "d": d.as_str() }),
+        }
+    }
+
+    /// Seals are discriminated by their exact set of field labels.
+    pub fn from_value(value: &Value) -> Result<Self> {
+        let map = value.to_map()?;
+        let field = |label: &str| value[label].to_string();
+
+        let seal = match labels(&map).as_slice() {
+            ["d"] => Self::Digest { d: field("d")? },
+            ["rd"] => Self::Root { rd: field("rd")? },
+            ["d", "s"] => Self::Source { s: field("s")?, d: field("d")? },
+            ["d", "i", "s"] => Self::Event { i: field("i")?, s: field("s")?, d: field("d")? },
+            ["i"] => Self::Last { i: field("i")? },
+            ["i", "p", "s", "t"] => {
+                Self::Location { i: field("i")?, s: field("s")?, t: field("t")?, p: field("p")? }
+            }
+            ["bi", "d"] => Self::Backer { bi: field("bi")?, d: field("d")? },
+            ["d", "ri"] => Self::Registrar { ri: field("ri")?, d: field("d")? },
+            labels => return err!(Error::Value(format!("unknown seal with labels {labels:?}"))),
+        };
+
+        Ok(seal)
+    }
+
+    pub fn from_values(values: &[Value]) -> Result<Vec<Self>> {
+        values.iter().map(Self::from_value).collect()
+    }
+
+    pub fn to_values(seals: &[Self]) -> Vec<Value> {
+        seals.iter().map(|seal| seal.to_value()).collect()
+    }
+
+    /// The counter code of the attachment group that carries this kind of seal.
+    pub fn group_code(&self) -> Result<&'static str> {
+        Ok(match self {
+            Self::Source { .. } => counter::Codex::SealSourceCouples,
+            Self::Event { .. } => counter::Codex::SealSourceTriples,
+            _ => return err!(Error::Value(format!("no attachment group for seal {self:?}"))),
+        })
+    }
+
+    /// The concatenated qb64 primitives of this seal as it appears in attachments, without any
+    /// counter. Sequence numbers are encoded as `Seqner`s.
+    pub fn qb64(&self) -> Result<String> {
+        Ok(match self {
+            Self::Source { s, d } => Seqner::new_with_snh(s)?.qb64()? + d,
+            Self::Event { i, s, d } => i.clone() + &Seqner::new_with_snh(s)?.qb64()? + d,
+            Self::Last { i } => i.clone(),
+            _ => return err!(Error::Value(format!("seal {self:?} cannot be attached"))),
+        })
+    }
+
+    /// Builds a counted attachment group of seals, which must all be of the same kind.
+    pub fn group(seals: &[Self]) -> Result<String> {
+        let code = match seals.first() {
+            Some(seal) => seal.group_code()?,
+            None => return err!(Error::Value("empty seal group".to_string())),
+        };
+
+        let mut atc = Counter::new_with_code_and_count(code, seals.len() as u32)?.qb64()?;
+        for seal in seals {
+            if seal.group_code()? != code {
+                return err!(Error::Value(format!("mixed seal group at seal {seal:?}")));
+            }
+            atc += &seal.qb64()?;
+        }
+
+        Ok(atc)
+    }
 
-        Self { i: i.to_string(), s: s.to_string(), d: d.to_string(), last }
+    /// Parses a counted seal attachment group from the start of `qb64`, returning the seals and
+    /// the number of characters consumed.
+    pub fn parse_group(qb64: &str) -> Result<(Vec<Self>, usize)> {
+        let counter = Counter::new_with_qb64(qb64)?;
+        let mut offset = counter.qb64()?.len();
+
+        let mut seals = vec![];
+        for _ in 0..counter.count() {
+            let (seal, size) = match counter.code().as_str() {
+                counter::Codex::SealSourceCouples => Self::parse_source(&qb64[offset..])?,
+                #[cfg(feature = "prefixing")]
+                counter::Codex::SealSourceTriples => Self::parse_event(&qb64[offset..])?,
+                code => {
+                    return err!(Error::UnexpectedCountCode(format!(
+                        "unexpected seal group code = {code}"
+                    )))
+                }
+            };
+            seals.push(seal);
+            offset += size;
+        }
+
+        Ok((seals, offset))
+    }
+
+    pub(crate) fn parse_source(qb64: &str) -> Result<(Self, usize)> {
+        let seqner = Seqner::new_with_qb64(qb64)?;
+        let offset = seqner.qb64()?.len();
+        let diger = Diger::new_with_qb64(&qb64[offset..])?;
+        let d = diger.qb64()?;
+
+        Ok((Self::Source { s: seqner.snh()?, d: d.clone() }, offset + d.len()))
     }
 
-    pub fn i(&self) -> String {
-        self.i.clone()
+    #[cfg(feature = "prefixing")]
+    pub(crate) fn parse_event(qb64: &str) -> Result<(Self, usize)> {
+        let prefixer = Prefixer::new_with_qb64(qb64)?;
+        let i = prefixer.qb64()?;
+        let (source, size) = Self::parse_source(&qb64[i.len()..])?;
+
+        match source {
+            Self::Source { s, d } => Ok((Self::Event { i: i.clone(), s, d }, i.len() + size)),
+            // unreachable
+            _ => err!(Error::Parsing("expected source seal".to_string())),
+        }
     }
 
-    pub fn s(&self) -> Result<String> {
-        Seqner::new_with_snh(&self.s)?.qb64()
+    #[cfg(feature = "prefixing")]
+    pub(crate) fn parse_last(qb64: &str) -> Result<(Self, usize)> {
+        let prefixer = Prefixer::new_with_qb64(qb64)?;
+        let i = prefixer.qb64()?;
+        let size = i.len();
+
+        Ok((Self::Last { i }, size))
     }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{cesr::core::counter::tables as counter, data::dat, seal::Seal};
+
+    const PRE: &str = "EBAjyPZ8Ed4XXl5cVZhqAy7SuaGivQp0WqQKVXvg7oqd";
+    const DIG: &str = "EIM66TjBMfwPnbwK7oZqbZyGz9nOeVmQHeH3NZxrsk8F";
+
+    #[test]
+    fn values() {
+        let seals = [
+            Seal::Digest { d: DIG.to_string() },
+            Seal::Root { rd: DIG.to_string() },
+            Seal::Source { s: "1".to_string(), d: DIG.to_string() },
+            Seal::Event { i: PRE.to_string(), s: "a".to_string(), d: DIG.to_string() },
+            Seal::Last { i: PRE.to_string() },
+            Seal::Location {
+                i: PRE.to_string(),
+                s: "2".to_string(),
+                t: "ixn".to_string(),
+                p: DIG.to_string(),
+            },
+            Seal::Backer { bi: PRE.to_string(), d: DIG.to_string() },
+            Seal::Registrar { ri: PRE.to_string(), d: DIG.to_string() },
+        ];
+
+        for seal in &seals {
+            assert_eq!(Seal::from_value(&seal.to_value()).unwrap(), *seal);
+        }
+
+        let values = Seal::to_values(&seals);
+        assert_eq!(Seal::from_values(&values).unwrap(), seals);
+
+        assert_eq!(seals[3].to_value(), dat!({ "i": PRE, "s": "a", "d": DIG }));
+
+        // field order does not matter, field set does
+        assert_eq!(Seal::from_value(&dat!({ "d": DIG, "s": "a", "i": PRE })).unwrap(), seals[3]);
+        assert!(Seal::from_value(&dat!({ "i": PRE, "x": DIG })).is_err());
+        assert!(Seal::from_value(&dat!({ "i": PRE, "s": 1, "d": DIG })).is_err());
+        assert!(Seal::from_value(&dat!([PRE])).is_err());
+    }
+
+    #[test]
+    fn groups() {
+        let triples = [
+            Seal::Event { i: PRE.to_string(), s: "0".to_string(), d: DIG.to_string() },
+            Seal::Event { i: PRE.to_string(), s: "1f".to_string(), d: DIG.to_string() },
+        ];
+        let atc = Seal::group(&triples).unwrap();
+        assert!(atc.starts_with(counter::Codex::SealSourceTriples));
+        assert_eq!(
+            atc,
+            "-IAC".to_string()
+                + PRE
+                + "0AAAAAAAAAAAAAAAAAAAAAAA"
+                + DIG
+                + PRE
+                + "0AAAAAAAAAAAAAAAAAAAAAAf"
+                + DIG
+        );
+
+        let extra = atc.clone() + "-AAB";
+        let (parsed, size) = Seal::parse_group(&extra).unwrap();
+        assert_eq!(parsed, triples);
+        assert_eq!(size, atc.len());
+
+        let couples = [Seal::Source { s: "3".to_string(), d: DIG.to_string() }];
+        let atc = Seal::group(&couples).unwrap();
+        assert_eq!(atc, "-GAB0AAAAAAAAAAAAAAAAAAAAAAD".to_string() + DIG);
+        assert_eq!(Seal::parse_group(&atc).unwrap(), (couples.to_vec(), atc.len()));
 
-    pub fn d(&self) -> String {
-        self.d.clone()
+        assert!(Seal::group(&[]).is_err());
+        assert!(Seal::group(&[triples[0].clone(), couples[0].clone()]).is_err());
+        assert!(Seal::group(&[Seal::Digest { d: DIG.to_string() }]).is_err());
+        assert!(Seal::parse_group("-AAB").is_err());
+        assert!(Seal::parse_group(&atc[..atc.len() - 4]).is_err());
     }
 }
@@ -1,41 +1,113 @@
-use anyhow::Ok;
-use zeroize::ZeroizeOnDrop;
-
 use crate::cesr::common::Tierage;
 use crate::cesr::Salter;
 
-use crate::cesr::core::matter::tables as matter;
-use crate::error::Result;
+use crate::cesr::core::matter::{tables as matter, Matter};
+use crate::error::{err, Error, Result};
 use crate::signing::Signer;
 
 pub const STEM: &str = "signify:aid";
 
+/// Names of the key creation algorithms.
+#[allow(non_snake_case)]
+#[allow(non_upper_case_globals)]
+pub mod Algos {
+    pub const randy: &str = "randy";
+    pub const salty: &str = "salty";
+    pub const r#extern: &str = "extern";
+}
+
 /// Creating a key pair based on algorithm.
-// pub trait Creator: Default {
-//     #[allow(clippy::too_many_arguments)]
-//     fn create(
-//         &self,
-//         codes: Option<Vec<&str>>,
-//         count: Option<u16>,
-//         code: Option<&str>,
-//         pidx: Option<u16>,
-//         ridx: Option<u16>,
-//         kidx: Option<u16>,
-//         stem: Option<&str>,
-//         transferable: Option<bool>,
-//         temp: bool,
-//     ) -> Vec<Signer>;
-// }
-
-#[derive(Debug, ZeroizeOnDrop)]
+pub trait Creator: std::fmt::Debug {
+    /// Creates a signer for each of `codes`, or `count` signers of `code` when no codes are
+    /// given. Creators that derive keys use the prefix, rotation and key indices and `stem`.
+    #[allow(clippy::too_many_arguments)]
+    fn create(
+        &self,
+        codes: Option<Vec<&str>>,
+        count: Option<u16>,
+        code: Option<&str>,
+        pidx: Option<u16>,
+        ridx: Option<u16>,
+        kidx: Option<u16>,
+        stem: Option<&str>,
+        transferable: Option<bool>,
+        temp: bool,
+    ) -> Result<Vec<Signer>>;
+
+    /// The qb64 of the salt keys derive from, empty when keys are not derived.
+    fn salt(&self) -> Result<String> {
+        Ok("".to_string())
+    }
+
+    fn stem(&self) -> String {
+        "".to_string()
+    }
+
+    fn tier(&self) -> String {
+        "".to_string()
+    }
+}
+
+fn codes<'a>(
+    codes: Option<Vec<&'a str>>,
+    count: Option<u16>,
+    code: Option<&'a str>,
+) -> Vec<&'a str> {
+    let code = code.unwrap_or(matter::Codex::Ed25519_Seed);
+    let count = count.unwrap_or(1);
+    let codes = codes.unwrap_or_default();
+
+    if codes.is_empty() {
+        (0..count).map(|_| code).collect()
+    } else {
+        codes
+    }
+}
+
+/// Makes the creator of a key creation algorithm.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Creatory {
+    algo: String,
+}
+
+impl Creatory {
+    pub fn new(algo: Option<&str>) -> Result<Self> {
+        let algo = algo.unwrap_or(Algos::salty);
+        match algo {
+            Algos::salty | Algos::randy | Algos::r#extern => Ok(Self { algo: algo.to_string() }),
+            _ => err!(Error::Value(format!("unsupported key creation algorithm = {algo}"))),
+        }
+    }
+
+    pub fn algo(&self) -> &str {
+        &self.algo
+    }
+
+    /// Makes a creator. Salty creators take `salt`, `stem` and `tier`, extern creators the
+    /// supplied `signers`.
+    pub fn make(
+        &self,
+        salt: Option<&str>,
+        stem: Option<&str>,
+        tier: Option<&str>,
+        signers: Option<Vec<Signer>>,
+    ) -> Result<Box<dyn Creator>> {
+        Ok(match self.algo.as_str() {
+            Algos::salty => Box::new(SaltyCreator::new(salt, stem, tier, None)?),
+            Algos::randy => Box::new(RandyCreator::new()),
+            _ => match signers {
+                Some(signers) => Box::new(ExternCreator::new(signers)),
+                None => return err!(Error::Value("extern creator without keys".to_string())),
+            },
+        })
+    }
+}
+
+#[derive(Debug)]
 pub struct SaltyCreator {
-    salt: String,
-    #[zeroize(skip)]
     stem: String,
-    #[zeroize(skip)]
     tier: String,
-    #[zeroize(skip)]
-    salter: Salter,
+    salter: Salter, // zeroized on drop
 }
 
 impl SaltyCreator {
@@ -45,35 +117,21 @@ impl SaltyCreator {
         tier: Option<&str>,
         salter: Option<Salter>,
     ) -> Result<Self> {
-        let mut salty_creator = Self::default();
-
-        if let Some(stm) = stem {
-            salty_creator.stem = stm.to_string();
-        }
-
-        if let Some(s) = salter {
-            salty_creator.salter = s;
-        } else {
-            salty_creator.salter = Salter::new(tier, None, None, None, salt, None).unwrap();
-        }
-
-        Ok(salty_creator)
-    }
-
-    fn set_salt(&mut self, salt: &str) {
-        self.salt = salt.to_string();
-    }
-
-    fn set_stem(&mut self, stem: &str) {
-        self.stem = stem.to_string();
-    }
-
-    fn set_tier(&mut self, tier: &str) {
-        self.tier = tier.to_string();
+        let salter = match salter {
+            Some(salter) => salter,
+            None => Salter::new(tier, None, None, None, salt, None)?,
+        };
+
+        Ok(Self {
+            stem: stem.unwrap_or("").to_string(),
+            tier: tier.unwrap_or(Tierage::low).to_string(),
+            salter,
+        })
     }
+}
 
-    #[allow(clippy::too_many_arguments)]
-    pub fn create(
+impl Creator for SaltyCreator {
+    fn create(
         &self,
         codes: Option<Vec<&str>>,
         count: Option<u16>,
@@ -84,10 +142,8 @@ impl SaltyCreator {
         stem: Option<&str>,
         transferable: Option<bool>,
         temp: bool,
-    ) -> Vec<Signer> {
-        let code = code.unwrap_or(matter::Codex::Ed25519_Seed);
-        let count = count.unwrap_or(1);
-        let mut codes = codes.unwrap_or(vec![]);
+    ) -> Result<Vec<Signer>> {
+        let codes = self::codes(codes, count, code);
         let pidx = pidx.unwrap_or(0);
         let ridx = ridx.unwrap_or(0);
         let kidx = kidx.unwrap_or(0);
@@ -97,31 +153,29 @@ impl SaltyCreator {
         let stem =
             if !self.stem.is_empty() { self.stem.as_str() } else { stem.unwrap_or(ps.as_str()) };
 
-        if codes.is_empty() {
-            codes = (0..count).map(|_| code).collect();
-        }
-
-        let mut signers = vec![];
+        let deriver = self.salter.deriver(Some(stem), Some(&self.tier), Some(temp));
+        let mut keys = vec![];
         for (i, c) in codes.iter().enumerate() {
-            let path = format!("{}{:x}{:x}", stem, &ridx, kidx + i as u16);
-            signers.push(
-                self.salter
-                    .signer(Some(c), Some(transferable), Some(&path), Some(&self.tier), Some(temp))
-                    .unwrap(),
-            );
+            let index = match u16::try_from(i).ok().and_then(|i| kidx.checked_add(i)) {
+                Some(index) => index,
+                None => return err!(Error::Value("key indices exhausted".to_string())),
+            };
+            keys.push((deriver.path(ridx, index), *c));
         }
-        signers
+
+        deriver.derive(&keys, Some(transferable))
     }
-}
 
-impl Default for SaltyCreator {
-    fn default() -> Self {
-        SaltyCreator {
-            salt: "".to_string(),
-            stem: "".to_string(),
-            tier: Tierage::low.to_string(),
-            salter: Salter::new(Some(Tierage::low), None, None, None, None, None).unwrap(),
-        }
+    fn salt(&self) -> Result<String> {
+        self.salter.qb64()
+    }
+
+    fn stem(&self) -> String {
+        self.stem.clone()
+    }
+
+    fn tier(&self) -> String {
+        self.tier.clone()
     }
 }
 
@@ -129,41 +183,98 @@ impl Default for SaltyCreator {
 pub struct RandyCreator {}
 
 impl RandyCreator {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self::default()
     }
+}
 
+impl Creator for RandyCreator {
     fn create(
         &self,
         codes: Option<Vec<&str>>,
         count: Option<u16>,
         code: Option<&str>,
+        _pidx: Option<u16>,
+        _ridx: Option<u16>,
+        _kidx: Option<u16>,
+        _stem: Option<&str>,
         transferable: Option<bool>,
-    ) -> Vec<Signer> {
-        let code = code.unwrap_or(matter::Codex::Ed25519_Seed);
-        let count = count.unwrap_or(1);
-        let mut codes = codes.unwrap_or(vec![]);
+        _temp: bool,
+    ) -> Result<Vec<Signer>> {
         let transferable = transferable.unwrap_or(true);
 
-        if codes.is_empty() {
-            codes = (0..count).map(|_| code).collect();
-        }
+        self::codes(codes, count, code)
+            .iter()
+            .map(|c| Signer::new(Some(transferable), Some(c), None, None, None, None))
+            .collect()
+    }
+}
+
+/// Hands out keys created elsewhere, such as on a hardware device. The key index selects the
+/// first of the supplied signers returned.
+#[derive(Debug, Default)]
+pub struct ExternCreator {
+    signers: Vec<Signer>,
+}
+
+impl ExternCreator {
+    pub fn new(signers: Vec<Signer>) -> Self {
+        Self { signers }
+    }
+}
+
+impl Creator for ExternCreator {
+    fn create(
+        &self,
+        codes: Option<Vec<&str>>,
+        count: Option<u16>,
+        code: Option<&str>,
+        _pidx: Option<u16>,
+        _ridx: Option<u16>,
+        kidx: Option<u16>,
+        _stem: Option<&str>,
+        transferable: Option<bool>,
+        _temp: bool,
+    ) -> Result<Vec<Signer>> {
+        let codes = self::codes(codes, count, code);
+        let kidx = kidx.unwrap_or(0) as usize;
+
+        let signers = match self.signers.get(kidx..kidx + codes.len()) {
+            Some(signers) => signers,
+            None => {
+                return err!(Error::Value(format!(
+                    "{} supplied keys, {} requested from index {kidx}",
+                    self.signers.len(),
+                    codes.len()
+                )))
+            }
+        };
 
         codes
             .iter()
-            .map(|c| Signer::new(Some(transferable), Some(c), None, None, None, None).unwrap())
+            .zip(signers)
+            .map(|(c, signer)| {
+                if signer.code() != *c {
+                    return err!(Error::UnexpectedCode(signer.code()));
+                }
+                Signer::new_with_raw(&signer.raw(), transferable, Some(c))
+            })
             .collect()
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::cesr::{
-        core::matter::{tables as matter, Matter},
-        Salter,
+    use crate::{
+        cesr::{
+            common::Tierage,
+            core::matter::{tables as matter, Matter},
+            Salter,
+        },
+        signing::Signer,
     };
 
-    use super::{RandyCreator, SaltyCreator};
+    use super::{Algos, Creator, Creatory, ExternCreator, RandyCreator, SaltyCreator};
 
     #[test]
     fn test_salty_python_interop() {
@@ -172,14 +283,15 @@ mod test {
         assert_eq!(sc.stem, "");
         assert_eq!(sc.tier, sc.salter.tier());
 
-        let signers = sc.create(None, None, None, None, None, None, None, None, false);
+        let signers = sc.create(None, None, None, None, None, None, None, None, false).unwrap();
         assert_eq!(signers.len(), 1);
 
         let signer = &signers[0];
         assert_eq!(signer.code(), matter::Codex::Ed25519_Seed);
         assert_eq!(signer.verfer().code(), matter::Codex::Ed25519);
 
-        let signers = sc.create(None, Some(2), None, None, None, None, None, Some(false), false);
+        let signers =
+            sc.create(None, Some(2), None, None, None, None, None, Some(false), false).unwrap();
         assert_eq!(signers.len(), 2);
 
         for s in signers.iter() {
@@ -198,7 +310,7 @@ mod test {
         assert_eq!(sc.salter.raw(), raw);
         assert_eq!(sc.salter.qb64().unwrap(), salt);
 
-        let signers = sc.create(None, None, None, None, None, None, None, None, false);
+        let signers = sc.create(None, None, None, None, None, None, None, None, false).unwrap();
         assert_eq!(signers.len(), 1);
 
         let signer = &signers[0];
@@ -208,7 +320,8 @@ mod test {
         assert_eq!(signer.verfer().code(), matter::Codex::Ed25519);
         assert_eq!(signer.verfer().qb64().unwrap(), "DMZy6qbgnKzvCE594tQ4SPs6pIECXTYQBH7BkC4hNY3E");
 
-        let signers = sc.create(None, Some(1), None, None, None, None, None, Some(false), true);
+        let signers =
+            sc.create(None, Some(1), None, None, None, None, None, Some(false), true).unwrap();
         assert_eq!(signers.len(), 1);
 
         let signer = &signers[0];
@@ -218,7 +331,7 @@ mod test {
         assert_eq!(signer.verfer().qb64().unwrap(), "BFRtyHAjSuJaRX6TDPva35GN11VHAruaOXMc79ZYDKsT");
 
         let sc = SaltyCreator::new(Some(&salt), Some("0123456789abcdefghijk"), None, None).unwrap();
-        let signers = sc.create(None, None, None, None, None, None, None, None, false);
+        let signers = sc.create(None, None, None, None, None, None, None, None, false).unwrap();
         assert_eq!(signers.len(), 1);
         let signer = &signers[0];
         println!("{:?}", signer.qb64().unwrap());
@@ -232,14 +345,15 @@ mod test {
     #[test]
     fn test_randy_python_interop() {
         let rc = RandyCreator::new();
-        let signers = rc.create(None, None, None, None);
+        let signers = rc.create(None, None, None, None, None, None, None, None, false).unwrap();
         assert_eq!(signers.len(), 1);
 
         let signer = &signers[0];
         assert_eq!(signer.code(), matter::Codex::Ed25519_Seed);
         assert_eq!(signer.verfer().code(), matter::Codex::Ed25519);
 
-        let signers = rc.create(None, Some(2), None, Some(false));
+        let signers =
+            rc.create(None, Some(2), None, None, None, None, None, Some(false), false).unwrap();
         assert_eq!(signers.len(), 2);
 
         for signer in signers.iter() {
@@ -247,4 +361,52 @@ mod test {
             assert_eq!(signer.verfer().code(), matter::Codex::Ed25519N);
         }
     }
+
+    #[test]
+    fn extern_creator() {
+        let supplied: Vec<Signer> =
+            (0..3).map(|_| Signer::new_with_defaults(None, None).unwrap()).collect();
+        let ec = ExternCreator::new(supplied.clone());
+
+        let signers =
+            ec.create(None, Some(2), None, None, None, Some(1), None, Some(false), false).unwrap();
+        assert_eq!(signers.len(), 2);
+        assert_eq!(signers[0].raw(), supplied[1].raw());
+        assert_eq!(signers[1].raw(), supplied[2].raw());
+        assert_eq!(signers[1].verfer().code(), matter::Codex::Ed25519N);
+
+        assert!(ec.create(None, Some(2), None, None, None, Some(2), None, None, false).is_err());
+        let codes = vec![matter::Codex::ECDSA_256k1_Seed];
+        assert!(ec.create(Some(codes), None, None, None, None, None, None, None, false).is_err());
+    }
+
+    #[test]
+    fn creatory() {
+        let salt = Salter::new_with_raw(b"0123456789abcdef", None, None).unwrap().qb64().unwrap();
+        let creator = Creatory::new(None).unwrap().make(Some(&salt), None, None, None).unwrap();
+        assert_eq!(creator.salt().unwrap(), salt);
+        assert_eq!(creator.tier(), Tierage::low);
+        let signers =
+            creator.create(None, None, None, None, None, None, None, None, false).unwrap();
+        assert_eq!(signers[0].qb64().unwrap(), "APMJe0lwOpwnX9PkvX1mh26vlzGYl6RWgWGclc8CAQJ9");
+
+        let creator = Creatory::new(Some(Algos::randy)).unwrap().make(None, None, None, None);
+        let creator = creator.unwrap();
+        assert_eq!(creator.salt().unwrap(), "");
+        assert_eq!(
+            creator.create(None, Some(3), None, None, None, None, None, None, false).unwrap().len(),
+            3
+        );
+
+        let creatory = Creatory::new(Some(Algos::r#extern)).unwrap();
+        assert_eq!(creatory.algo(), Algos::r#extern);
+        assert!(creatory.make(None, None, None, None).is_err());
+        let signer = Signer::new_with_defaults(None, None).unwrap();
+        let creator = creatory.make(None, None, None, Some(vec![signer.clone()])).unwrap();
+        let signers =
+            creator.create(None, None, None, None, None, None, None, None, false).unwrap();
+        assert_eq!(signers, [signer]);
+
+        assert!(Creatory::new(Some("group")).is_err());
+    }
 }
@@ -0,0 +1,1004 @@
+use crate::{
+    cesr::core::{
+        cigar::Cigar,
+        common::Ilkage,
+        diger::Diger,
+        indexer::Indexer,
+        matter::{tables as matter, Matter},
+        sadder::Sadder,
+        serder::Serder,
+        siger::Siger,
+        verfer::Verfer,
+    },
+    data::{dat, Value},
+    error::{err, Error, Result},
+    eventing::{
+        event,
+        kever::Kever,
+        kevery::{Kevery, Outcome},
+    },
+    messaging::messagize,
+    parsing::parser::parse,
+    receipting::receipt,
+    seal::Seal,
+    signify::{keeping::Keystore, managing::Manager},
+};
+
+fn qb64s<M: Matter>(matters: &[M]) -> Result<Vec<String>> {
+    matters.iter().map(|matter| matter.qb64()).collect()
+}
+
+fn refs(strings: &[String]) -> Vec<&str> {
+    strings.iter().map(|string| string.as_str()).collect()
+}
+
+/// The error of a failed change, with the error of undoing the change attached when that failed
+/// too.
+fn undo_failed(error: anyhow::Error, undone: Result<()>) -> anyhow::Error {
+    match undone {
+        Ok(()) => error,
+        Err(undo) => error.context(format!("undo failed: {undo}")),
+    }
+}
+
+/// A locally controlled identifier, as KERIpy's Hab: its keys are created and used by a
+/// `Manager` and its KEL is kept by a `Kevery`, which also holds the key state of the
+/// identifiers whose messages it verifies. Events are signed, accepted into the KEL and returned
+/// as CESR messages.
+#[derive(Debug)]
+pub struct Habitat<S: Keystore> {
+    pre: String,
+    manager: Manager<S>,
+    kevery: Kevery,
+}
+
+impl<S: Keystore> Habitat<S> {
+    /// Incepts a new identifier with `icount` signing keys and `ncount` next keys, as many as
+    /// signing keys by default, created by `manager`. Non-transferable identifiers have a single
+    /// key as their prefix. Returns the habitat and the signed inception event.
+    #[allow(clippy::too_many_arguments)]
+    pub fn incept(
+        mut manager: Manager<S>,
+        kevery: Option<Kevery>,
+        icount: Option<usize>,
+        isith: Option<&Value>,
+        ncount: Option<usize>,
+        nsith: Option<&Value>,
+        toad: Option<u128>,
+        wits: Option<&[&str]>,
+        cnfg: Option<&[&str]>,
+        data: Option<&[Seal]>,
+        delpre: Option<&str>,
+        transferable: Option<bool>,
+    ) -> Result<(Self, String)> {
+        let transferable = transferable.unwrap_or(true);
+        let icount = icount.unwrap_or(1);
+        let ncount = if transferable { ncount.unwrap_or(icount) } else { 0 };
+        if !transferable && icount != 1 {
+            return err!(Error::Value("non-transferable identifiers have one key".to_string()));
+        }
+
+        let (verfers, digers) = manager.incept(
+            None,
+            Some(icount),
+            None,
+            None,
+            Some(ncount),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(transferable),
+            None,
+        )?;
+        let keys = qb64s(&verfers)?;
+        let ndigs = qb64s(&digers)?;
+
+        // keys are recorded under the first of them until the prefix is known
+        let mut habitat =
+            Self { pre: keys[0].clone(), manager, kevery: kevery.unwrap_or_default() };
+        let code = if transferable { Some(matter::Codex::Blake3_256) } else { None };
+        let made = habitat
+            .make_inception(&keys, &ndigs, isith, nsith, toad, wits, cnfg, data, delpre, code);
+        match made {
+            Ok(message) => Ok((habitat, message)),
+            Err(error) => {
+                let undone = habitat.manager.unincept(&habitat.pre);
+                Err(undo_failed(error, undone))
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn make_inception(
+        &mut self,
+        keys: &[String],
+        ndigs: &[String],
+        isith: Option<&Value>,
+        nsith: Option<&Value>,
+        toad: Option<u128>,
+        wits: Option<&[&str]>,
+        cnfg: Option<&[&str]>,
+        data: Option<&[Seal]>,
+        delpre: Option<&str>,
+        code: Option<&str>,
+    ) -> Result<String> {
+        let serder = event::incept(
+            &refs(keys),
+            isith,
+            Some(&refs(ndigs)),
+            nsith,
+            toad,
+            wits,
+            cnfg,
+            data,
+            None,
+            None,
+            code,
+            None,
+            delpre,
+        )?;
+        let pre = serder.pre()?;
+        self.manager.move_prefix(&keys[0], &pre)?;
+        self.pre = pre;
+        self.kevery.add_prefix(&self.pre);
+
+        self.make(&serder)
+    }
+
+    pub fn pre(&self) -> &str {
+        &self.pre
+    }
+
+    pub fn manager(&self) -> &Manager<S> {
+        &self.manager
+    }
+
+    pub fn kevery(&self) -> &Kevery {
+        &self.kevery
+    }
+
+    /// The kevery, to process the messages of other identifiers.
+    pub fn kevery_mut(&mut self) -> &mut Kevery {
+        &mut self.kevery
+    }
+
+    /// The key state of the identifier, once its inception is accepted.
+    pub fn kever(&self) -> Result<&Kever> {
+        match self.kevery.kever(&self.pre) {
+            Some(kever) => Ok(kever),
+            None => err!(Error::Value(format!("no accepted key state for {}", self.pre))),
+        }
+    }
+
+    /// The KEL of the identifier, as a CESR stream.
+    pub fn replay(&self) -> Result<String> {
+        self.kevery.replay(&self.pre)
+    }
+
+    fn pubs(&self) -> Result<Vec<String>> {
+        match self.manager.sit(&self.pre)? {
+            Some(sit) => Ok(sit.new.pubs),
+            None => err!(Error::Value(format!("no keys recorded for {}", self.pre))),
+        }
+    }
+
+    /// Signs `ser` with the current signing keys, indexed by their position in the key list.
+    pub fn sign(&self, ser: &[u8]) -> Result<Vec<Siger>> {
+        let pubs = self.pubs()?;
+        self.manager.sign(ser, &refs(&pubs), None, None)
+    }
+
+    /// Signs `ser` with the current signing keys, without indices.
+    pub fn sign_unindexed(&self, ser: &[u8]) -> Result<Vec<Cigar>> {
+        let pubs = self.pubs()?;
+        self.manager.sign_unindexed(ser, &refs(&pubs))
+    }
+
+    /// Signs an establishment event, indexing each signature into the prior next keys too.
+    fn sign_establishment(&self, ser: &[u8]) -> Result<Vec<Siger>> {
+        let sit = match self.manager.sit(&self.pre)? {
+            Some(sit) => sit,
+            None => return err!(Error::Value(format!("no keys recorded for {}", self.pre))),
+        };
+        self.manager.sign(ser, &refs(&sit.new.pubs), None, Some(&sit.ondices()))
+    }
+
+    /// Signs an event of the identifier and accepts it into the KEL, returning its message. Own
+    /// events may only be held back awaiting delegation.
+    fn make(&mut self, serder: &Serder) -> Result<String> {
+        let sigers = if serder.est()? {
+            self.sign_establishment(&serder.raw())?
+        } else {
+            self.sign(&serder.raw())?
+        };
+        let message = messagize(serder, Some(&sigers), None, None, None)?;
+
+        for outcome in self.kevery.process_stream(&message)? {
+            match outcome {
+                Outcome::Accepted | Outcome::Escrowed(_) => {}
+                outcome => {
+                    return err!(Error::Validation(format!(
+                        "own event of {pre} not accepted, {outcome:?}",
+                        pre = self.pre
+                    )))
+                }
+            }
+        }
+
+        Ok(message)
+    }
+
+    /// Rotates to the next keys, pre-rotating `ncount` new ones, as many as the next keys by
+    /// default. Returns the signed rotation event. The rotation of the keys is undone unless the
+    /// event is made and processed, and the seeds of the prior keys are only erased once it is.
+    #[allow(clippy::too_many_arguments)]
+    pub fn rotate(
+        &mut self,
+        isith: Option<&Value>,
+        ncount: Option<usize>,
+        nsith: Option<&Value>,
+        toad: Option<u128>,
+        cuts: Option<&[&str]>,
+        adds: Option<&[&str]>,
+        data: Option<&[Seal]>,
+    ) -> Result<String> {
+        let ncount = ncount.unwrap_or(self.kever()?.digers().len());
+        let prior = match self.manager.sit(&self.pre)? {
+            Some(prior) => prior,
+            None => {
+                return err!(Error::Value(format!("no keys recorded for {pre}", pre = self.pre)))
+            }
+        };
+
+        let (verfers, digers) = self.manager.rotate(
+            &self.pre,
+            None,
+            Some(ncount),
+            None,
+            None,
+            None,
+            None,
+            Some(false),
+        )?;
+        let made = self.make_rotation(&verfers, &digers, isith, nsith, toad, cuts, adds, data);
+        let message = match made {
+            Ok(message) => message,
+            Err(error) => {
+                let undone = self.manager.unrotate(&self.pre, &prior);
+                return Err(undo_failed(error, undone));
+            }
+        };
+
+        for pub_ in &prior.old.pubs {
+            self.manager.keeper_mut().remove_signer(pub_)?;
+        }
+
+        Ok(message)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn make_rotation(
+        &mut self,
+        verfers: &[Verfer],
+        digers: &[Diger],
+        isith: Option<&Value>,
+        nsith: Option<&Value>,
+        toad: Option<u128>,
+        cuts: Option<&[&str]>,
+        adds: Option<&[&str]>,
+        data: Option<&[Seal]>,
+    ) -> Result<String> {
+        let kever = self.kever()?;
+        let said = kever.said()?;
+        let sn = kever.sn()? + 1;
+        let wits = kever.wits().to_vec();
+        let ilk = if kever.delpre().is_some() { Ilkage::drt } else { Ilkage::rot };
+        let keys = qb64s(verfers)?;
+        let ndigs = qb64s(digers)?;
+
+        let serder = event::rotate(
+            &self.pre,
+            &refs(&keys),
+            &said,
+            Some(ilk),
+            Some(sn),
+            isith,
+            Some(&refs(&ndigs)),
+            nsith,
+            toad,
+            Some(&refs(&wits)),
+            cuts,
+            adds,
+            data,
+            None,
+            None,
+            None,
+        )?;
+
+        self.make(&serder)
+    }
+
+    /// Anchors `data` in an interaction event, returning the signed event.
+    pub fn interact(&mut self, data: Option<&[Seal]>) -> Result<String> {
+        let kever = self.kever()?;
+        let serder =
+            event::interact(&self.pre, &kever.said()?, Some(kever.sn()? + 1), data, None, None)?;
+        kever.traits().validate_event(&serder)?;
+
+        self.make(&serder)
+    }
+
+    /// Signs `serder`, a message of any kind, returning it with its signatures attached. A
+    /// transferable identifier attaches an indexed signature group sealed to its latest
+    /// establishment event, or to its latest keys whatever they are when `last`. A
+    /// non-transferable one attaches receipt couples.
+    pub fn endorse(&self, serder: &Serder, last: Option<bool>) -> Result<String> {
+        let kever = self.kever()?;
+        if !kever.prefixer().transferable() {
+            let cigars = self.sign_unindexed(&serder.raw())?;
+            return messagize(serder, None, None, None, Some(&cigars));
+        }
+
+        let seal = if last.unwrap_or(false) {
+            Seal::Last { i: self.pre.clone() }
+        } else {
+            let est = kever.est();
+            Seal::Event { i: self.pre.clone(), s: est.sner()?.numh()?, d: est.said()? }
+        };
+        let sigers = self.sign(&serder.raw())?;
+
+        messagize(serder, Some(&sigers), Some(&seal), None, None)
+    }
+
+    /// Receipts the key event `serder` of another identifier, returning the signed receipt.
+    pub fn receipt(&self, serder: &Serder) -> Result<String> {
+        let kever = self.kever()?;
+        let reserder = receipt(&serder.pre()?, serder.sn()?, &serder.said()?, None, None)?;
+
+        if !kever.prefixer().transferable() {
+            let cigars = self.sign_unindexed(&serder.raw())?;
+            return messagize(&reserder, None, None, None, Some(&cigars));
+        }
+
+        let est = kever.est();
+        let seal = Seal::Event { i: self.pre.clone(), s: est.sner()?.numh()?, d: est.said()? };
+        let sigers = self.sign(&serder.raw())?;
+
+        messagize(&reserder, Some(&sigers), Some(&seal), None, None)
+    }
+
+    /// Queries `src` about `pre`, on `route`, the KEL by default, returning the signed query.
+    pub fn query(
+        &self,
+        pre: &str,
+        src: &str,
+        route: Option<&str>,
+        query: Option<&Value>,
+        stamp: Option<&str>,
+    ) -> Result<String> {
+        let mut query = query.cloned().unwrap_or(dat!({}));
+        query["i"] = dat!(pre);
+        query["src"] = dat!(src);
+        let serder =
+            event::query(Some(route.unwrap_or("logs")), None, Some(&query), stamp, None, None)?;
+
+        self.endorse(&serder, Some(true))
+    }
+}
+
+/// A group multisig identifier as seen by one of its members, as KERIpy's GroupHab. The group's
+/// keys are the first current keys of its signing members and its next key digests the first
+/// next key digests of its rotating members, read from their key states in the member's kevery.
+/// Each member signs group events with its own key at its index in the group, exchanges its
+/// partial signatures with the others and merges theirs until the group's thresholds are met.
+#[derive(Debug)]
+pub struct GroupHabitat<S: Keystore> {
+    pre: String,
+    mhab: Habitat<S>,
+    smids: Vec<String>, // signing members
+    rmids: Vec<String>, // rotating members
+}
+
+impl<S: Keystore> GroupHabitat<S> {
+    /// Incepts the group of `smids`, rotating to the next keys of `rmids`, the signing members by
+    /// default, as the member `mhab`. The kevery of `mhab` must hold the key states of all
+    /// members. Returns the group habitat and the inception event with the member's signature.
+    #[allow(clippy::too_many_arguments)]
+    pub fn incept(
+        mhab: Habitat<S>,
+        smids: &[&str],
+        rmids: Option<&[&str]>,
+        isith: Option<&Value>,
+        nsith: Option<&Value>,
+        toad: Option<u128>,
+        wits: Option<&[&str]>,
+        cnfg: Option<&[&str]>,
+        data: Option<&[Seal]>,
+        delpre: Option<&str>,
+    ) -> Result<(Self, String)> {
+        let mut group = Self {
+            pre: "".to_string(),
+            mhab,
+            smids: smids.iter().map(|mid| mid.to_string()).collect(),
+            rmids: rmids.unwrap_or(smids).iter().map(|mid| mid.to_string()).collect(),
+        };
+        let (keys, ndigs) = group.keys(&group.smids, &group.rmids)?;
+
+        let serder = event::incept(
+            &refs(&keys),
+            isith,
+            Some(&refs(&ndigs)),
+            nsith,
+            toad,
+            wits,
+            cnfg,
+            data,
+            None,
+            None,
+            Some(matter::Codex::Blake3_256),
+            None,
+            delpre,
+        )?;
+        group.pre = serder.pre()?;
+        group.mhab.kevery.add_prefix(&group.pre);
+        let message = group.make(&serder)?;
+
+        Ok((group, message))
+    }
+
+    pub fn pre(&self) -> &str {
+        &self.pre
+    }
+
+    /// The habitat of the member.
+    pub fn mhab(&self) -> &Habitat<S> {
+        &self.mhab
+    }
+
+    /// The habitat of the member, to rotate its own keys and to process the events of the other
+    /// members before group rotations.
+    pub fn mhab_mut(&mut self) -> &mut Habitat<S> {
+        &mut self.mhab
+    }
+
+    pub fn smids(&self) -> &[String] {
+        &self.smids
+    }
+
+    pub fn rmids(&self) -> &[String] {
+        &self.rmids
+    }
+
+    /// The key state of the group, once its inception is accepted.
+    pub fn kever(&self) -> Result<&Kever> {
+        match self.mhab.kevery.kever(&self.pre) {
+            Some(kever) => Ok(kever),
+            None => err!(Error::Value(format!("no accepted key state for {}", self.pre))),
+        }
+    }
+
+    fn member(&self, mid: &str) -> Result<&Kever> {
+        match self.mhab.kevery.kever(mid) {
+            Some(kever) => Ok(kever),
+            None => err!(Error::Value(format!("no key state for group member {mid}"))),
+        }
+    }
+
+    /// The group's signing keys and next key digests, from the states of its signing members
+    /// `smids` and rotating members `rmids`.
+    fn keys(&self, smids: &[String], rmids: &[String]) -> Result<(Vec<String>, Vec<String>)> {
+        if !smids.iter().any(|mid| mid == self.mhab.pre()) {
+            return err!(Error::Value(format!(
+                "{pre} is not a signing member of the group",
+                pre = self.mhab.pre()
+            )));
+        }
+
+        let mut keys = vec![];
+        for mid in smids {
+            match self.member(mid)?.verfers().first() {
+                Some(verfer) => keys.push(verfer.qb64()?),
+                None => return err!(Error::Value(format!("no keys for group member {mid}"))),
+            }
+        }
+        let mut ndigs = vec![];
+        for mid in rmids {
+            match self.member(mid)?.digers().first() {
+                Some(diger) => ndigs.push(diger.qb64()?),
+                None => return err!(Error::Value(format!("no next keys for group member {mid}"))),
+            }
+        }
+
+        Ok((keys, ndigs))
+    }
+
+    /// The member's key among the group's `keys`, with its index there and, for rotations, among
+    /// the `prior` next key digests of the group, `None` when the key was not committed to. The
+    /// member may have rotated its own keys since the group's latest establishment event, so its
+    /// key is either its current or its prior signing key, whose seed is kept until the next
+    /// rotation.
+    pub fn indices(
+        &self,
+        keys: &[String],
+        prior: Option<&[Diger]>,
+    ) -> Result<(String, u32, Option<u32>)> {
+        let sit = match self.mhab.manager.sit(self.mhab.pre())? {
+            Some(sit) => sit,
+            None => return err!(Error::Value("member without keys".to_string())),
+        };
+        let found = keys
+            .iter()
+            .enumerate()
+            .find(|(_, key)| sit.new.pubs.contains(key) || sit.old.pubs.contains(key));
+        let (index, key) = match found {
+            Some((index, key)) => (index as u32, key.clone()),
+            None => {
+                return err!(Error::Value(format!(
+                    "no key of member {pre} is a key of the group",
+                    pre = self.mhab.pre()
+                )))
+            }
+        };
+
+        let ondex = match prior {
+            Some(prior) => {
+                let mut ondex = None;
+                for (i, diger) in prior.iter().enumerate() {
+                    if diger.verify(key.as_bytes())? {
+                        ondex = Some(i as u32);
+                        break;
+                    }
+                }
+                ondex
+            }
+            None => Some(index),
+        };
+
+        Ok((key, index, ondex))
+    }
+
+    /// Signs a group event with the member's key, indexed into the group's keys and, for
+    /// rotations, the group's prior next keys, and processes it, returning its message.
+    fn make(&mut self, serder: &Serder) -> Result<String> {
+        let (keys, prior) = match serder.ilk()?.as_str() {
+            Ilkage::rot | Ilkage::drt => {
+                (qb64s(&serder.verfers()?)?, Some(self.kever()?.digers().to_vec()))
+            }
+            Ilkage::ixn => (qb64s(self.kever()?.verfers())?, None),
+            _ => (qb64s(&serder.verfers()?)?, None),
+        };
+
+        let (key, index, ondex) = self.indices(&keys, prior.as_deref())?;
+        let sigers =
+            self.mhab.manager.sign(&serder.raw(), &[&key], Some(&[index]), Some(&[ondex]))?;
+
+        self.process(&messagize(serder, Some(&sigers), None, None, None)?)
+    }
+
+    fn process(&mut self, message: &str) -> Result<String> {
+        for outcome in self.mhab.kevery.process_stream(message)? {
+            if let Outcome::Rejected(reason) | Outcome::Duplicitous(reason) = outcome {
+                return err!(Error::Validation(format!(
+                    "group event of {pre} not accepted, {reason}",
+                    pre = self.pre
+                )));
+            }
+        }
+
+        Ok(message.to_string())
+    }
+
+    /// Merges the partial signatures of another member on a group event. Returns the event with
+    /// all of the signatures verified for it, in index order, once the group has accepted it.
+    pub fn merge(&mut self, message: &str) -> Result<Option<String>> {
+        let messages = parse(message)?;
+        for message in &messages {
+            if message.serder.pre()? != self.pre {
+                return err!(Error::Value(format!("not an event of group {}", self.pre)));
+            }
+        }
+        self.process(message)?;
+
+        for message in messages {
+            let serder = &message.serder;
+            match self.mhab.kevery.record(&self.pre, serder.sn()?) {
+                Some(record) if record.serder.said()? == serder.said()? => {
+                    let mut sigers = record.sigers.clone();
+                    sigers.sort_by_key(|siger| siger.index());
+                    return Ok(Some(messagize(&record.serder, Some(&sigers), None, None, None)?));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Rotates the group to the current keys of `smids` and the next keys of `rmids`, the prior
+    /// members by default. Members rotate their own keys first and the kevery of the member
+    /// must hold their new key states. Returns the rotation event with the member's signature.
+    /// The members of the group only change once the event is made and processed.
+    #[allow(clippy::too_many_arguments)]
+    pub fn rotate(
+        &mut self,
+        smids: Option<&[&str]>,
+        rmids: Option<&[&str]>,
+        isith: Option<&Value>,
+        nsith: Option<&Value>,
+        toad: Option<u128>,
+        cuts: Option<&[&str]>,
+        adds: Option<&[&str]>,
+        data: Option<&[Seal]>,
+    ) -> Result<String> {
+        let kever = self.kever()?;
+        let said = kever.said()?;
+        let sn = kever.sn()? + 1;
+        let wits = kever.wits().to_vec();
+        let ilk = if kever.delpre().is_some() { Ilkage::drt } else { Ilkage::rot };
+
+        let (mut nsmids, mut nrmids) = (self.smids.clone(), self.rmids.clone());
+        if let Some(smids) = smids {
+            nsmids = smids.iter().map(|mid| mid.to_string()).collect();
+            nrmids = nsmids.clone();
+        }
+        if let Some(rmids) = rmids {
+            nrmids = rmids.iter().map(|mid| mid.to_string()).collect();
+        }
+        let (keys, ndigs) = self.keys(&nsmids, &nrmids)?;
+
+        let serder = event::rotate(
+            &self.pre,
+            &refs(&keys),
+            &said,
+            Some(ilk),
+            Some(sn),
+            isith,
+            Some(&refs(&ndigs)),
+            nsith,
+            toad,
+            Some(&refs(&wits)),
+            cuts,
+            adds,
+            data,
+            None,
+            None,
+            None,
+        )?;
+        let message = self.make(&serder)?;
+
+        self.smids = nsmids;
+        self.rmids = nrmids;
+
+        Ok(message)
+    }
+
+    /// Anchors `data` in a group interaction event, returning it with the member's signature.
+    pub fn interact(&mut self, data: Option<&[Seal]>) -> Result<String> {
+        let kever = self.kever()?;
+        let serder =
+            event::interact(&self.pre, &kever.said()?, Some(kever.sn()? + 1), data, None, None)?;
+        kever.traits().validate_event(&serder)?;
+
+        self.make(&serder)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        cesr::core::{
+            common::{Ilkage, Tierage},
+            indexer::{tables as indexer, Indexer},
+            matter::{tables as matter, Matter},
+            sadder::Sadder,
+            salter::Salter,
+            verfer::Verfer,
+        },
+        data::dat,
+        eventing::{event, kevery::Outcome, traiting},
+        parsing::parser::parse,
+        seal::Seal,
+        signify::{
+            habbing::{GroupHabitat, Habitat},
+            keeping::{file::FileKeystore, memory::MemoryKeystore, Keeper},
+            managing::Manager,
+        },
+    };
+
+    fn manager(raw: &[u8]) -> Manager<MemoryKeystore> {
+        let salt = Salter::new_with_raw(raw, None, Some(Tierage::min)).unwrap().qb64().unwrap();
+        let keeper =
+            Keeper::new(MemoryKeystore::new(), "0123456789abcdefghijk", Some(Tierage::min))
+                .unwrap();
+        Manager::new(keeper, Some(&salt), None, Some(Tierage::min)).unwrap()
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn incept(
+        raw: &[u8],
+        wits: Option<&[&str]>,
+        transferable: bool,
+    ) -> (Habitat<MemoryKeystore>, String) {
+        Habitat::incept(
+            manager(raw),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            wits,
+            None,
+            None,
+            None,
+            Some(transferable),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn habitat() {
+        let (wit, _) = incept(b"wwwwwwwwwwwwwwww", None, false);
+        assert_eq!(&wit.pre()[..1], matter::Codex::Ed25519N);
+        assert!(wit.kever().unwrap().digers().is_empty());
+
+        // an inception that cannot be made leaves no keys behind
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileKeystore::open(dir.path()).unwrap();
+        let keeper = Keeper::new(store, "0123456789abcdefghijk", Some(Tierage::min)).unwrap();
+        let result = Habitat::incept(
+            Manager::new(keeper, None, None, Some(Tierage::min)).unwrap(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(5),
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+        assert!(result.is_err());
+        let store = FileKeystore::open(dir.path()).unwrap();
+        let keeper = Keeper::new(store, "0123456789abcdefghijk", Some(Tierage::min)).unwrap();
+        assert!(keeper.pubkeys().unwrap().is_empty());
+        assert!(keeper.prefixes().unwrap().is_empty());
+        assert_eq!(Manager::new(keeper, None, None, None).unwrap().pidx().unwrap(), 0);
+
+        // own events are accepted before they are witnessed
+        let (mut alice, icp) = incept(b"aaaaaaaaaaaaaaaa", Some(&[wit.pre()]), true);
+        assert_eq!(&alice.pre()[..1], matter::Codex::Blake3_256);
+        assert_eq!(alice.kever().unwrap().sn().unwrap(), 0);
+        assert!(alice.replay().unwrap().starts_with(&icp));
+
+        let (mut bob, bicp) = incept(b"bbbbbbbbbbbbbbbb", None, true);
+        let outcomes = bob.kevery_mut().process_stream(&icp).unwrap();
+        assert!(matches!(outcomes[..], [Outcome::Escrowed(_)]));
+
+        // the witness receipt completes the event for others
+        let serder = parse(&icp).unwrap()[0].serder.clone();
+        let rct = wit.receipt(&serder).unwrap();
+        assert_eq!(parse(&rct).unwrap()[0].cigars.len(), 1);
+        assert_eq!(bob.kevery_mut().process_stream(&rct).unwrap(), [Outcome::Accepted]);
+        assert_eq!(alice.kevery_mut().process_stream(&rct).unwrap(), [Outcome::Accepted]);
+        assert_eq!(bob.kevery().kever(alice.pre()).unwrap().sn().unwrap(), 0);
+
+        // a rotation that cannot be made leaves the keys and the key state as they were
+        let sit = alice.manager().sit(alice.pre()).unwrap().unwrap();
+        let pubkeys = alice.manager().keeper().pubkeys().unwrap();
+        let kever = alice.kever().unwrap().clone();
+        assert!(alice.rotate(None, Some(2), None, None, Some(&["Bunknown"]), None, None).is_err());
+        assert!(alice.rotate(None, Some(2), None, Some(5), None, None, None).is_err());
+        assert_eq!(alice.manager().sit(alice.pre()).unwrap().unwrap(), sit);
+        assert_eq!(alice.manager().keeper().pubkeys().unwrap(), pubkeys);
+        assert_eq!(alice.kever().unwrap(), &kever);
+
+        let rot = alice.rotate(None, Some(2), None, None, None, None, None).unwrap();
+        assert_eq!(alice.kever().unwrap().verfers()[0].qb64().unwrap(), sit.nxt.pubs[0]);
+        let ixn = alice.interact(Some(&[Seal::Digest { d: alice.pre().to_string() }])).unwrap();
+        assert_eq!(alice.kever().unwrap().sn().unwrap(), 2);
+        assert_eq!(alice.kever().unwrap().digers().len(), 2);
+        assert_eq!(alice.manager().sit(alice.pre()).unwrap().unwrap().nxt.pubs.len(), 2);
+        for msg in [&rot, &ixn] {
+            let serder = parse(msg).unwrap()[0].serder.clone();
+            bob.kevery_mut().process_stream(msg).unwrap();
+            bob.kevery_mut().process_stream(&wit.receipt(&serder).unwrap()).unwrap();
+        }
+        assert_eq!(
+            bob.kevery().kever(alice.pre()).unwrap().said().unwrap(),
+            alice.kever().unwrap().said().unwrap()
+        );
+
+        // transferable receipts are sealed to the latest establishment event of the validator
+        let serder = parse(&ixn).unwrap()[0].serder.clone();
+        let vrc = bob.receipt(&serder).unwrap();
+        let message = &parse(&vrc).unwrap()[0];
+        assert_eq!(message.serder.ilk().unwrap(), Ilkage::rct);
+        assert!(matches!(&message.tsgs[0].0, Seal::Event { i, .. } if i == bob.pre()));
+        assert!(alice.kevery_mut().process_stream(&vrc).is_ok_and(|o| o[0] != Outcome::Accepted));
+        alice.kevery_mut().process_stream(&bicp).unwrap();
+        assert_eq!(alice.kevery_mut().process_stream(&vrc).unwrap(), [Outcome::Accepted]);
+        assert_eq!(alice.kevery().record(alice.pre(), 2).unwrap().vrcs.len(), 1);
+
+        // endorsed messages carry signature groups
+        let rpy = event::reply(
+            Some("/end/role/add"),
+            Some(&dat!({"cid": alice.pre()})),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let message = &parse(&alice.endorse(&rpy, None).unwrap()).unwrap()[0];
+        let (seal, sigers) = &message.tsgs[0];
+        assert_eq!(
+            seal,
+            &Seal::Event {
+                i: alice.pre().to_string(),
+                s: "1".to_string(),
+                d: parse(&rot).unwrap()[0].serder.said().unwrap()
+            }
+        );
+        let verfers: &[Verfer] = alice.kever().unwrap().verfers();
+        for siger in sigers {
+            assert!(verfers[siger.index() as usize].verify(&siger.raw(), &rpy.raw()).unwrap());
+        }
+        let message = &parse(&wit.endorse(&rpy, None).unwrap()).unwrap()[0];
+        assert!(message.cigars[0].verfer().verify(&message.cigars[0].raw(), &rpy.raw()).unwrap());
+
+        let qry = alice.query(bob.pre(), wit.pre(), None, None, None).unwrap();
+        let message = &parse(&qry).unwrap()[0];
+        let ked = message.serder.ked();
+        assert_eq!(ked["r"].to_string().unwrap(), "logs");
+        assert_eq!(ked["q"]["i"].to_string().unwrap(), bob.pre());
+        assert_eq!(ked["q"]["src"].to_string().unwrap(), wit.pre());
+        assert_eq!(message.tsgs[0].0, Seal::Last { i: alice.pre().to_string() });
+
+        // non-transferable identifiers do not rotate
+        let mut wit = wit;
+        assert!(wit.rotate(None, None, None, None, None, None, None).is_err());
+        assert!(wit.interact(None).is_err());
+
+        // establishment only identifiers refuse to sign interactions
+        let (mut eo, _) = Habitat::incept(
+            manager(b"eeeeeeeeeeeeeeee"),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(&[traiting::Codex::EstOnly]),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert!(eo.interact(None).is_err());
+        assert_eq!(eo.kever().unwrap().sn().unwrap(), 0);
+        assert!(eo.rotate(None, None, None, None, None, None, None).is_ok());
+    }
+
+    #[test]
+    fn group() {
+        let (a, aicp) = incept(b"aaaaaaaaaaaaaaaa", None, true);
+        let (b, bicp) = incept(b"bbbbbbbbbbbbbbbb", None, true);
+        let (c, cicp) = incept(b"cccccccccccccccc", None, true);
+        let (d, _) = incept(b"dddddddddddddddd", None, true);
+        let mids = [a.pre().to_string(), b.pre().to_string(), c.pre().to_string()];
+        let smids: Vec<&str> = mids.iter().map(|mid| mid.as_str()).collect();
+
+        let mut habs = vec![];
+        for mut hab in [a, b, c] {
+            for icp in [&aicp, &bicp, &cicp] {
+                hab.kevery_mut().process_stream(icp).unwrap();
+            }
+            habs.push(hab);
+        }
+        let [a, b, c]: [Habitat<MemoryKeystore>; 3] = habs.try_into().unwrap();
+
+        // a non-member cannot incept the group
+        assert!(GroupHabitat::incept(d, &smids, None, None, None, None, None, None, None, None)
+            .is_err());
+
+        let incept = |hab| {
+            GroupHabitat::incept(
+                hab,
+                &smids,
+                None,
+                Some(&dat!(2)),
+                Some(&dat!(2)),
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap()
+        };
+        let (mut ga, aicp) = incept(a);
+        let (mut gb, bicp) = incept(b);
+        let (mut gc, cicp) = incept(c);
+        assert_eq!(ga.pre(), gb.pre());
+        assert_eq!(ga.pre(), gc.pre());
+        assert!(ga.kever().is_err());
+        assert_eq!(parse(&bicp).unwrap()[0].sigers[0].index(), 1);
+
+        // the event is emitted once its threshold is met
+        let icp = ga.merge(&bicp).unwrap().unwrap();
+        assert_eq!(parse(&icp).unwrap()[0].sigers.len(), 2);
+        assert_eq!(gb.merge(&aicp).unwrap(), Some(icp.clone()));
+        assert!(gc.merge(&icp).unwrap().is_some());
+        assert_eq!(gc.kever().unwrap().verfers().len(), 3);
+        assert!(ga.merge(&cicp).is_ok());
+
+        // members rotate their own keys first, the group follows with its members reordered
+        let mut rots = vec![];
+        for group in [&mut ga, &mut gb, &mut gc] {
+            rots.push(group.mhab_mut().rotate(None, None, None, None, None, None, None).unwrap());
+        }
+        for group in [&mut ga, &mut gb, &mut gc] {
+            for rot in &rots {
+                group.mhab_mut().kevery_mut().process_stream(rot).unwrap();
+            }
+        }
+        let reordered = [smids[2], smids[0], smids[1]];
+
+        // a rotation that cannot be made keeps the members
+        let cuts: &[&str] = &["Bunknown"];
+        let sith = dat!(2);
+        assert!(ga
+            .rotate(Some(&reordered), None, Some(&sith), None, None, Some(cuts), None, None)
+            .is_err());
+        assert_eq!(ga.smids(), smids);
+        assert_eq!(ga.rmids(), smids);
+
+        let arot = ga
+            .rotate(Some(&reordered), None, Some(&dat!(2)), Some(&dat!(2)), None, None, None, None)
+            .unwrap();
+        let siger = parse(&arot).unwrap()[0].sigers[0].clone();
+        assert_eq!((siger.index(), siger.ondex()), (1, 0));
+        assert_eq!(siger.code(), indexer::Codex::Ed25519_Big);
+        let crot = gc
+            .rotate(Some(&reordered), None, Some(&dat!(2)), Some(&dat!(2)), None, None, None, None)
+            .unwrap();
+        let rot = ga.merge(&crot).unwrap().unwrap();
+        assert!(gc.merge(&arot).unwrap().is_some());
+        assert!(gb.merge(&rot).unwrap().is_some());
+        assert_eq!(ga.smids(), reordered);
+        assert_eq!(gc.smids(), reordered);
+        let kever = ga.kever().unwrap();
+        assert_eq!(kever.sn().unwrap(), 1);
+        assert_eq!(kever.verfers()[1], ga.mhab().kever().unwrap().verfers()[0]);
+
+        let aixn = ga.interact(None).unwrap();
+        assert_eq!(parse(&aixn).unwrap()[0].sigers[0].code(), indexer::Codex::Ed25519);
+        assert_eq!(gb.merge(&aixn).unwrap(), None);
+        let bixn = gb.interact(None).unwrap();
+        assert!(ga.merge(&bixn).unwrap().is_some());
+        assert_eq!(ga.kever().unwrap().sn().unwrap(), 2);
+
+        // events of other identifiers are not merged, nor the group events streamed with them
+        assert!(ga.merge(&rots[0]).is_err());
+        let bixn = gb.interact(None).unwrap();
+        assert!(ga.merge(&(bixn + &rots[0])).is_err());
+        assert_eq!(ga.kever().unwrap().sn().unwrap(), 2);
+        assert!(ga.mhab().kevery().pses().is_empty());
+
+        // a member that rotated its own keys since still signs with its key of the group
+        let key = ga.kever().unwrap().verfers()[1].qb64().unwrap();
+        ga.mhab_mut().rotate(None, None, None, None, None, None, None).unwrap();
+        assert_ne!(ga.mhab().kever().unwrap().verfers()[0].qb64().unwrap(), key);
+        let aixn = ga.interact(None).unwrap();
+        let siger = parse(&aixn).unwrap()[0].sigers[0].clone();
+        assert_eq!(siger.index(), 1);
+        assert!(gb.merge(&aixn).unwrap().is_some());
+        assert_eq!(gb.kever().unwrap().sn().unwrap(), 3);
+    }
+}
@@ -0,0 +1,284 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    error::{err, Error, Result},
+    signify::keeping::Keystore,
+};
+
+/// A `Keystore` persisted in a directory, one JSON file of string values per table. A table is
+/// written to a temporary file, synced and renamed over the old one on every change, so a crash
+/// leaves either the old or the new table. Changes to several tables are listed in a journal
+/// before any is renamed, and completed on open if interrupted. On unix, files are readable by
+/// their owner only.
+#[derive(Debug)]
+pub struct FileKeystore {
+    dir: PathBuf,
+    tables: HashMap<String, BTreeMap<String, String>>,
+}
+
+fn validate_table(table: &str) -> Result<()> {
+    if table.is_empty() || !table.chars().all(|c| c.is_ascii_lowercase()) {
+        return err!(Error::Value(format!("invalid table name = {table}")));
+    }
+
+    Ok(())
+}
+
+const JOURNAL: &str = "journal"; // tables of an interrupted change, renamed on open
+
+#[cfg(unix)]
+fn create(path: &Path) -> Result<File> {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    Ok(OpenOptions::new().write(true).create(true).truncate(true).mode(0o600).open(path)?)
+}
+
+#[cfg(not(unix))]
+fn create(path: &Path) -> Result<File> {
+    Ok(OpenOptions::new().write(true).create(true).truncate(true).open(path)?)
+}
+
+impl FileKeystore {
+    /// Opens the keystore in `dir`, creating it if needed. A journaled change that was
+    /// interrupted is completed, other temporary files left by an interrupted write are removed.
+    pub fn open(dir: &Path) -> Result<Self> {
+        fs::create_dir_all(dir)?;
+
+        // the temporary files of a journaled change were all synced, complete it
+        let journal = dir.join(JOURNAL);
+        if journal.exists() {
+            let names: Vec<String> = serde_json::from_str(&fs::read_to_string(&journal)?)?;
+            for table in &names {
+                validate_table(table)?;
+                let temp = dir.join(format!("{table}.json.tmp"));
+                if temp.exists() {
+                    fs::rename(&temp, dir.join(format!("{table}.json")))?;
+                }
+            }
+            File::open(dir)?.sync_all()?;
+            fs::remove_file(&journal)?;
+        }
+
+        let mut tables = HashMap::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let name = match path.file_name().and_then(|name| name.to_str()) {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+
+            if name.ends_with(".json.tmp") || name == format!("{JOURNAL}.tmp") {
+                fs::remove_file(&path)?;
+            } else if let Some(table) = name.strip_suffix(".json") {
+                validate_table(table)?;
+                let values: BTreeMap<String, String> =
+                    serde_json::from_str(&fs::read_to_string(&path)?)?;
+                tables.insert(table.to_string(), values);
+            }
+        }
+
+        Ok(Self { dir: dir.to_path_buf(), tables })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Writes `values` to the temporary file of `table` and syncs it.
+    fn stage(&self, table: &str, values: &BTreeMap<String, String>) -> Result<()> {
+        let mut file = create(&self.dir.join(format!("{table}.json.tmp")))?;
+        file.write_all(serde_json::to_string(values)?.as_bytes())?;
+        file.sync_all()?;
+
+        Ok(())
+    }
+
+    /// Renames the staged temporary files of `tables` over the tables, through the journal when
+    /// there are several.
+    fn commit(&self, tables: &[&str]) -> Result<()> {
+        let journal = self.dir.join(JOURNAL);
+        if tables.len() > 1 {
+            let temp = self.dir.join(format!("{JOURNAL}.tmp"));
+            let mut file = create(&temp)?;
+            file.write_all(serde_json::to_string(tables)?.as_bytes())?;
+            file.sync_all()?;
+            fs::rename(&temp, &journal)?;
+            File::open(&self.dir)?.sync_all()?;
+        }
+
+        for table in tables {
+            let temp = self.dir.join(format!("{table}.json.tmp"));
+            fs::rename(&temp, self.dir.join(format!("{table}.json")))?;
+        }
+        File::open(&self.dir)?.sync_all()?;
+
+        if tables.len() > 1 {
+            fs::remove_file(&journal)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes `staged`, tables of values by name, replacing the tables held in memory once all of
+    /// them are on disk.
+    fn write(&mut self, staged: HashMap<String, BTreeMap<String, String>>) -> Result<()> {
+        for (table, values) in &staged {
+            self.stage(table, values)?;
+        }
+
+        let tables: Vec<&str> = staged.keys().map(|table| table.as_str()).collect();
+        self.commit(&tables)?;
+        self.tables.extend(staged);
+
+        Ok(())
+    }
+}
+
+impl Keystore for FileKeystore {
+    fn put(&mut self, table: &str, key: &str, value: &str) -> Result<()> {
+        self.put_all(&[(table, key, value)])
+    }
+
+    fn update(&mut self, puts: &[(&str, &str, &str)], removes: &[(&str, &str)]) -> Result<()> {
+        let mut staged: HashMap<String, BTreeMap<String, String>> = HashMap::new();
+        for (table, key, value) in puts {
+            validate_table(table)?;
+            staged
+                .entry(table.to_string())
+                .or_insert_with(|| self.tables.get(*table).cloned().unwrap_or_default())
+                .insert(key.to_string(), value.to_string());
+        }
+        for (table, key) in removes {
+            if self.tables.get(*table).is_some_and(|values| values.contains_key(*key)) {
+                staged
+                    .entry(table.to_string())
+                    .or_insert_with(|| self.tables.get(*table).cloned().unwrap_or_default())
+                    .remove(*key);
+            }
+        }
+
+        self.write(staged)
+    }
+
+    fn get(&self, table: &str, key: &str) -> Result<Option<String>> {
+        Ok(self.tables.get(table).and_then(|table| table.get(key)).cloned())
+    }
+
+    fn remove(&mut self, table: &str, key: &str) -> Result<bool> {
+        let mut values = match self.tables.get(table) {
+            Some(values) if values.contains_key(key) => values.clone(),
+            _ => return Ok(false),
+        };
+        values.remove(key);
+
+        self.write(HashMap::from([(table.to_string(), values)]))?;
+
+        Ok(true)
+    }
+
+    fn keys(&self, table: &str) -> Result<Vec<String>> {
+        Ok(self.tables.get(table).map(|table| table.keys().cloned().collect()).unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use crate::{
+        cesr::{common::Tierage, Matter, Salter},
+        signify::keeping::{file::FileKeystore, Keeper, Keystore},
+        signing::Signer,
+    };
+
+    #[test]
+    fn file() {
+        let dir = tempfile::tempdir().unwrap();
+        let passcode = "0123456789abcdefghijk";
+
+        let signer = Signer::new_with_defaults(None, None).unwrap();
+        let salter = Salter::new_with_defaults(Some(Tierage::min)).unwrap();
+        let pubkey = signer.verfer().qb64().unwrap();
+
+        let store = FileKeystore::open(dir.path()).unwrap();
+        let mut keeper = Keeper::new(store, passcode, Some(Tierage::min)).unwrap();
+        keeper.put_signer(&signer).unwrap();
+        keeper.put_salter("Epre", &salter).unwrap();
+        drop(keeper);
+
+        // only ciphertext reaches the disk
+        let pris = fs::read_to_string(dir.path().join("pris.json")).unwrap();
+        assert!(pris.contains(&pubkey));
+        assert!(!pris.contains(&signer.qb64().unwrap()));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(dir.path().join("pris.json")).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // an interrupted write leaves the previous table
+        fs::write(dir.path().join("pris.json.tmp"), "{\"torn").unwrap();
+        let store = FileKeystore::open(dir.path()).unwrap();
+        assert!(!dir.path().join("pris.json.tmp").exists());
+        let other = "abcdefghijk0123456789";
+        let reopened = FileKeystore::open(dir.path()).unwrap();
+        assert!(Keeper::new(reopened, other, Some(Tierage::min)).is_err());
+
+        let mut keeper = Keeper::new(store, passcode, Some(Tierage::min)).unwrap();
+        assert_eq!(keeper.signer(&pubkey).unwrap(), Some(signer.clone()));
+        assert_eq!(keeper.salter("Epre").unwrap(), Some(salter.clone()));
+
+        assert!(keeper.remove_signer(&pubkey).unwrap());
+        let store = FileKeystore::open(dir.path()).unwrap();
+        assert_eq!(store.get("pris", &pubkey).unwrap(), None);
+        assert!(store.get("salts", "Epre").unwrap().is_some());
+        assert_eq!(store.keys("gbls").unwrap(), ["aeid"]);
+
+        let mut store = store;
+        assert!(store.put("../pris", "key", "value").is_err());
+
+        // a passcode update that cannot be written changes nothing
+        let mut keeper = Keeper::new(store, passcode, Some(Tierage::min)).unwrap();
+        keeper.put_signer(&signer).unwrap();
+        fs::create_dir(dir.path().join("salts.json.tmp")).unwrap();
+        assert!(keeper.update_passcode(other, Some(Tierage::min)).is_err());
+        assert_eq!(keeper.signer(&pubkey).unwrap(), Some(signer.clone()));
+        fs::remove_dir(dir.path().join("salts.json.tmp")).unwrap();
+        drop(keeper);
+        let store = FileKeystore::open(dir.path()).unwrap();
+        let mut keeper = Keeper::new(store, passcode, Some(Tierage::min)).unwrap();
+        assert_eq!(keeper.signer(&pubkey).unwrap(), Some(signer.clone()));
+        assert_eq!(keeper.salter("Epre").unwrap(), Some(salter.clone()));
+
+        keeper.update_passcode(other, Some(Tierage::min)).unwrap();
+        drop(keeper);
+        let store = FileKeystore::open(dir.path()).unwrap();
+        assert!(!dir.path().join("journal").exists());
+        let keeper = Keeper::new(store, other, Some(Tierage::min)).unwrap();
+        assert_eq!(keeper.signer(&pubkey).unwrap(), Some(signer.clone()));
+        assert_eq!(keeper.salter("Epre").unwrap(), Some(salter));
+
+        // an interrupted change of several tables is completed on open
+        let mut store = FileKeystore::open(dir.path()).unwrap();
+        store.put_all(&[("pris", "a", "1"), ("salts", "b", "2")]).unwrap();
+        fs::write(dir.path().join("pris.json.tmp"), "{}").unwrap();
+        fs::write(dir.path().join("journal"), "[\"pris\",\"salts\"]").unwrap();
+        let store = FileKeystore::open(dir.path()).unwrap();
+        assert!(store.keys("pris").unwrap().is_empty());
+        assert_eq!(store.get("salts", "b").unwrap(), Some("2".to_string()));
+        assert!(!dir.path().join("journal").exists());
+
+        // values move between keys in one change
+        let mut store = FileKeystore::open(dir.path()).unwrap();
+        store.update(&[("salts", "c", "2")], &[("salts", "b"), ("prms", "b")]).unwrap();
+        let store = FileKeystore::open(dir.path()).unwrap();
+        assert_eq!(store.keys("salts").unwrap(), ["Epre", "c"]);
+        assert!(store.keys("prms").unwrap().is_empty());
+    }
+}
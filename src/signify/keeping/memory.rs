@@ -0,0 +1,49 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{error::Result, signify::keeping::Keystore};
+
+/// A `Keystore` held in memory, for tests and ephemeral use.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryKeystore {
+    tables: HashMap<String, BTreeMap<String, String>>,
+}
+
+impl MemoryKeystore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Keystore for MemoryKeystore {
+    fn put(&mut self, table: &str, key: &str, value: &str) -> Result<()> {
+        self.tables
+            .entry(table.to_string())
+            .or_default()
+            .insert(key.to_string(), value.to_string());
+
+        Ok(())
+    }
+
+    fn update(&mut self, puts: &[(&str, &str, &str)], removes: &[(&str, &str)]) -> Result<()> {
+        for (table, key, value) in puts {
+            self.put(table, key, value)?;
+        }
+        for (table, key) in removes {
+            self.remove(table, key)?;
+        }
+
+        Ok(())
+    }
+
+    fn get(&self, table: &str, key: &str) -> Result<Option<String>> {
+        Ok(self.tables.get(table).and_then(|table| table.get(key)).cloned())
+    }
+
+    fn remove(&mut self, table: &str, key: &str) -> Result<bool> {
+        Ok(self.tables.get_mut(table).and_then(|table| table.remove(key)).is_some())
+    }
+
+    fn keys(&self, table: &str) -> Result<Vec<String>> {
+        Ok(self.tables.get(table).map(|table| table.keys().cloned().collect()).unwrap_or_default())
+    }
+}
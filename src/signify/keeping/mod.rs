@@ -0,0 +1,324 @@
+pub mod file;
+pub mod memory;
+
+use crate::{
+    cesr::{
+        core::{
+            cipher::Cipher,
+            decrypter::Decrypter,
+            encrypter::Encrypter,
+            matter::{tables as matter, Matter},
+            verfer::Verfer,
+        },
+        Salter,
+    },
+    data::dat,
+    error::{err, Error, Result},
+    signing::Signer,
+};
+
+const GLOBALS: &str = "gbls"; // keystore parameters by name
+pub(crate) const PRIS: &str = "pris"; // sealed signer seeds by public key
+pub(crate) const SALTS: &str = "salts"; // sealed salts and their tiers by prefix
+
+const AEID: &str = "aeid";
+const SALT: &str = "salt";
+
+/// The minimum length of a passcode, as in KERIpy.
+pub const PASSCODE_SIZE: usize = 21;
+
+/// Storage of string values in named tables, the backend of a `Keeper`. Values are stored as
+/// given, secrets are encrypted by the keeper before they get here.
+pub trait Keystore {
+    /// Stores `value` under `key` in `table`, replacing any value there.
+    fn put(&mut self, table: &str, key: &str, value: &str) -> Result<()>;
+
+    /// Stores each value of `puts`, given with its table and key, in a single step. Either all of
+    /// the values are stored or, on failure, none is.
+    fn put_all(&mut self, puts: &[(&str, &str, &str)]) -> Result<()> {
+        self.update(puts, &[])
+    }
+
+    /// Stores each value of `puts` and removes the value under each key of `removes`, given with
+    /// their tables, in a single step. Either every change is made or, on failure, none is.
+    fn update(&mut self, puts: &[(&str, &str, &str)], removes: &[(&str, &str)]) -> Result<()>;
+
+    fn get(&self, table: &str, key: &str) -> Result<Option<String>>;
+
+    /// Removes the value under `key` in `table`, returning whether there was one.
+    fn remove(&mut self, table: &str, key: &str) -> Result<bool>;
+
+    /// The keys of `table`, sorted.
+    fn keys(&self, table: &str) -> Result<Vec<String>>;
+}
+
+/// A keystore of signer seeds by public key and salts by prefix, sealed at rest to an X25519 key
+/// derived from a passcode. The public key of the passcode, the aeid, is kept with the secrets so
+/// that opening with another passcode fails.
+#[derive(Debug)]
+pub struct Keeper<S: Keystore> {
+    store: S,
+    encrypter: Encrypter,
+    decrypter: Decrypter, // zeroized on drop
+}
+
+/// Derives the encryption keys of `passcode`, returning the aeid with them.
+fn derive(passcode: &str, tier: Option<&str>) -> Result<(String, Encrypter, Decrypter)> {
+    if passcode.len() < PASSCODE_SIZE || !passcode.is_char_boundary(PASSCODE_SIZE) {
+        return err!(Error::Value(format!(
+            "passcode must have at least {PASSCODE_SIZE} characters"
+        )));
+    }
+
+    let qb64 =
+        format!("{code}A{bran}", code = matter::Codex::Salt_128, bran = &passcode[..PASSCODE_SIZE]);
+    let signer = Salter::new_with_qb64(&qb64, tier)?.signer(None, Some(false), None, None, None)?;
+
+    Ok((
+        signer.verfer().qb64()?,
+        Encrypter::new_with_verfer(&signer.verfer())?,
+        Decrypter::new_with_signer(&signer)?,
+    ))
+}
+
+/// Seals the salt of `salter` to `encrypter`, with its tier.
+fn seal_salter(encrypter: &Encrypter, salter: &Salter) -> Result<String> {
+    let cipher = encrypter.encrypt_salter(salter)?;
+    dat!({"salt": cipher.qb64()?.as_str(), "tier": salter.tier().as_str()}).to_json()
+}
+
+impl<S: Keystore> Keeper<S> {
+    /// Opens `store` with `passcode`, stretched at `tier`. A new store takes the aeid of the
+    /// passcode, an existing one must have been created with the same passcode and tier.
+    pub fn new(mut store: S, passcode: &str, tier: Option<&str>) -> Result<Self> {
+        let (aeid, encrypter, decrypter) = derive(passcode, tier)?;
+
+        match store.get(GLOBALS, AEID)? {
+            Some(existing) if existing != aeid => {
+                return err!(Error::Validation("invalid passcode for keystore".to_string()))
+            }
+            Some(_) => {}
+            None => store.put(GLOBALS, AEID, &aeid)?,
+        }
+
+        Ok(Self { store, encrypter, decrypter })
+    }
+
+    /// The public key of the passcode that secrets are sealed to.
+    pub fn aeid(&self) -> Result<String> {
+        match self.store.get(GLOBALS, AEID)? {
+            Some(aeid) => Ok(aeid),
+            None => err!(Error::Value("keystore without aeid".to_string())),
+        }
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    pub fn store_mut(&mut self) -> &mut S {
+        &mut self.store
+    }
+
+    /// Stores the seed of `signer` under its public key.
+    pub fn put_signer(&mut self, signer: &Signer) -> Result<()> {
+        let cipher = self.encrypter.encrypt_signer(signer)?;
+        self.store.put(PRIS, &signer.verfer().qb64()?, &cipher.qb64()?)
+    }
+
+    /// Stores the seeds of `signers` under their public keys, in a single step.
+    pub fn put_signers<'a>(&mut self, signers: impl IntoIterator<Item = &'a Signer>) -> Result<()> {
+        let mut values = vec![];
+        for signer in signers {
+            values.push((signer.verfer().qb64()?, self.seal_signer(signer)?));
+        }
+
+        let puts: Vec<(&str, &str, &str)> = values
+            .iter()
+            .map(|(pubkey, cipher)| (PRIS, pubkey.as_str(), cipher.as_str()))
+            .collect();
+        self.store.put_all(&puts)
+    }
+
+    /// The seed of `signer` sealed as `put_signer` stores it under its public key in `PRIS`, for
+    /// callers that store it along with other changes.
+    pub(crate) fn seal_signer(&self, signer: &Signer) -> Result<String> {
+        self.encrypter.encrypt_signer(signer)?.qb64()
+    }
+
+    /// The salt of `salter` sealed with its tier as `put_salter` stores it in `SALTS`.
+    pub(crate) fn seal_salter(&self, salter: &Salter) -> Result<String> {
+        seal_salter(&self.encrypter, salter)
+    }
+
+    /// The signer of public key `pubkey`, transferable when the key is.
+    pub fn signer(&self, pubkey: &str) -> Result<Option<Signer>> {
+        let cipher = match self.store.get(PRIS, pubkey)? {
+            Some(cipher) => Cipher::new_with_qb64(&cipher)?,
+            None => return Ok(None),
+        };
+
+        let transferable = Verfer::new_with_qb64(pubkey)?.transferable();
+        let signer = self.decrypter.decrypt_signer(&cipher, Some(transferable))?;
+        if signer.verfer().qb64()? != pubkey {
+            return err!(Error::Validation(format!("stored seed does not match key {pubkey}")));
+        }
+
+        Ok(Some(signer))
+    }
+
+    pub fn remove_signer(&mut self, pubkey: &str) -> Result<bool> {
+        self.store.remove(PRIS, pubkey)
+    }
+
+    /// The public keys with stored seeds, sorted.
+    pub fn pubkeys(&self) -> Result<Vec<String>> {
+        self.store.keys(PRIS)
+    }
+
+    /// Stores the salt of `salter`, with its tier, under prefix `pre`.
+    pub fn put_salter(&mut self, pre: &str, salter: &Salter) -> Result<()> {
+        let value = seal_salter(&self.encrypter, salter)?;
+        self.store.put(SALTS, pre, &value)
+    }
+
+    pub fn salter(&self, pre: &str) -> Result<Option<Salter>> {
+        self.store.get(SALTS, pre)?.map(|value| self.open_salter(&value)).transpose()
+    }
+
+    pub fn remove_salter(&mut self, pre: &str) -> Result<bool> {
+        self.store.remove(SALTS, pre)
+    }
+
+    /// The prefixes with stored salts, sorted.
+    pub fn prefixes(&self) -> Result<Vec<String>> {
+        self.store.keys(SALTS)
+    }
+
+    /// Stores the root salt of the keystore, which identifiers derive their keys from by default.
+    pub fn put_root_salter(&mut self, salter: &Salter) -> Result<()> {
+        let value = seal_salter(&self.encrypter, salter)?;
+        self.store.put(GLOBALS, SALT, &value)
+    }
+
+    pub fn root_salter(&self) -> Result<Option<Salter>> {
+        self.store.get(GLOBALS, SALT)?.map(|value| self.open_salter(&value)).transpose()
+    }
+
+    fn open_salter(&self, value: &str) -> Result<Salter> {
+        let value: serde_json::Value = serde_json::from_str(value)?;
+        match (value["salt"].as_str(), value["tier"].as_str()) {
+            (Some(salt), Some(tier)) => {
+                self.decrypter.decrypt_salter(&Cipher::new_with_qb64(salt)?, Some(tier))
+            }
+            _ => err!(Error::Value(format!("invalid salt entry {value}"))),
+        }
+    }
+
+    /// Seals every stored secret to the key of a new `passcode`, stretched at `tier`, and makes
+    /// its aeid the aeid of the store. Each secret is opened and checked first, then the resealed
+    /// secrets and the new aeid are stored in a single step, so that the store is sealed either
+    /// entirely to the old passcode or entirely to the new one.
+    pub fn update_passcode(&mut self, passcode: &str, tier: Option<&str>) -> Result<()> {
+        let (aeid, encrypter, decrypter) = derive(passcode, tier)?;
+
+        let mut values = vec![];
+        for pubkey in self.pubkeys()? {
+            let signer = match self.signer(&pubkey)? {
+                Some(signer) => signer,
+                None => return err!(Error::Value(format!("missing seed of {pubkey}"))),
+            };
+            values.push((PRIS, pubkey, encrypter.encrypt_signer(&signer)?.qb64()?));
+        }
+        for pre in self.prefixes()? {
+            let salter = match self.salter(&pre)? {
+                Some(salter) => salter,
+                None => return err!(Error::Value(format!("missing salt of {pre}"))),
+            };
+            values.push((SALTS, pre, seal_salter(&encrypter, &salter)?));
+        }
+        if let Some(root) = self.root_salter()? {
+            values.push((GLOBALS, SALT.to_string(), seal_salter(&encrypter, &root)?));
+        }
+        values.push((GLOBALS, AEID.to_string(), aeid));
+
+        let puts: Vec<(&str, &str, &str)> = values
+            .iter()
+            .map(|(table, key, value)| (*table, key.as_str(), value.as_str()))
+            .collect();
+        self.store.put_all(&puts)?;
+
+        self.encrypter = encrypter;
+        self.decrypter = decrypter;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        cesr::{
+            common::Tierage,
+            core::matter::{tables as matter, Matter},
+            Salter,
+        },
+        signify::keeping::{memory::MemoryKeystore, Keeper, Keystore},
+        signing::Signer,
+    };
+
+    const PASSCODE: &str = "0123456789abcdefghijk";
+
+    #[test]
+    fn keeper() {
+        let mut keeper = Keeper::new(MemoryKeystore::new(), PASSCODE, Some(Tierage::min)).unwrap();
+        let aeid = keeper.aeid().unwrap();
+        assert_eq!(&aeid[..1], matter::Codex::Ed25519N);
+
+        let signer = Signer::new_with_defaults(None, None).unwrap();
+        let witness = Signer::new_with_defaults(Some(false), None).unwrap();
+        let pubkey = signer.verfer().qb64().unwrap();
+        keeper.put_signer(&signer).unwrap();
+        keeper.put_signer(&witness).unwrap();
+
+        // seeds are sealed at rest
+        let stored = keeper.store().get("pris", &pubkey).unwrap().unwrap();
+        assert_eq!(stored.len(), 124);
+        assert!(!stored.contains(&signer.qb64().unwrap()));
+
+        assert_eq!(keeper.signer(&pubkey).unwrap(), Some(signer.clone()));
+        let opened = keeper.signer(&witness.verfer().qb64().unwrap()).unwrap().unwrap();
+        assert_eq!(opened.verfer().code(), matter::Codex::Ed25519N);
+        assert_eq!(keeper.pubkeys().unwrap().len(), 2);
+        assert_eq!(keeper.signer("DUnknown").unwrap(), None);
+
+        let salter = Salter::new_with_defaults(Some(Tierage::min)).unwrap();
+        keeper.put_salter("Epre", &salter).unwrap();
+        assert_eq!(keeper.salter("Epre").unwrap(), Some(salter.clone()));
+        assert_eq!(keeper.prefixes().unwrap(), ["Epre"]);
+        assert_eq!(keeper.root_salter().unwrap(), None);
+        keeper.put_root_salter(&salter).unwrap();
+
+        // another passcode cannot open the store
+        let store = std::mem::take(keeper.store_mut());
+        assert!(Keeper::new(store.clone(), "abcdefghijk0123456789", Some(Tierage::min)).is_err());
+        assert!(Keeper::new(store.clone(), "short", Some(Tierage::min)).is_err());
+        let mut keeper = Keeper::new(store, PASSCODE, Some(Tierage::min)).unwrap();
+
+        // changing the passcode seals every secret again
+        keeper.update_passcode("abcdefghijk0123456789", Some(Tierage::min)).unwrap();
+        assert_ne!(keeper.aeid().unwrap(), aeid);
+        assert_eq!(keeper.signer(&pubkey).unwrap(), Some(signer));
+        assert_eq!(keeper.salter("Epre").unwrap(), Some(salter.clone()));
+        assert_eq!(keeper.root_salter().unwrap(), Some(salter));
+
+        let store = std::mem::take(keeper.store_mut());
+        assert!(Keeper::new(store.clone(), PASSCODE, Some(Tierage::min)).is_err());
+        let mut keeper = Keeper::new(store, "abcdefghijk0123456789", Some(Tierage::min)).unwrap();
+        assert!(keeper.remove_signer(&pubkey).unwrap());
+        assert!(!keeper.remove_signer(&pubkey).unwrap());
+        assert!(keeper.remove_salter("Epre").unwrap());
+        assert_eq!(keeper.signer(&pubkey).unwrap(), None);
+        assert_eq!(keeper.salter("Epre").unwrap(), None);
+    }
+}
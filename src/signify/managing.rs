@@ -0,0 +1,932 @@
+use crate::{
+    cesr::{
+        core::{
+            cigar::Cigar,
+            common::Tierage,
+            dater::Dater,
+            diger::Diger,
+            matter::{tables as matter, Matter},
+            siger::Siger,
+            verfer::Verfer,
+        },
+        Salter,
+    },
+    data::{dat, Value},
+    error::{err, Error, Result},
+    signify::{
+        creating::{Algos, Creator, Creatory, SaltyCreator},
+        keeping::{Keeper, Keystore, PRIS, SALTS},
+    },
+    signing::Signer,
+};
+
+const GLOBALS: &str = "gbls"; // manager parameters by name
+const PRMS: &str = "prms"; // key creation parameters by prefix
+const SITS: &str = "sits"; // key situations by prefix
+
+const PIDX: &str = "pidx";
+const ALGO: &str = "algo";
+const TIER: &str = "tier";
+
+/// How the keys of an identifier are created. Salts are kept sealed in the keeper.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrePrm {
+    pub pidx: u16,    // prefix index of the identifier among those of the manager
+    pub algo: String, // key creation algorithm
+    pub stem: String, // path stem of salty keys, empty for the hex prefix index
+    pub tier: String, // security tier of salty keys
+}
+
+/// A set of public keys, created together unless some were held in reserve by a partial
+/// rotation.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PubLot {
+    pub pubs: Vec<String>,         // public keys, qb64
+    pub ridx: u16,                 // rotation index of the set
+    pub kidx: u16,                 // key index of the first key created for the set
+    pub ondices: Vec<Option<u32>>, // prior next index of each key, empty when not rotated to
+    pub dt: String,                // datetime of creation
+}
+
+/// The key sets of an identifier: the prior and current signing keys and the pre-rotated next
+/// keys whose digests are committed to.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PreSit {
+    pub old: PubLot,
+    pub new: PubLot,
+    pub nxt: PubLot,
+}
+
+fn index(value: &Value) -> Result<u16> {
+    Ok(u16::try_from(value.to_i64()?)?)
+}
+
+impl PrePrm {
+    pub fn from_value(prm: &Value) -> Result<Self> {
+        Ok(Self {
+            pidx: index(&prm["pidx"])?,
+            algo: prm["algo"].to_string()?,
+            stem: prm["stem"].to_string()?,
+            tier: prm["tier"].to_string()?,
+        })
+    }
+
+    pub fn to_value(&self) -> Value {
+        dat!({
+            "pidx": self.pidx as i64,
+            "algo": &self.algo,
+            "stem": &self.stem,
+            "tier": &self.tier
+        })
+    }
+}
+
+impl PubLot {
+    fn new(signers: &[Signer], ridx: u16, kidx: u16, dt: &str) -> Result<Self> {
+        Ok(Self {
+            pubs: signers.iter().map(|signer| signer.verfer().qb64()).collect::<Result<_>>()?,
+            ridx,
+            kidx,
+            ondices: vec![],
+            dt: dt.to_string(),
+        })
+    }
+
+    pub fn from_value(lot: &Value) -> Result<Self> {
+        Ok(Self {
+            pubs: lot["pubs"]
+                .to_vec()?
+                .iter()
+                .map(|pub_| pub_.to_string())
+                .collect::<Result<_>>()?,
+            ridx: index(&lot["ridx"])?,
+            kidx: index(&lot["kidx"])?,
+            ondices: lot["ondices"]
+                .to_vec()?
+                .iter()
+                .map(|ondex| match ondex {
+                    Value::Null => Ok(None),
+                    ondex => Ok(Some(u32::try_from(ondex.to_i64()?)?)),
+                })
+                .collect::<Result<_>>()?,
+            dt: lot["dt"].to_string()?,
+        })
+    }
+
+    pub fn to_value(&self) -> Value {
+        let pubs: Vec<Value> = self.pubs.iter().map(|pub_| dat!(pub_.as_str())).collect();
+        let ondices: Vec<Value> = self
+            .ondices
+            .iter()
+            .map(|ondex| match ondex {
+                Some(ondex) => dat!(*ondex as i64),
+                None => dat!(null),
+            })
+            .collect();
+        dat!({
+            "pubs": pubs.as_slice(),
+            "ridx": self.ridx as i64,
+            "kidx": self.kidx as i64,
+            "ondices": ondices.as_slice(),
+            "dt": &self.dt
+        })
+    }
+}
+
+impl PreSit {
+    /// The prior next index of each signing key, `None` for keys that were not committed to.
+    /// Keys that were not rotated to, as at inception, appear at the same index in both lists.
+    pub fn ondices(&self) -> Vec<Option<u32>> {
+        if self.new.ondices.is_empty() {
+            (0..self.new.pubs.len() as u32).map(Some).collect()
+        } else {
+            self.new.ondices.clone()
+        }
+    }
+
+    pub fn from_value(sit: &Value) -> Result<Self> {
+        Ok(Self {
+            old: PubLot::from_value(&sit["old"])?,
+            new: PubLot::from_value(&sit["new"])?,
+            nxt: PubLot::from_value(&sit["nxt"])?,
+        })
+    }
+
+    pub fn to_value(&self) -> Value {
+        dat!({
+            "old": self.old.to_value(),
+            "new": self.new.to_value(),
+            "nxt": self.nxt.to_value()
+        })
+    }
+}
+
+fn json(value: &str) -> Result<Value> {
+    Ok(Value::from(&serde_json::from_str::<serde_json::Value>(value)?))
+}
+
+/// Creates and stores the keys of identifiers, keeping track of the prefix, rotation and key
+/// indices from which salty keys are derived. Identifiers are recorded under the public key of
+/// their first signing key until moved to their prefix.
+#[derive(Debug)]
+pub struct Manager<S: Keystore> {
+    keeper: Keeper<S>,
+}
+
+impl<S: Keystore> Manager<S> {
+    /// Manages the keys in `keeper`. The root `salt`, default algorithm and default tier are set
+    /// when the keeper has none yet, a random root salt being created without `salt`.
+    pub fn new(
+        mut keeper: Keeper<S>,
+        salt: Option<&str>,
+        algo: Option<&str>,
+        tier: Option<&str>,
+    ) -> Result<Self> {
+        let store = keeper.store_mut();
+        if store.get(GLOBALS, ALGO)?.is_none() {
+            store.put(GLOBALS, ALGO, algo.unwrap_or(Algos::salty))?;
+        }
+        if store.get(GLOBALS, TIER)?.is_none() {
+            store.put(GLOBALS, TIER, tier.unwrap_or(Tierage::low))?;
+        }
+        if store.get(GLOBALS, PIDX)?.is_none() {
+            store.put(GLOBALS, PIDX, "0")?;
+        }
+
+        if keeper.root_salter()?.is_none() {
+            let tier = keeper.store().get(GLOBALS, TIER)?;
+            let salter = Salter::new(tier.as_deref(), None, None, None, salt, None)?;
+            keeper.put_root_salter(&salter)?;
+        }
+
+        Ok(Self { keeper })
+    }
+
+    pub fn keeper(&self) -> &Keeper<S> {
+        &self.keeper
+    }
+
+    pub fn keeper_mut(&mut self) -> &mut Keeper<S> {
+        &mut self.keeper
+    }
+
+    fn global(&self, name: &str) -> Result<String> {
+        match self.keeper.store().get(GLOBALS, name)? {
+            Some(value) => Ok(value),
+            None => err!(Error::Value(format!("missing manager parameter {name}"))),
+        }
+    }
+
+    /// The prefix index of the next identifier incepted.
+    pub fn pidx(&self) -> Result<u16> {
+        Ok(self.global(PIDX)?.parse()?)
+    }
+
+    pub fn algo(&self) -> Result<String> {
+        self.global(ALGO)
+    }
+
+    pub fn tier(&self) -> Result<String> {
+        self.global(TIER)
+    }
+
+    pub fn prm(&self, pre: &str) -> Result<Option<PrePrm>> {
+        self.keeper.store().get(PRMS, pre)?.map(|prm| PrePrm::from_value(&json(&prm)?)).transpose()
+    }
+
+    pub fn sit(&self, pre: &str) -> Result<Option<PreSit>> {
+        self.keeper.store().get(SITS, pre)?.map(|sit| PreSit::from_value(&json(&sit)?)).transpose()
+    }
+
+    /// Creates signers for `codes` at the given indices, by the parameters of an identifier.
+    #[allow(clippy::too_many_arguments)]
+    fn create(
+        &self,
+        prm: &PrePrm,
+        salter: Option<Salter>,
+        codes: &[&str],
+        ridx: u16,
+        kidx: u16,
+        transferable: bool,
+        temp: bool,
+    ) -> Result<Vec<Signer>> {
+        if codes.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let creator: Box<dyn Creator> = match (prm.algo.as_str(), salter) {
+            (Algos::salty, Some(salter)) => {
+                let stem = (!prm.stem.is_empty()).then_some(prm.stem.as_str());
+                Box::new(SaltyCreator::new(None, stem, Some(&prm.tier), Some(salter))?)
+            }
+            (Algos::salty, None) => {
+                return err!(Error::Value("missing salt of salty keys".to_string()))
+            }
+            (algo, _) => Creatory::new(Some(algo))?.make(None, None, None, None)?,
+        };
+
+        creator.create(
+            Some(codes.to_vec()),
+            None,
+            None,
+            Some(prm.pidx),
+            Some(ridx),
+            Some(kidx),
+            None,
+            Some(transferable),
+            temp,
+        )
+    }
+
+    fn put_sit(&mut self, pre: &str, sit: &PreSit) -> Result<()> {
+        self.keeper.store_mut().put(SITS, pre, &sit.to_value().to_json()?)
+    }
+
+    fn digers(pubs: &[String], dcode: &str) -> Result<Vec<Diger>> {
+        pubs.iter().map(|pub_| Diger::new_with_ser(pub_.as_bytes(), Some(dcode))).collect()
+    }
+
+    /// Creates the signing keys of a new identifier, `icount` keys of `icode` unless `icodes` are
+    /// given, and its next keys likewise, returning the signing keys and the digests of the next
+    /// keys. Salty keys derive from `salt`, or the root salt, along a path of `stem`, or the hex
+    /// prefix index, then the hex rotation and key indices. The identifier is recorded under its
+    /// first public key.
+    #[allow(clippy::too_many_arguments)]
+    pub fn incept(
+        &mut self,
+        icodes: Option<&[&str]>,
+        icount: Option<usize>,
+        icode: Option<&str>,
+        ncodes: Option<&[&str]>,
+        ncount: Option<usize>,
+        ncode: Option<&str>,
+        dcode: Option<&str>,
+        algo: Option<&str>,
+        salt: Option<&str>,
+        stem: Option<&str>,
+        tier: Option<&str>,
+        transferable: Option<bool>,
+        temp: Option<bool>,
+    ) -> Result<(Vec<Verfer>, Vec<Diger>)> {
+        let icodes = match icodes {
+            Some(icodes) => icodes.to_vec(),
+            None => vec![icode.unwrap_or(matter::Codex::Ed25519_Seed); icount.unwrap_or(1)],
+        };
+        let ncodes = match ncodes {
+            Some(ncodes) => ncodes.to_vec(),
+            None => vec![ncode.unwrap_or(matter::Codex::Ed25519_Seed); ncount.unwrap_or(1)],
+        };
+        if icodes.is_empty() {
+            return err!(Error::Value("inception without signing keys".to_string()));
+        }
+        let dcode = dcode.unwrap_or(matter::Codex::Blake3_256);
+        let transferable = transferable.unwrap_or(true);
+        let temp = temp.unwrap_or(false);
+
+        let pidx = self.pidx()?;
+        let next = match pidx.checked_add(1) {
+            Some(next) => next.to_string(),
+            None => return err!(Error::Value("prefix indices exhausted".to_string())),
+        };
+        let prm = PrePrm {
+            pidx,
+            algo: algo.map(|algo| algo.to_string()).unwrap_or(self.algo()?),
+            stem: stem.unwrap_or("").to_string(),
+            tier: tier.map(|tier| tier.to_string()).unwrap_or(self.tier()?),
+        };
+        let salter = match (prm.algo.as_str(), salt) {
+            (Algos::salty, Some(salt)) => Some(Salter::new_with_qb64(salt, Some(&prm.tier))?),
+            (Algos::salty, None) => self.keeper.root_salter()?,
+            _ => None,
+        };
+
+        let kidx = u16::try_from(icodes.len())?;
+        let isigners = self.create(&prm, salter.clone(), &icodes, 0, 0, transferable, temp)?;
+        let nsigners = self.create(&prm, salter.clone(), &ncodes, 1, kidx, transferable, temp)?;
+
+        let pre = isigners[0].verfer().qb64()?;
+        if self.prm(&pre)?.is_some() {
+            return err!(Error::Value(format!("keys of {pre} already incepted")));
+        }
+
+        let dt = Dater::new(None, None, None, None, None, None)?.dts()?;
+        let sit = PreSit {
+            old: PubLot::default(),
+            new: PubLot::new(&isigners, 0, 0, &dt)?,
+            nxt: PubLot::new(&nsigners, 1, kidx, &dt)?,
+        };
+
+        let mut seeds = vec![];
+        for signer in isigners.iter().chain(nsigners.iter()) {
+            seeds.push((signer.verfer().qb64()?, self.keeper.seal_signer(signer)?));
+        }
+        let salt = salter.as_ref().map(|salter| self.keeper.seal_salter(salter)).transpose()?;
+        let prm = prm.to_value().to_json()?;
+        let sit_json = sit.to_value().to_json()?;
+
+        let mut puts: Vec<(&str, &str, &str)> =
+            seeds.iter().map(|(pubkey, seed)| (PRIS, pubkey.as_str(), seed.as_str())).collect();
+        if let Some(salt) = &salt {
+            puts.push((SALTS, &pre, salt));
+        }
+        puts.extend([
+            (PRMS, pre.as_str(), prm.as_str()),
+            (SITS, &pre, &sit_json),
+            (GLOBALS, PIDX, &next),
+        ]);
+        self.keeper.store_mut().put_all(&puts)?;
+
+        Ok((
+            isigners.iter().map(|signer| signer.verfer()).collect(),
+            Self::digers(&sit.nxt.pubs, dcode)?,
+        ))
+    }
+
+    /// Records the keys of identifier `old`, the first public key at inception, under its prefix
+    /// `new`.
+    pub fn move_prefix(&mut self, old: &str, new: &str) -> Result<()> {
+        if old == new {
+            return Ok(());
+        }
+        if self.prm(new)?.is_some() {
+            return err!(Error::Value(format!("keys of {new} already recorded")));
+        }
+
+        let (prm, sit) = match (self.prm(old)?, self.sit(old)?) {
+            (Some(prm), Some(sit)) => (prm, sit),
+            _ => return err!(Error::Value(format!("no keys recorded for {old}"))),
+        };
+        let salt = self.keeper.store().get(SALTS, old)?;
+        let prm = prm.to_value().to_json()?;
+        let sit = sit.to_value().to_json()?;
+
+        let mut puts = vec![(PRMS, new, prm.as_str()), (SITS, new, sit.as_str())];
+        if let Some(salt) = &salt {
+            puts.push((SALTS, new, salt));
+        }
+        let removes = [(SITS, old), (PRMS, old), (SALTS, old)];
+
+        self.keeper.store_mut().update(&puts, &removes)
+    }
+
+    /// Undoes the inception of `pre`, recorded under its prefix or first public key, removing its
+    /// seeds and records and giving back its prefix index unless another identifier took the
+    /// next one since.
+    pub fn unincept(&mut self, pre: &str) -> Result<()> {
+        let (prm, sit) = match (self.prm(pre)?, self.sit(pre)?) {
+            (Some(prm), Some(sit)) => (prm, sit),
+            _ => return err!(Error::Value(format!("no keys recorded for {pre}"))),
+        };
+
+        let pidx = prm.pidx.to_string();
+        let mut puts = vec![];
+        if prm.pidx.checked_add(1) == Some(self.pidx()?) {
+            puts.push((GLOBALS, PIDX, pidx.as_str()));
+        }
+        let mut removes = vec![(SITS, pre), (PRMS, pre), (SALTS, pre)];
+        for pub_ in sit.old.pubs.iter().chain(&sit.new.pubs).chain(&sit.nxt.pubs) {
+            removes.push((PRIS, pub_));
+        }
+
+        self.keeper.store_mut().update(&puts, &removes)
+    }
+
+    /// Rotates the keys of `pre`: the next keys become the signing keys and new next keys are
+    /// created, `ncount` keys of `ncode` unless `ncodes` are given. Returns the signing keys and
+    /// the digests of the new next keys. With `erase`, the seeds of the prior signing keys are
+    /// removed.
+    #[allow(clippy::too_many_arguments)]
+    pub fn rotate(
+        &mut self,
+        pre: &str,
+        ncodes: Option<&[&str]>,
+        ncount: Option<usize>,
+        ncode: Option<&str>,
+        dcode: Option<&str>,
+        transferable: Option<bool>,
+        temp: Option<bool>,
+        erase: Option<bool>,
+    ) -> Result<(Vec<Verfer>, Vec<Diger>)> {
+        self.rotate_partial(
+            pre,
+            None,
+            None,
+            ncodes,
+            ncount,
+            ncode,
+            dcode,
+            transferable,
+            temp,
+            erase,
+        )
+    }
+
+    /// Rotates the keys of `pre` to the next keys at `exposed` indices, all of them by default,
+    /// followed by new keys for `acodes` that were never committed to. The next keys not exposed
+    /// are held in reserve: their digests lead the digests of the new next keys, created as by
+    /// `rotate`. The prior next index of each signing key is kept for signing, see
+    /// `PreSit::ondices`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn rotate_partial(
+        &mut self,
+        pre: &str,
+        exposed: Option<&[u32]>,
+        acodes: Option<&[&str]>,
+        ncodes: Option<&[&str]>,
+        ncount: Option<usize>,
+        ncode: Option<&str>,
+        dcode: Option<&str>,
+        transferable: Option<bool>,
+        temp: Option<bool>,
+        erase: Option<bool>,
+    ) -> Result<(Vec<Verfer>, Vec<Diger>)> {
+        let acodes = acodes.unwrap_or_default();
+        let ncodes = match ncodes {
+            Some(ncodes) => ncodes.to_vec(),
+            None => vec![ncode.unwrap_or(matter::Codex::Ed25519_Seed); ncount.unwrap_or(1)],
+        };
+        let dcode = dcode.unwrap_or(matter::Codex::Blake3_256);
+        let transferable = transferable.unwrap_or(true);
+        let temp = temp.unwrap_or(false);
+        let erase = erase.unwrap_or(true);
+
+        let (prm, mut sit) = match (self.prm(pre)?, self.sit(pre)?) {
+            (Some(prm), Some(sit)) => (prm, sit),
+            _ => return err!(Error::Value(format!("no keys recorded for {pre}"))),
+        };
+        if sit.nxt.pubs.is_empty() {
+            return err!(Error::Value(format!("keys of {pre} are not rotatable")));
+        }
+
+        let exposed = match exposed {
+            Some(exposed) => exposed.to_vec(),
+            None => (0..sit.nxt.pubs.len() as u32).collect(),
+        };
+        if exposed.is_empty() {
+            return err!(Error::Value(format!("rotation of {pre} exposes no next keys")));
+        }
+        for (i, ondex) in exposed.iter().enumerate() {
+            if *ondex as usize >= sit.nxt.pubs.len() || exposed[..i].contains(ondex) {
+                return err!(Error::Value(format!("invalid exposed next key index = {ondex}")));
+            }
+        }
+
+        let ridx = sit.nxt.ridx.checked_add(1);
+        let akidx =
+            u16::try_from(sit.nxt.pubs.len()).ok().and_then(|len| sit.nxt.kidx.checked_add(len));
+        let nkidx = akidx.and_then(|kidx| kidx.checked_add(u16::try_from(acodes.len()).ok()?));
+        let (ridx, akidx, nkidx) = match (ridx, akidx, nkidx) {
+            (Some(ridx), Some(akidx), Some(nkidx)) => (ridx, akidx, nkidx),
+            _ => return err!(Error::Value(format!("key indices of {pre} exhausted"))),
+        };
+
+        let pubs: Vec<String> =
+            exposed.iter().map(|ondex| sit.nxt.pubs[*ondex as usize].clone()).collect();
+        let refs: Vec<&str> = pubs.iter().map(|pub_| pub_.as_str()).collect();
+        let mut verfers: Vec<Verfer> =
+            self.signers(&refs)?.iter().map(|signer| signer.verfer()).collect();
+
+        let salter = self.keeper.salter(pre)?;
+        let asigners =
+            self.create(&prm, salter.clone(), acodes, sit.nxt.ridx, akidx, transferable, temp)?;
+        let nsigners = self.create(&prm, salter, &ncodes, ridx, nkidx, transferable, temp)?;
+        let dt = Dater::new(None, None, None, None, None, None)?.dts()?;
+
+        let reserves: Vec<String> = (0..sit.nxt.pubs.len() as u32)
+            .filter(|ondex| !exposed.contains(ondex))
+            .map(|ondex| sit.nxt.pubs[ondex as usize].clone())
+            .collect();
+        let mut nxt = PubLot::new(&nsigners, ridx, nkidx, &dt)?;
+        nxt.pubs = [reserves, nxt.pubs].concat();
+
+        let old = std::mem::take(&mut sit.old);
+        sit.old = std::mem::take(&mut sit.new);
+        sit.new = std::mem::replace(&mut sit.nxt, nxt);
+        sit.new.pubs = pubs;
+        sit.new.ondices = exposed.iter().map(|ondex| Some(*ondex)).collect();
+        for signer in &asigners {
+            sit.new.pubs.push(signer.verfer().qb64()?);
+            sit.new.ondices.push(None);
+            verfers.push(signer.verfer());
+        }
+
+        self.keeper.put_signers(asigners.iter().chain(nsigners.iter()))?;
+        self.put_sit(pre, &sit)?;
+        if erase {
+            for pub_ in &old.pubs {
+                self.keeper.remove_signer(pub_)?;
+            }
+        }
+
+        Ok((verfers, Self::digers(&sit.nxt.pubs, dcode)?))
+    }
+
+    /// Undoes the latest rotation of `pre`, made without `erase`, putting back `prior`, its key
+    /// situation before the rotation, and removing the seeds the rotation created.
+    pub fn unrotate(&mut self, pre: &str, prior: &PreSit) -> Result<()> {
+        let sit = match self.sit(pre)? {
+            Some(sit) => sit,
+            None => return err!(Error::Value(format!("no keys recorded for {pre}"))),
+        };
+
+        self.put_sit(pre, prior)?;
+        let kept: Vec<&String> =
+            prior.old.pubs.iter().chain(&prior.new.pubs).chain(&prior.nxt.pubs).collect();
+        for pub_ in sit.new.pubs.iter().chain(&sit.nxt.pubs) {
+            if !kept.contains(&pub_) {
+                self.keeper.remove_signer(pub_)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn signers(&self, pubs: &[&str]) -> Result<Vec<Signer>> {
+        pubs.iter()
+            .map(|pub_| match self.keeper.signer(pub_)? {
+                Some(signer) => Ok(signer),
+                None => err!(Error::Value(format!("missing seed of {pub_}"))),
+            })
+            .collect()
+    }
+
+    /// Signs `ser` with the keys of `pubs`, indexing each signature by its position in `pubs`
+    /// unless `indices` are given. With `ondices`, a signature also carries the index of its key
+    /// among the prior next keys, or is current only when that index is `None`.
+    pub fn sign(
+        &self,
+        ser: &[u8],
+        pubs: &[&str],
+        indices: Option<&[u32]>,
+        ondices: Option<&[Option<u32>]>,
+    ) -> Result<Vec<Siger>> {
+        if indices.is_some_and(|indices| indices.len() != pubs.len()) {
+            return err!(Error::Value("mismatched signature indices".to_string()));
+        }
+        if ondices.is_some_and(|ondices| ondices.len() != pubs.len()) {
+            return err!(Error::Value("mismatched signature ondices".to_string()));
+        }
+
+        let mut sigers = vec![];
+        for (j, signer) in self.signers(pubs)?.iter().enumerate() {
+            let index = indices.map(|indices| indices[j]).unwrap_or(j as u32);
+            let (only, ondex) = match ondices {
+                Some(ondices) => (ondices[j].is_none(), ondices[j]),
+                None => (false, Some(index)),
+            };
+            sigers.push(signer.sign_indexed(ser, only, index, ondex)?);
+        }
+
+        Ok(sigers)
+    }
+
+    /// Signs `ser` with the keys of `pubs`, without indices.
+    pub fn sign_unindexed(&self, ser: &[u8], pubs: &[&str]) -> Result<Vec<Cigar>> {
+        self.signers(pubs)?.iter().map(|signer| signer.sign_unindexed(ser)).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        cesr::{
+            common::Tierage,
+            core::{
+                diger::Diger,
+                indexer::{tables as indexer, Indexer},
+                matter::{tables as matter, Matter},
+                sadder::Sadder,
+                verfer::Verfer,
+            },
+            Salter,
+        },
+        data::dat,
+        eventing::{
+            event::{incept, rotate},
+            kever::Kever,
+        },
+        signify::{
+            creating::Algos,
+            keeping::{memory::MemoryKeystore, Keeper, Keystore},
+            managing::Manager,
+        },
+    };
+
+    fn qb64s<M: Matter>(matters: &[M]) -> Vec<String> {
+        matters.iter().map(|matter| matter.qb64().unwrap()).collect()
+    }
+
+    #[test]
+    fn manager() {
+        let salter = Salter::new_with_raw(b"0123456789abcdef", None, Some(Tierage::min)).unwrap();
+        let salt = salter.qb64().unwrap();
+        let keeper =
+            Keeper::new(MemoryKeystore::new(), "0123456789abcdefghijk", Some(Tierage::min))
+                .unwrap();
+        let mut manager = Manager::new(keeper, Some(&salt), None, Some(Tierage::min)).unwrap();
+        assert_eq!(manager.pidx().unwrap(), 0);
+        assert_eq!(manager.algo().unwrap(), Algos::salty);
+
+        let path = |path: &str| {
+            salter.signer(None, None, Some(path), Some(Tierage::min), None).unwrap().verfer()
+        };
+        let digest = |verfer: &Verfer| Diger::new_with_ser(&verfer.qb64b().unwrap(), None).unwrap();
+
+        // keys derive from the root salt along the prefix, rotation and key indices
+        let (verfers, digers) = manager
+            .incept(
+                None,
+                Some(2),
+                None,
+                None,
+                Some(2),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(verfers, [path("000"), path("001")]);
+        assert_eq!(digers, [digest(&path("012")), digest(&path("013"))]);
+        assert_eq!(manager.pidx().unwrap(), 1);
+
+        let pre = verfers[0].qb64().unwrap();
+        let sit = manager.sit(&pre).unwrap().unwrap();
+        assert_eq!((sit.new.ridx, sit.new.kidx, sit.nxt.ridx, sit.nxt.kidx), (0, 0, 1, 2));
+        let prm = manager.prm(&pre).unwrap().unwrap();
+        assert_eq!((prm.pidx, prm.algo.as_str(), prm.stem.as_str()), (0, Algos::salty, ""));
+
+        manager.move_prefix(&pre, "Epre").unwrap();
+        assert_eq!(manager.sit(&pre).unwrap(), None);
+        assert_eq!(manager.prm(&pre).unwrap(), None);
+        assert_eq!(manager.keeper().salter(&pre).unwrap(), None);
+        assert_eq!(manager.sit("Epre").unwrap(), Some(sit.clone()));
+        assert_eq!(manager.keeper().salter("Epre").unwrap(), Some(salter.clone()));
+
+        // rotation promotes the next keys and pre-rotates fresh ones
+        let (verfers, digers) =
+            manager.rotate("Epre", None, Some(3), None, None, None, None, None).unwrap();
+        assert_eq!(verfers, [path("012"), path("013")]);
+        assert_eq!(digers, [digest(&path("024")), digest(&path("025")), digest(&path("026"))]);
+        let rotated = manager.sit("Epre").unwrap().unwrap();
+        assert_eq!(rotated.old, sit.new);
+        assert_eq!(rotated.new.pubs, sit.nxt.pubs);
+        assert_eq!(rotated.new.ondices, [Some(0), Some(1)]);
+        assert_eq!((rotated.nxt.ridx, rotated.nxt.kidx), (2, 4));
+
+        let (verfers, _) =
+            manager.rotate("Epre", None, None, None, None, None, None, None).unwrap();
+        assert_eq!(verfers.len(), 3);
+
+        // the seeds of superseded keys are erased
+        let first = path("000").qb64().unwrap();
+        assert_eq!(manager.keeper().signer(&first).unwrap(), None);
+        assert!(manager.keeper().signer(&path("012").qb64().unwrap()).unwrap().is_some());
+
+        // signing by public key
+        let pubs: Vec<String> = verfers.iter().map(|verfer| verfer.qb64().unwrap()).collect();
+        let pubs: Vec<&str> = pubs.iter().map(|pub_| pub_.as_str()).collect();
+        let sigers = manager.sign(b"abc", &pubs, None, None).unwrap();
+        for (i, siger) in sigers.iter().enumerate() {
+            assert_eq!(siger.index(), i as u32);
+            assert!(verfers[i].verify(&siger.raw(), b"abc").unwrap());
+        }
+        let sigers = manager.sign(b"abc", &pubs[..1], Some(&[2]), Some(&[None])).unwrap();
+        assert_eq!(sigers[0].index(), 2);
+        assert_eq!(sigers[0].code(), crate::cesr::indexer::Codex::Ed25519_Crt);
+        let cigars = manager.sign_unindexed(b"abc", &pubs).unwrap();
+        assert!(verfers[2].verify(&cigars[2].raw(), b"abc").unwrap());
+        assert!(manager.sign(b"abc", &[&first], None, None).is_err());
+        assert!(manager.sign(b"abc", &pubs, Some(&[0]), None).is_err());
+
+        // identifiers have their own prefix index, stem or salt
+        let (verfers, _) = manager
+            .incept(None, None, None, None, None, None, None, None, None, None, None, None, None)
+            .unwrap();
+        assert_eq!(verfers, [path("100")]);
+        let (verfers, _) = manager
+            .incept(
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some("x"),
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(verfers, [path("x00")]);
+        assert_eq!(manager.pidx().unwrap(), 3);
+
+        // random and non-transferable keys
+        let (verfers, digers) = manager
+            .incept(
+                None,
+                None,
+                None,
+                None,
+                Some(0),
+                None,
+                None,
+                Some(Algos::randy),
+                None,
+                None,
+                None,
+                Some(false),
+                None,
+            )
+            .unwrap();
+        assert!(digers.is_empty());
+        assert_eq!(verfers[0].code(), matter::Codex::Ed25519N);
+        let pre = verfers[0].qb64().unwrap();
+        assert_eq!(manager.keeper().salter(&pre).unwrap(), None);
+        assert!(manager.rotate(&pre, None, None, None, None, None, None, None).is_err());
+        assert!(manager.rotate("Eunknown", None, None, None, None, None, None, None).is_err());
+
+        // parameters persist with the keeper
+        let store = std::mem::take(manager.keeper_mut().store_mut());
+        let keeper = Keeper::new(store, "0123456789abcdefghijk", Some(Tierage::min)).unwrap();
+        let manager = Manager::new(keeper, None, None, None).unwrap();
+        assert_eq!(manager.pidx().unwrap(), 4);
+        assert_eq!(manager.tier().unwrap(), Tierage::min);
+        assert_eq!(manager.keeper().root_salter().unwrap(), Some(salter));
+        assert!(manager.keeper().store().get("prms", "Epre").unwrap().is_some());
+    }
+
+    #[test]
+    fn exhausted() {
+        let keeper =
+            Keeper::new(MemoryKeystore::new(), "0123456789abcdefghijk", Some(Tierage::min))
+                .unwrap();
+        let mut manager = Manager::new(keeper, None, None, Some(Tierage::min)).unwrap();
+        manager.keeper_mut().store_mut().put("gbls", "pidx", "65535").unwrap();
+
+        // nothing is recorded when the prefix index cannot advance
+        let result = manager
+            .incept(None, None, None, None, None, None, None, None, None, None, None, None, None);
+        assert!(result.is_err());
+        assert!(manager.keeper().pubkeys().unwrap().is_empty());
+        assert!(manager.keeper().prefixes().unwrap().is_empty());
+        assert!(manager.keeper().store().keys("prms").unwrap().is_empty());
+        assert_eq!(manager.pidx().unwrap(), u16::MAX);
+    }
+
+    #[test]
+    fn partial_rotation() {
+        let salter = Salter::new_with_raw(b"0123456789abcdef", None, Some(Tierage::min)).unwrap();
+        let keeper =
+            Keeper::new(MemoryKeystore::new(), "0123456789abcdefghijk", Some(Tierage::min))
+                .unwrap();
+        let salt = salter.qb64().unwrap();
+        let mut manager = Manager::new(keeper, Some(&salt), None, Some(Tierage::min)).unwrap();
+        let path = |path: &str| {
+            salter.signer(None, None, Some(path), Some(Tierage::min), None).unwrap().verfer()
+        };
+
+        let (verfers, digers) = manager
+            .incept(None, None, None, None, Some(3), None, None, None, None, None, None, None, None)
+            .unwrap();
+        let keys = qb64s(&verfers);
+        let ndigs = qb64s(&digers);
+        let icp = incept(
+            &[&keys[0]],
+            None,
+            Some(&ndigs.iter().map(|dig| dig.as_str()).collect::<Vec<_>>()),
+            Some(&dat!(2)),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(matter::Codex::Blake3_256),
+            None,
+            None,
+        )
+        .unwrap();
+        let pre = icp.pre().unwrap();
+        manager.move_prefix(&keys[0], &pre).unwrap();
+        let sit = manager.sit(&pre).unwrap().unwrap();
+        assert_eq!(sit.ondices(), [Some(0)]);
+        let sigers = manager.sign(&icp.raw(), &[&keys[0]], None, Some(&sit.ondices())).unwrap();
+        let mut kever = Kever::new(&icp, &sigers).unwrap();
+
+        assert!(manager
+            .rotate_partial(&pre, Some(&[]), None, None, None, None, None, None, None, None)
+            .is_err());
+        assert!(manager
+            .rotate_partial(&pre, Some(&[3]), None, None, None, None, None, None, None, None)
+            .is_err());
+        assert!(manager
+            .rotate_partial(&pre, Some(&[1, 1]), None, None, None, None, None, None, None, None)
+            .is_err());
+
+        // exposes the third and first next keys, adds a fresh one and holds the second in reserve
+        let (verfers, digers) = manager
+            .rotate_partial(
+                &pre,
+                Some(&[2, 0]),
+                Some(&[matter::Codex::Ed25519_Seed]),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(verfers, [path("013"), path("011"), path("014")]);
+        let reserve = Diger::new_with_ser(&path("012").qb64b().unwrap(), None).unwrap();
+        let fresh = Diger::new_with_ser(&path("025").qb64b().unwrap(), None).unwrap();
+        assert_eq!(digers, [reserve, fresh]);
+        let sit = manager.sit(&pre).unwrap().unwrap();
+        assert_eq!(sit.ondices(), [Some(2), Some(0), None]);
+        assert_eq!(sit.nxt.pubs[0], path("012").qb64().unwrap());
+        assert_eq!((sit.nxt.ridx, sit.nxt.kidx), (2, 5));
+
+        let keys = qb64s(&verfers);
+        let keys: Vec<&str> = keys.iter().map(|key| key.as_str()).collect();
+        let ndigs = qb64s(&digers);
+        let rot = rotate(
+            &pre,
+            &keys,
+            &icp.said().unwrap(),
+            None,
+            Some(1),
+            Some(&dat!(2)),
+            Some(&ndigs.iter().map(|dig| dig.as_str()).collect::<Vec<_>>()),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let sigers = manager.sign(&rot.raw(), &keys, None, Some(&sit.ondices())).unwrap();
+        assert_eq!(sigers[0].code(), indexer::Codex::Ed25519_Big);
+        assert_eq!(sigers[2].code(), indexer::Codex::Ed25519_Crt);
+        let plain = manager.sign(&rot.raw(), &keys, None, None).unwrap();
+        assert!(kever.clone().update(&rot, &plain).is_err());
+        kever.update(&rot, &sigers).unwrap();
+
+        // the reserve key is exposed by the next rotation
+        let (verfers, _) = manager.rotate(&pre, None, None, None, None, None, None, None).unwrap();
+        assert_eq!(verfers, [path("012"), path("025")]);
+        assert_eq!(manager.sit(&pre).unwrap().unwrap().ondices(), [Some(0), Some(1)]);
+    }
+}
@@ -1 +1,5 @@
 pub mod creating;
+#[cfg(feature = "eventing")]
+pub mod habbing;
+pub mod keeping;
+pub mod managing;